@@ -0,0 +1,117 @@
+use circkit::{
+    canonicalize::{canonicalize, hash_canonical, lmsr},
+    monomerize::Monomerizer,
+    orfs::find_orfs,
+};
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use std::{collections::hash_map::DefaultHasher, hash::Hasher};
+
+/// A deterministic pseudo-random DNA sequence, so that runs are comparable.
+fn random_seq(len: usize, seed: u64) -> Vec<u8> {
+    let mut state = seed;
+    (0..len)
+        .map(|_| {
+            // a 64-bit LCG (Knuth's MMIX constants); the high bits are the most random
+            state = state
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            b"ACGT"[(state >> 62) as usize]
+        })
+        .collect()
+}
+
+const LENGTHS: [usize; 3] = [100, 1_000, 10_000];
+
+fn bench_lmsr(c: &mut Criterion) {
+    let mut group = c.benchmark_group("lmsr");
+    for len in LENGTHS {
+        let seq = random_seq(len, 1);
+        group.bench_with_input(BenchmarkId::from_parameter(len), &seq, |b, seq| {
+            b.iter(|| lmsr(black_box(seq)))
+        });
+    }
+    group.finish();
+}
+
+fn bench_canonicalize(c: &mut Criterion) {
+    let mut group = c.benchmark_group("canonicalize");
+    for len in LENGTHS {
+        let seq = random_seq(len, 2);
+        group.bench_with_input(BenchmarkId::from_parameter(len), &seq, |b, seq| {
+            b.iter(|| canonicalize(black_box(seq)))
+        });
+    }
+    group.finish();
+}
+
+/// Hashing the canonical form by building it first, as `uniq` used to, against streaming it into the hasher.
+fn bench_canonical_hash(c: &mut Criterion) {
+    let mut group = c.benchmark_group("canonical_hash");
+    for len in LENGTHS {
+        let seq = random_seq(len, 5);
+        group.bench_with_input(BenchmarkId::new("allocating", len), &seq, |b, seq| {
+            b.iter(|| {
+                let mut hasher = DefaultHasher::new();
+                hasher.write(&canonicalize(black_box(seq)));
+                hasher.finish()
+            })
+        });
+        group.bench_with_input(BenchmarkId::new("streaming", len), &seq, |b, seq| {
+            b.iter(|| {
+                let mut hasher = DefaultHasher::new();
+                hash_canonical(black_box(seq), &mut hasher);
+                hasher.finish()
+            })
+        });
+    }
+    group.finish();
+}
+
+fn bench_monomerize(c: &mut Criterion) {
+    let monomerizer = Monomerizer::builder().seed_len(10).build().unwrap();
+    let mut group = c.benchmark_group("monomerize");
+    for len in LENGTHS {
+        // a dimer, so that the overlap is found and checked
+        let monomer = random_seq(len / 2, 3);
+        let seq = [&monomer[..], &monomer[..]].concat();
+        group.bench_with_input(BenchmarkId::from_parameter(len), &seq, |b, seq| {
+            b.iter(|| monomerizer.monomerize(black_box(seq)))
+        });
+    }
+    group.finish();
+}
+
+fn bench_monomerize_tandem_array(c: &mut Criterion) {
+    let monomerizer = Monomerizer::builder().seed_len(10).build().unwrap();
+    let mut group = c.benchmark_group("monomerize_tandem_array");
+    for len in LENGTHS {
+        // many copies of a low-complexity monomer, so that the seed occurs over and over
+        let seq = [&b"A".repeat(19)[..], b"C"].concat().repeat(len / 20);
+        group.bench_with_input(BenchmarkId::from_parameter(len), &seq, |b, seq| {
+            b.iter(|| monomerizer.monomerize(black_box(seq)))
+        });
+    }
+    group.finish();
+}
+
+fn bench_find_orfs(c: &mut Criterion) {
+    let mut group = c.benchmark_group("find_orfs");
+    for len in LENGTHS {
+        let seq = String::from_utf8(random_seq(len, 4)).unwrap();
+        group.bench_with_input(BenchmarkId::from_parameter(len), &seq, |b, seq| {
+            b.iter(|| find_orfs(black_box(seq)))
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    bench_lmsr,
+    bench_canonicalize,
+    bench_canonical_hash,
+    bench_monomerize,
+    bench_monomerize_tandem_array,
+    bench_find_orfs
+);
+criterion_main!(benches);
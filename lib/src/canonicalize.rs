@@ -1,10 +1,21 @@
 use bio::alphabets;
+use std::hash::Hasher;
 
 /// compute index of the lexicographically minimal string rotation of a string.
 /// https://codeforces.com/blog/entry/90035#duval
-pub fn lmsr_index(x: &[u8]) -> usize {
-    let s = std::str::from_utf8(x).unwrap();
-    let n: isize = s.len().try_into().unwrap();
+///
+/// Any ordered symbols can be rotated, not just bytes, so this works equally well for proteins or annotation tokens.
+/// An empty string has no rotations, so its index is 0.
+pub fn lmsr_index<T: Ord>(x: &[T]) -> usize {
+    lmsr_index_by(x.len(), |i| &x[i])
+}
+
+/// Compute [`lmsr_index`] of a string of length `n` whose symbols are given by `at`, so that e.g. a reverse complement doesn't have to be built first.
+fn lmsr_index_by<T: Ord>(n: usize, at: impl Fn(usize) -> T) -> usize {
+    if n == 0 {
+        return 0;
+    }
+    let n: isize = n.try_into().unwrap();
     let mut res: isize = 0;
     let mut l: isize = 0;
 
@@ -14,15 +25,17 @@ pub fn lmsr_index(x: &[u8]) -> usize {
         let mut p: isize = l + 1;
 
         while r < n {
+            // p runs past the end at most once, so wrapping around is a single subtraction
             let c = if p < n {
-                s.chars().nth(p.try_into().unwrap()).unwrap()
+                at(p as usize)
             } else {
-                s.chars().nth((p - n).try_into().unwrap()).unwrap()
+                at((p - n) as usize)
             };
-            if s.chars().nth(r.try_into().unwrap()).unwrap() > c {
+            let x = at(r as usize);
+            if x > c {
                 break;
             }
-            if s.chars().nth(r.try_into().unwrap()).unwrap() < c {
+            if x < c {
                 r = l - 1;
             }
             r += 1;
@@ -37,9 +50,9 @@ pub fn lmsr_index(x: &[u8]) -> usize {
 
 /// Compute the lexicographically minimal string rotation of a string.
 ///
-/// Internally, this function computes the index of the LMSR and then converts it to a `Vec<u8>`.
-pub fn lmsr(s: &[u8]) -> Vec<u8> {
-    let mut buf = Vec::<u8>::with_capacity(s.len());
+/// Internally, this function computes the index of the LMSR and then converts it to a `Vec`.
+pub fn lmsr<T: Ord + Clone>(s: &[T]) -> Vec<T> {
+    let mut buf = Vec::<T>::with_capacity(s.len());
     let i = lmsr_index(s);
     buf.extend_from_slice(&s[i..]);
     buf.extend_from_slice(&s[..i]);
@@ -52,16 +65,238 @@ pub fn lmsr(s: &[u8]) -> Vec<u8> {
 /// Ensure that the input is a valid DNA sequence before calling this function.
 /// Non-ATGC characters will be treated normally, meaning that they too will be used when sorting lexicographically.
 pub fn canonicalize(s: &[u8]) -> Vec<u8> {
-    let lmsr_s = lmsr(s);
-    let lmsr_revcomp_s = lmsr(&alphabets::dna::revcomp(&lmsr_s));
+    canonicalize_with_info(s).0
+}
+
+/// How [`canonicalize_with_info`] got from a sequence to its canonical form.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CanonInfo {
+    /// The index in the original (forward) sequence of the base that becomes position 0 of the canonical sequence.
+    /// If the reverse complement was chosen, position 0 is the complement of this base.
+    pub offset: usize,
+    /// Whether the canonical sequence is a rotation of the reverse complement
+    pub reverse_complemented: bool,
+}
+
+/// Canonicalize a circular DNA sequence like [`canonicalize`], also returning where the canonical sequence starts in the input.
+///
+/// Unlike [`canonicalize_with_offset`], the offset is always in forward strand coordinates, which is what lifting annotations from the input onto the canonical sequence needs.
+/// On the reverse complement, the canonical sequence reads backwards from the offset.
+pub fn canonicalize_with_info(s: &[u8]) -> (Vec<u8>, CanonInfo) {
+    let (canonical, index, reverse_complemented) = canonicalize_with_offset(s);
+    let offset = match reverse_complemented {
+        // the reverse complement is only chosen if it is smaller, so s isn't empty
+        true => s.len() - 1 - index,
+        false => index,
+    };
+    (
+        canonical,
+        CanonInfo {
+            offset,
+            reverse_complemented,
+        },
+    )
+}
+
+/// Canonicalize a circular DNA sequence onto its purine-rich strand.
+///
+/// The strand with more purines (A and G) than pyrimidines is chosen and its lexicographically minimal string rotation is returned,
+/// so the result starts with a purine whenever that strand contains an A.
+/// If both strands are equally purine-rich, this is the same as [`canonicalize`].
+pub fn canonicalize_purine_strand(s: &[u8]) -> Vec<u8> {
+    let count = |bases: &[u8]| {
+        s.iter()
+            .filter(|b| bases.contains(&b.to_ascii_uppercase()))
+            .count()
+    };
+
+    // the purines of the reverse complement are the pyrimidines of the forward strand
+    match count(b"AG").cmp(&count(b"CT")) {
+        std::cmp::Ordering::Greater => lmsr(s),
+        std::cmp::Ordering::Less => lmsr(&alphabets::dna::revcomp(s)),
+        std::cmp::Ordering::Equal => canonicalize(s),
+    }
+}
+
+/// Canonicalize a circular DNA sequence, also returning the rotation that was applied.
+///
+/// The returned offset is the index in the chosen strand at which the canonical sequence starts,
+/// and the returned boolean is true if the chosen strand is the reverse complement of the input.
+/// That is, the canonical sequence is `strand[offset..]` followed by `strand[..offset]`.
+/// The sequence is always identical to the result of [`canonicalize`]; if both strands give the same sequence, the forward strand is reported.
+pub fn canonicalize_with_offset(s: &[u8]) -> (Vec<u8>, usize, bool) {
+    let forward_index = lmsr_index(s);
+    let revcomp_s = alphabets::dna::revcomp(s);
+    let revcomp_index = lmsr_index(&revcomp_s);
+
+    let rotate = |x: &[u8], i: usize| [&x[i..], &x[..i]].concat();
+    let lmsr_s = rotate(s, forward_index);
+    let lmsr_revcomp_s = rotate(&revcomp_s, revcomp_index);
+
+    if lmsr_s <= lmsr_revcomp_s {
+        (lmsr_s, forward_index, false)
+    } else {
+        (lmsr_revcomp_s, revcomp_index, true)
+    }
+}
+
+/// Feed the canonical form of a circular DNA sequence, as returned by [`canonicalize`], to `hasher` without allocating it.
+///
+/// Returns whether the canonical form is a rotation of the reverse complement.
+/// The sequence is written in several pieces, so the hash is only the same as hashing the canonical sequence in one go
+/// for hashers that only depend on the bytes written, such as streaming xxh3 (but not `std`'s `Hash` for slices, which also writes the length).
+pub fn hash_canonical<H: Hasher>(s: &[u8], hasher: &mut H) -> bool {
+    let n = s.len();
+    let revcomp_at = |i: usize| alphabets::dna::complement(s[n - 1 - i]);
+    let forward_index = lmsr_index(s);
+    let revcomp_index = lmsr_index_by(n, revcomp_at);
+
+    // position i of the reverse complement is the complement of position n - 1 - i, so its rotation reads s backwards from there
+    let split = n - revcomp_index;
+    let revcomp = || {
+        s[..split]
+            .iter()
+            .rev()
+            .chain(s[split..].iter().rev())
+            .map(|&base| alphabets::dna::complement(base))
+    };
+    if s[forward_index..]
+        .iter()
+        .chain(&s[..forward_index])
+        .copied()
+        .le(revcomp())
+    {
+        hasher.write(&s[forward_index..]);
+        hasher.write(&s[..forward_index]);
+        return false;
+    }
+
+    // the reverse complement is built a chunk at a time instead
+    let mut buf = [0; 256];
+    let mut bases = revcomp();
+    loop {
+        let len = buf
+            .iter_mut()
+            .zip(&mut bases)
+            .map(|(b, base)| *b = base)
+            .count();
+        if len == 0 {
+            break;
+        }
+        hasher.write(&buf[..len]);
+    }
+    true
+}
+
+/// Canonicalize a linear DNA sequence.
+///
+/// Unlike [`canonicalize`], the sequence is never rotated: the lexicographically smaller of the sequence and its reverse complement is returned.
+/// If both strands are identical, the input is returned unchanged.
+pub fn canonicalize_linear(s: &[u8]) -> Vec<u8> {
+    let revcomp_s = alphabets::dna::revcomp(s);
 
-    if lmsr_s < lmsr_revcomp_s {
-        lmsr_s
+    if revcomp_s.as_slice() < s {
+        revcomp_s
     } else {
-        lmsr_revcomp_s
+        s.to_vec()
     }
 }
 
+/// Canonicalize a circular sequence like [`canonicalize_with_offset`], but comparing bases by their rank instead of their byte values.
+///
+/// `rank` maps each base to the (ASCII) byte it sorts as, and `complement` gives the complement of each base in the sequence's own alphabet.
+/// The returned sequence is made of the original bases, not their ranks.
+pub fn canonicalize_with_order(
+    s: &[u8],
+    rank: impl Fn(u8) -> u8,
+    complement: impl Fn(u8) -> u8,
+) -> (Vec<u8>, usize, bool) {
+    let revcomp_s = s.iter().rev().map(|&b| complement(b)).collect::<Vec<_>>();
+    let ranked = |x: &[u8]| x.iter().map(|&b| rank(b)).collect::<Vec<_>>();
+    let (ranked_s, ranked_revcomp_s) = (ranked(s), ranked(&revcomp_s));
+    let forward_index = lmsr_index(&ranked_s);
+    let revcomp_index = lmsr_index(&ranked_revcomp_s);
+
+    let rotate = |x: &[u8], i: usize| [&x[i..], &x[..i]].concat();
+    if rotate(&ranked_s, forward_index) <= rotate(&ranked_revcomp_s, revcomp_index) {
+        (rotate(s, forward_index), forward_index, false)
+    } else {
+        (rotate(&revcomp_s, revcomp_index), revcomp_index, true)
+    }
+}
+
+/// The rank of an RNA base, for use with [`canonicalize_with_order`]: U sorts where T would.
+pub fn rna_rank(base: u8) -> u8 {
+    match base {
+        b'U' => b'T',
+        b'u' => b't',
+        _ => base,
+    }
+}
+
+/// The complement of an RNA base, for use with [`canonicalize_with_order`]: A pairs with U.
+pub fn rna_complement(base: u8) -> u8 {
+    match base {
+        b'A' => b'U',
+        b'a' => b'u',
+        b'U' => b'A',
+        b'u' => b'a',
+        _ => alphabets::dna::complement(base),
+    }
+}
+
+/// Canonicalize a circular RNA sequence.
+///
+/// [`canonicalize`] compares raw bytes and complements A as T, so it would treat an RNA sequence differently from its DNA equivalent.
+/// Here, U is ranked where T would be and A is complemented as U, so the result is always the canonical form of the DNA equivalent, with T written as U.
+pub fn canonicalize_rna(s: &[u8]) -> Vec<u8> {
+    canonicalize_with_order(s, rna_rank, rna_complement).0
+}
+
+/// The nucleic acid alphabet of a sequence, which decides how its reverse complement is taken.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Alphabet {
+    #[default]
+    Dna,
+    Rna,
+}
+
+/// Canonicalize a circular sequence in the given alphabet.
+///
+/// DNA is canonicalized with [`canonicalize`] and RNA with [`canonicalize_rna`], so U is complemented to A rather than left as is.
+pub fn canonicalize_with_alphabet(s: &[u8], alphabet: Alphabet) -> Vec<u8> {
+    match alphabet {
+        Alphabet::Dna => canonicalize(s),
+        Alphabet::Rna => canonicalize_rna(s),
+    }
+}
+
+/// Map a base to the alphabet [`canonicalize_iupac`] compares in: uppercase A, C, G, T, or N for everything else.
+///
+/// Every ambiguity code collapses to N, since the complement of an ambiguity code is another one, so the mapping works the same on both strands.
+pub fn iupac_collapse(base: u8) -> u8 {
+    match base.to_ascii_uppercase() {
+        b'U' => b'T',
+        b @ (b'A' | b'C' | b'G' | b'T') => b,
+        _ => b'N',
+    }
+}
+
+/// Canonicalize a circular DNA sequence, treating all ambiguity codes as the same.
+///
+/// Case is normalized, U is written as T, and each IUPAC ambiguity code (R, Y, N, etc.) or any other character becomes N before computing the canonical form.
+/// Sequences that only differ in their ambiguity codes, or in case, therefore canonicalize identically.
+///
+/// This is lossy: the result doesn't say which ambiguity code was at each position, so it can't be mapped back to the input symbols.
+/// A concrete base and an ambiguity code are still different (an N never matches an A), since treating them as wildcards would make equality intransitive and the canonical form ill-defined.
+pub fn canonicalize_iupac(s: &[u8]) -> Vec<u8> {
+    let collapsed = s
+        .iter()
+        .map(|&base| iupac_collapse(base))
+        .collect::<Vec<_>>();
+    canonicalize(&collapsed)
+}
+
 #[cfg(test)]
 mod lmsr_index_test {
     use super::*;
@@ -80,12 +315,23 @@ mod lmsr_index_test {
     fn taa() {
         assert_eq!(lmsr_index(b"TAA"), 1);
     }
+
+    #[test]
+    fn tokens() {
+        assert_eq!(lmsr_index(&["ori", "gfp", "amp"]), 2);
+        assert_eq!(lmsr(&[3u32, 1, 2, 1, 1]), [1, 1, 3, 1, 2]);
+    }
 }
 
 #[cfg(test)]
 mod lmsr_test {
     use super::*;
     #[test]
+    fn empty() {
+        assert_eq!(lmsr_index(b""), 0);
+        assert_eq!(lmsr(b""), b"");
+    }
+    #[test]
     fn aaa() {
         assert_eq!(lmsr(b"AAA"), b"AAA");
     }
@@ -110,6 +356,10 @@ mod lmsr_test {
 mod canonicalize_test {
     use super::*;
     #[test]
+    fn empty() {
+        assert_eq!(canonicalize(b""), b"");
+    }
+    #[test]
     fn aaa() {
         assert_eq!(canonicalize(b"AAA"), b"AAA");
     }
@@ -132,6 +382,183 @@ mod canonicalize_test {
     }
 }
 
+#[cfg(test)]
+mod canonicalize_purine_strand_test {
+    use super::*;
+    #[test]
+    fn purine_rich_forward() {
+        assert_eq!(canonicalize_purine_strand(b"GAGC"), b"AGCG");
+    }
+    #[test]
+    fn purine_rich_reverse() {
+        // the reverse complement of CTCG is CGAG
+        assert_eq!(canonicalize_purine_strand(b"CTCG"), b"AGCG");
+    }
+    #[test]
+    fn balanced_is_canonical() {
+        assert_eq!(canonicalize_purine_strand(b"ATGC"), canonicalize(b"ATGC"));
+    }
+}
+
+#[cfg(test)]
+mod canonicalize_with_offset_test {
+    use super::*;
+    #[test]
+    fn forward() {
+        assert_eq!(
+            canonicalize_with_offset(b"TAA"),
+            (b"AAT".to_vec(), 1, false)
+        );
+    }
+    #[test]
+    fn reverse() {
+        // the reverse complement of TTGCA is TGCAA
+        assert_eq!(
+            canonicalize_with_offset(b"TTGCA"),
+            (b"AATGC".to_vec(), 3, true)
+        );
+    }
+    #[test]
+    fn palindrome_is_forward() {
+        assert_eq!(
+            canonicalize_with_offset(b"ATAT"),
+            (b"ATAT".to_vec(), 0, false)
+        );
+    }
+}
+
+#[cfg(test)]
+mod canonicalize_with_info_test {
+    use super::*;
+    #[test]
+    fn forward() {
+        assert_eq!(
+            canonicalize_with_info(b"TAA"),
+            (
+                b"AAT".to_vec(),
+                CanonInfo {
+                    offset: 1,
+                    reverse_complemented: false
+                }
+            )
+        );
+    }
+    #[test]
+    fn reverse() {
+        // AATGC reads backwards from the T at index 1 of TTGCA, complemented
+        assert_eq!(
+            canonicalize_with_info(b"TTGCA"),
+            (
+                b"AATGC".to_vec(),
+                CanonInfo {
+                    offset: 1,
+                    reverse_complemented: true
+                }
+            )
+        );
+    }
+    #[test]
+    fn empty() {
+        assert_eq!(
+            canonicalize_with_info(b""),
+            (
+                Vec::new(),
+                CanonInfo {
+                    offset: 0,
+                    reverse_complemented: false
+                }
+            )
+        );
+    }
+}
+
+#[cfg(test)]
+mod canonicalize_linear_test {
+    use super::*;
+    #[test]
+    fn forward() {
+        assert_eq!(canonicalize_linear(b"AATGC"), b"AATGC");
+    }
+    #[test]
+    fn reverse() {
+        // the reverse complement of TTGCA is TGCAA
+        assert_eq!(canonicalize_linear(b"TTGCA"), b"TGCAA");
+    }
+    #[test]
+    fn is_not_rotated() {
+        // the circular canonical form starts with AA, but a linear sequence must keep its ends
+        assert_eq!(canonicalize_linear(b"GCAAT"), b"ATTGC");
+        assert_eq!(canonicalize(b"GCAAT"), b"AATGC");
+    }
+}
+
+#[cfg(test)]
+mod canonicalize_rna_test {
+    use super::*;
+    #[test]
+    fn reverse() {
+        // the DNA equivalent is TTGCA, which canonicalizes to AATGC
+        assert_eq!(canonicalize_rna(b"UUGCA"), b"AAUGC");
+    }
+    #[test]
+    fn no_t_in_reverse_complement() {
+        // the DNA reverse complement of UUGCA is TGCAA, which mixes T into an RNA sequence
+        assert_eq!(canonicalize_rna(b"UUGCAA"), b"AAUUGC");
+        assert_eq!(canonicalize(b"TTGCAA"), b"AATTGC");
+    }
+    #[test]
+    fn reports_rotation() {
+        assert_eq!(
+            canonicalize_with_order(b"UUGCA", rna_rank, rna_complement),
+            (b"AAUGC".to_vec(), 3, true)
+        );
+    }
+}
+
+#[cfg(test)]
+mod canonicalize_with_alphabet_test {
+    use super::*;
+    #[test]
+    fn rna_reverse_complement() {
+        // treated as DNA, U is its own complement and the reverse complement is never chosen
+        assert_eq!(
+            canonicalize_with_alphabet(b"UUGCA", Alphabet::Dna),
+            b"AUUGC"
+        );
+        assert_eq!(
+            canonicalize_with_alphabet(b"UUGCA", Alphabet::Rna),
+            b"AAUGC"
+        );
+    }
+
+    #[test]
+    fn dna_is_default() {
+        assert_eq!(
+            canonicalize_with_alphabet(b"TTGCA", Alphabet::default()),
+            canonicalize(b"TTGCA")
+        );
+    }
+}
+
+#[cfg(test)]
+mod canonicalize_iupac_test {
+    use super::*;
+    #[test]
+    fn ambiguity_codes_collapse() {
+        assert_eq!(canonicalize_iupac(b"ACGRT"), canonicalize_iupac(b"ACGYT"));
+        assert_eq!(canonicalize_iupac(b"ACGRT"), canonicalize(b"ACGNT"));
+        assert_ne!(canonicalize(b"ACGRT"), canonicalize(b"ACGYT"));
+    }
+    #[test]
+    fn case_and_u() {
+        assert_eq!(canonicalize_iupac(b"acgun"), canonicalize(b"ACGTN"));
+    }
+    #[test]
+    fn bases_are_not_wildcards() {
+        assert_ne!(canonicalize_iupac(b"ACGAT"), canonicalize_iupac(b"ACGNT"));
+    }
+}
+
 #[cfg(test)]
 /// We have multiple implementations of lmsr_index, so we can compare them against each other to make sure the optimized version is correct
 mod fuzzing {
@@ -141,14 +568,7 @@ mod fuzzing {
     /// An auxiliary function to rotate a string by n characters
     fn rotate(s: &str, n: usize) -> String {
         let s = s.chars().collect::<Vec<char>>();
-        let mut res = String::new();
-        for i in n..s.len() {
-            res.push(s[i]);
-        }
-        for i in 0..n {
-            res.push(s[i]);
-        }
-        res
+        s[n..].iter().chain(&s[..n]).collect()
     }
 
     fn lmsr_index_simple(s: &str) -> usize {
@@ -220,6 +640,16 @@ mod fuzzing {
             prop_assert_eq!(lmsr_index_2(&s), lmsr_index(s.as_bytes()));
         }
 
+        #[test]
+        fn lmsr_index_is_alphabet_independent(s in proptest::collection::vec(0u16..1000, 1..100)) {
+            // squashing the symbols into bytes in an order-preserving way must not move the minimal rotation
+            let mut symbols = s.clone();
+            symbols.sort_unstable();
+            symbols.dedup();
+            let bytes = s.iter().map(|x| symbols.binary_search(x).unwrap() as u8).collect::<Vec<_>>();
+            prop_assert_eq!(lmsr_index(&s), lmsr_index(&bytes));
+        }
+
         #[test]
         fn lmsr_is_idempotent(s in "[ -~]{1, 100}") {
             prop_assert_eq!(lmsr(&lmsr(s.as_bytes())), lmsr(s.as_bytes()));
@@ -228,5 +658,70 @@ mod fuzzing {
         fn canonicalize_is_idempotent(s in "[ATGC]{1, 100}") {
             prop_assert_eq!(canonicalize(&canonicalize(s.as_bytes())), canonicalize(s.as_bytes()));
         }
+        #[test]
+        fn canonicalize_linear_is_strand_independent(s in "[ATGC]{1, 100}") {
+            let linear = canonicalize_linear(s.as_bytes());
+            prop_assert_eq!(&linear, &canonicalize_linear(&alphabets::dna::revcomp(s.as_bytes())));
+            prop_assert!(linear == s.as_bytes() || linear == alphabets::dna::revcomp(s.as_bytes()));
+        }
+        #[test]
+        fn rna_canonicalizes_like_dna(s in "[ACGT]{1, 100}") {
+            let transcribe = |x: &[u8]| x.iter().map(|&b| if b == b'T' { b'U' } else { b }).collect::<Vec<_>>();
+            prop_assert_eq!(canonicalize_rna(&transcribe(s.as_bytes())), transcribe(&canonicalize(s.as_bytes())));
+        }
+        #[test]
+        fn canonicalize_with_offset_describes_rotation(s in "[ATGC]{1, 100}") {
+            let (canonical, offset, reverse) = canonicalize_with_offset(s.as_bytes());
+            prop_assert_eq!(&canonical, &canonicalize(s.as_bytes()));
+
+            let strand = match reverse {
+                true => alphabets::dna::revcomp(s.as_bytes()),
+                false => s.as_bytes().to_vec(),
+            };
+            prop_assert_eq!([&strand[offset..], &strand[..offset]].concat(), canonical);
+        }
+        #[test]
+        fn canonicalize_iupac_is_strand_independent(s in "[ACGTRYSWKMBDHVNacgtn]{1, 100}") {
+            prop_assert_eq!(
+                canonicalize_iupac(s.as_bytes()),
+                canonicalize_iupac(&alphabets::dna::revcomp(s.as_bytes()))
+            );
+        }
+        #[test]
+        fn canonicalize_with_info_points_into_input(s in "[ATGC]{1, 100}") {
+            let (canonical, info) = canonicalize_with_info(s.as_bytes());
+            let s = s.as_bytes();
+            let n = s.len();
+
+            // walk the input from the offset, forwards or backwards depending on the strand
+            let lifted = (0..n)
+                .map(|i| match info.reverse_complemented {
+                    true => alphabets::dna::complement(s[(info.offset + n - i) % n]),
+                    false => s[(info.offset + i) % n],
+                })
+                .collect::<Vec<_>>();
+            prop_assert_eq!(lifted, canonical);
+        }
+        #[test]
+        fn hash_canonical_writes_canonical_sequence(s in "[ATGCN]{0, 600}") {
+            let mut bytes = Bytes::default();
+            let reverse = hash_canonical(s.as_bytes(), &mut bytes);
+            let (canonical, _, expected_reverse) = canonicalize_with_offset(s.as_bytes());
+            prop_assert_eq!(bytes.0, canonical);
+            prop_assert_eq!(reverse, expected_reverse);
+        }
+    }
+
+    /// A "hasher" that keeps everything written to it.
+    #[derive(Default)]
+    struct Bytes(Vec<u8>);
+
+    impl std::hash::Hasher for Bytes {
+        fn finish(&self) -> u64 {
+            0
+        }
+        fn write(&mut self, bytes: &[u8]) {
+            self.0.extend_from_slice(bytes);
+        }
     }
 }
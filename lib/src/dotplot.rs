@@ -0,0 +1,63 @@
+use std::collections::HashMap;
+
+/// Find every pair of positions in a circular sequence at which the same k-mer starts, for plotting the sequence against itself.
+///
+/// The k-mers are read around the origin, so there is one per position.
+/// Pairs are returned in both orders, sorted by the first position, and a position is never paired with itself.
+/// Repeats show up as diagonals offset from the main one by the repeat's period.
+/// Nothing is returned if `k` is 0 or longer than the sequence.
+pub fn self_matches(seq: &[u8], k: usize) -> Vec<(usize, usize)> {
+    let n = seq.len();
+    if k == 0 || k > n {
+        return Vec::new();
+    }
+
+    // a k-mer starting near the end continues from the start
+    let doubled = [seq, &seq[..k - 1]].concat();
+    let mut positions = HashMap::<&[u8], Vec<usize>>::new();
+    for i in 0..n {
+        positions.entry(&doubled[i..i + k]).or_default().push(i);
+    }
+
+    let mut matches = Vec::new();
+    for i in 0..n {
+        matches.extend(
+            positions[&doubled[i..i + k]]
+                .iter()
+                .filter(|&&j| j != i)
+                .map(|&j| (i, j)),
+        );
+    }
+    matches
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rstest::rstest;
+
+    #[test]
+    fn tandem_repeat() {
+        let seq = b"ACGTTGCAA".repeat(3);
+        let matches = self_matches(&seq, 5);
+        // every position matches the same position in the other two copies
+        assert_eq!(matches.len(), 2 * seq.len());
+        assert!(matches
+            .iter()
+            .all(|&(i, j)| (j + seq.len() - i).is_multiple_of(9) && i != j));
+    }
+
+    #[test]
+    fn across_origin() {
+        // the AGT at 8 wraps around to the start
+        assert_eq!(self_matches(b"GTCACAGTA", 3), vec![(5, 8), (8, 5)]);
+    }
+
+    #[rstest]
+    #[case(b"", 1)]
+    #[case(b"ACGT", 0)]
+    #[case(b"ACGT", 5)]
+    fn nothing_to_match(#[case] seq: &[u8], #[case] k: usize) {
+        assert!(self_matches(seq, k).is_empty());
+    }
+}
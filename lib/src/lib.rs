@@ -1,7 +1,15 @@
 pub mod canonicalize;
+pub mod dotplot;
 pub mod monomerize;
-pub use crate::canonicalize::canonicalize;
-pub use crate::monomerize::Monomerizer;
+pub use crate::canonicalize::{
+    canonicalize, canonicalize_iupac, canonicalize_linear, canonicalize_purine_strand,
+    canonicalize_rna, canonicalize_with_alphabet, canonicalize_with_info, canonicalize_with_offset,
+    canonicalize_with_order, hash_canonical, Alphabet, CanonInfo,
+};
+pub use crate::monomerize::{
+    auto_seed_len, MonomerizedRecord, Monomerizer, Overlap, OverlapCombine, OverlapMetric,
+};
 pub mod orfs;
+pub mod period;
 #[macro_use]
 extern crate derive_builder;
@@ -1,27 +1,122 @@
-use bio::alignment::distance::simd::*;
+use bio::alignment::distance::simd::{self, bounded_levenshtein};
 use bio::alphabets::dna;
+use bio::io::fasta;
 use bio::pattern_matching::shift_and;
 use log::{debug, warn};
+use std::io::{self, Read};
+
+/// How to combine `overlap_dist` and `overlap_min_identity` when both are set.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum OverlapCombine {
+    /// Allow the fewer of the two numbers of mismatches, so an overlap has to pass both cutoffs
+    #[default]
+    Stricter,
+    /// Allow the greater of the two numbers of mismatches, so an overlap has to pass either cutoff
+    Looser,
+}
+
+/// How to count the differences between the two copies in an overlap.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum OverlapMetric {
+    /// Count mismatched positions, which is fast but treats everything after an insertion or deletion as mismatched
+    #[default]
+    Hamming,
+    /// Count the edits (substitutions, insertions, and deletions) between the copies, which tolerates indels, e.g. in noisy long reads.
+    /// Both copies are still compared over the same length, so the monomer boundary is off by the net length of any indels
+    Edit,
+}
+
+/// The terminal repeat that ends a monomer, as found by [`Monomerizer::monomerize_detailed`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Overlap {
+    /// The end index of the monomer, i.e. its length
+    pub monomer_end: usize,
+    /// The index at which the repeat of the start of the sequence begins
+    pub overlap_start: usize,
+    /// The length of the repeat, which is compared to the first `overlap_len` bases of the sequence
+    pub overlap_len: usize,
+    /// The number of differences between the repeat and the start of the sequence, as counted by `overlap_metric`
+    pub mismatches: u64,
+}
+
+/// A record read by [`Monomerizer::monomerize_records`], along with where its monomer ends.
+#[derive(Debug, Clone)]
+pub struct MonomerizedRecord {
+    /// The record as it was read
+    pub record: fasta::Record,
+    /// The end index of the monomer, or `None` if the record isn't a multimer
+    pub end: Option<usize>,
+}
+
+impl MonomerizedRecord {
+    /// The monomer, cut from the record as it was read, or the whole sequence if it isn't a multimer.
+    pub fn monomer(&self) -> &[u8] {
+        let seq = self.record.seq();
+        &seq[..self.end.unwrap_or(seq.len())]
+    }
+}
 
 #[derive(Builder, Default, Clone, Copy)]
 #[builder(setter(strip_option), build_fn(validate = "Self::validate"))]
 pub struct Monomerizer {
-    /// The maximum number of mismatches allowed in an overlap. Conflicts with `overlap_min_identity`.
+    /// The maximum number of mismatches allowed in an overlap. Conflicts with `overlap_mismatch_rate`.
     #[builder(default)]
     pub overlap_dist: Option<u64>,
-    /// The minimum percent identity within an overlap that may be considered a match. Conflicts with `overlap_dist`.
+    /// The minimum percent identity within an overlap that may be considered a match. Conflicts with `overlap_mismatch_rate`.
     #[builder(default)]
     pub overlap_min_identity: Option<f64>,
-    /// The size of the seed to search for in the overlap.
+    /// The maximum number of mismatches allowed in an overlap, as a fraction of the overlap length. Conflicts with `overlap_dist` and `overlap_min_identity`.
+    #[builder(default)]
+    pub overlap_mismatch_rate: Option<f64>,
+    /// How to combine `overlap_dist` and `overlap_min_identity` when both are set.
+    #[builder(default, setter(strip_option = false))]
+    pub overlap_combine: OverlapCombine,
+    /// How to count the differences in an overlap, which are then compared to the maximum distance.
+    #[builder(default, setter(strip_option = false))]
+    pub overlap_metric: OverlapMetric,
+    /// Whether IUPAC ambiguity codes (including N) match any base they could stand for, both when searching for the seed and when counting mismatches.
+    /// This is slower than the default exact matching. It only changes the Hamming metric, since the edit distance compares bases exactly
+    #[builder(default, setter(strip_option = false))]
+    pub iupac_aware: bool,
+    /// The shortest monomer to accept. Shorter monomers are rejected, leaving the sequence as it is.
+    #[builder(default)]
+    pub min_monomer_len: Option<usize>,
+    /// The longest monomer to accept. Longer monomers are rejected, leaving the sequence as it is.
+    #[builder(default)]
+    pub max_monomer_len: Option<usize>,
+    /// The size of the seed to search for in the overlap. May be left unset with `auto_seed_len`.
+    #[builder(default)]
     pub seed_len: usize,
+    /// Whether to pick the seed length for each sequence from its length (see [`auto_seed_len`]) instead of using `seed_len`.
+    #[builder(default, setter(custom))]
+    pub auto_seed_len: bool,
 }
 
 impl MonomerizerBuilder {
+    /// Pick the seed length for each sequence from its length rather than using a fixed `seed_len`.
+    pub fn auto_seed_len(&mut self) -> &mut Self {
+        self.auto_seed_len = Some(true);
+        self
+    }
+
     fn validate(&self) -> Result<(), String> {
-        if self.overlap_dist.is_some() && self.overlap_min_identity.is_some() {
-            // there's no support for overlap_dist and overlap_min_identity at the same time yet
-            // TODO: allow users to specify both and choose the stricter/looser one
-            return Err("Both overlap_dist and overlap_min_identity are set. They are mutually exclusive since they may produce conflicting filtering results.".to_string());
+        if self.seed_len.is_none() && !self.auto_seed_len.unwrap_or(false) {
+            return Err("seed_len must be set unless auto_seed_len is used.".to_string());
+        }
+
+        if self.overlap_mismatch_rate.is_some()
+            && (self.overlap_dist.is_some() || self.overlap_min_identity.is_some())
+        {
+            return Err("overlap_mismatch_rate is set along with overlap_dist or overlap_min_identity. They are mutually exclusive since they may produce conflicting filtering results.".to_string());
+        }
+
+        if let (Some(Some(min)), Some(Some(max))) = (self.min_monomer_len, self.max_monomer_len) {
+            if min > max {
+                return Err(format!(
+                    "min_monomer_len ({}) is greater than max_monomer_len ({}).",
+                    min, max
+                ));
+            }
         }
 
         if let Some(seed_len) = self.seed_len {
@@ -40,14 +135,71 @@ impl MonomerizerBuilder {
     }
 }
 
+/// The seed length picked for a sequence of `len` bases by `auto_seed_len`.
+///
+/// It grows with the log of the length, from 5 nt for short sequences to 13 nt at 100 nt and 20 nt at 1 kb, and is at most 63 nt.
+/// Longer seeds have fewer spurious hits in long sequences, whereas short sequences need a short seed to find short overlaps at all.
+pub fn auto_seed_len(len: usize) -> usize {
+    ((2.0 * (len.max(1) as f64).log2()).round() as usize).clamp(5, 63)
+}
+
 impl Monomerizer {
     pub fn builder() -> MonomerizerBuilder {
         MonomerizerBuilder::default()
     }
+
+    /// The monomerizer to use for `seq`, which has the seed length picked for it if `auto_seed_len` is set.
+    ///
+    /// The seed length is fixed from then on, so every copy trimmed off the sequence is found with the same seed length.
+    pub fn for_sequence(self, seq: &[u8]) -> Self {
+        match self.auto_seed_len {
+            true => Monomerizer {
+                seed_len: auto_seed_len(seq.len()),
+                auto_seed_len: false,
+                ..self
+            },
+            false => self,
+        }
+    }
+
+    /// The maximum number of mismatches allowed in an overlap of `overlap_len` bases.
+    ///
+    /// With `overlap_min_identity`, the identity is relative to the whole overlap, i.e. `overlap_len` is the denominator.
+    /// The number of matching bases required is rounded down to the nearest nt.
+    ///
+    /// With `overlap_mismatch_rate`, the number of mismatches allowed is rounded down instead.
+    /// A rate of `r` is therefore at least as strict as an identity of `1 - r`, and stricter whenever `overlap_len * r` isn't a whole number.
+    ///
+    /// With both `overlap_dist` and `overlap_min_identity`, each gives a number of mismatches and `overlap_combine` chooses between them.
+    pub fn max_overlap_dist(&self, overlap_len: usize) -> u64 {
+        let identity_dist =
+            |identity: f64| overlap_len as u64 - (overlap_len as f64 * identity).floor() as u64;
+        match (
+            self.overlap_min_identity,
+            self.overlap_mismatch_rate,
+            self.overlap_dist,
+        ) {
+            (Some(identity), _, Some(dist)) => match self.overlap_combine {
+                OverlapCombine::Stricter => identity_dist(identity).min(dist),
+                OverlapCombine::Looser => identity_dist(identity).max(dist),
+            },
+            (Some(identity), _, None) => identity_dist(identity),
+            // nudge the product up so that e.g. 0.29 of 100 nt isn't rounded down to 28
+            (None, Some(rate), _) => (overlap_len as f64 * rate + 1e-9).floor() as u64,
+            (None, None, dist) => dist.unwrap_or(0),
+        }
+    }
+
     /// Compute the index of the last base of the first monomer in the sequence, if found.
     pub fn first_monomer_end_index(self, seq: &[u8]) -> Option<usize> {
+        self.first_overlap(seq).map(|overlap| overlap.monomer_end)
+    }
+
+    /// Find the first overlap that passes the cutoffs, i.e. the shortest repeat at the end of the sequence.
+    fn first_overlap(self, seq: &[u8]) -> Option<Overlap> {
+        let monomerizer = self.for_sequence(seq);
         // if the sequence is shorter than the seed, give up
-        let seed_len = self.seed_len;
+        let seed_len = monomerizer.seed_len;
         if seq.len() <= seed_len {
             warn!("Sequence is not longer than seed length");
             return None;
@@ -56,23 +208,37 @@ impl Monomerizer {
         // slice last n bases of the record
         let seed = &seq[seq.len() - seed_len..];
 
-        // create a seed matcher
+        // find the seed, either exactly or with ambiguity codes matching what they stand for
+        let text = &seq[..seq.len() - seed_len];
         let matcher = shift_and::ShiftAnd::new(seed);
+        let occurrences: Box<dyn Iterator<Item = usize>> = match monomerizer.iupac_aware {
+            false => Box::new(matcher.find_all(text)),
+            true => Box::new(
+                text.windows(seed_len)
+                    .enumerate()
+                    .filter_map(|(i, window)| iupac_hamming(window, seed).eq(&0).then_some(i)),
+            ),
+        };
 
-        for occ in matcher.find_all(&seq[..seq.len() - seed_len]) {
+        for occ in occurrences {
             let successor_seed = &seq[..occ + seed_len];
             let starter_seed = &seq[seq.len() - successor_seed.len()..];
 
-            // compare the potential overlap to the seed
-            let dist = hamming(starter_seed, successor_seed);
-
             // compute the maximum distance allowed for the overlap
-            let max_dist = match self.overlap_min_identity {
-                Some(identity) => {
-                    successor_seed.len() as u64
-                        - (successor_seed.len() as f64 * identity).floor() as u64
+            let max_dist = monomerizer.max_overlap_dist(successor_seed.len());
+
+            // compare the potential overlap to the seed
+            let dist = match monomerizer.overlap_metric {
+                OverlapMetric::Hamming => {
+                    overlap_distance(starter_seed, successor_seed, monomerizer.iupac_aware)
                 }
-                None => self.overlap_dist.unwrap_or(0),
+                // the banded distance gives up beyond max_dist, where the exact distance no longer matters
+                OverlapMetric::Edit => bounded_levenshtein(
+                    starter_seed,
+                    successor_seed,
+                    max_dist.try_into().unwrap_or(u32::MAX),
+                )
+                .map_or(max_dist + 1, u64::from),
             };
 
             debug!(
@@ -89,27 +255,77 @@ impl Monomerizer {
 
             // decide whether the overlap is good enough to be a monomer
             if dist <= max_dist {
-                return Some(seq.len() - starter_seed.len());
+                return Some(Overlap {
+                    monomer_end: seq.len() - starter_seed.len(),
+                    overlap_start: seq.len() - starter_seed.len(),
+                    overlap_len: starter_seed.len(),
+                    mismatches: dist,
+                });
             }
         }
         None
     }
-    pub fn last_monomer_end_index(self, seq: &[u8]) -> Option<usize> {
-        let mut monomerized = self.first_monomer_end_index(seq);
-        debug!("monomerized index (first pass): {:?}\n", monomerized);
-        while let Some(monomer_index) = monomerized {
+
+    /// Whether the sequence is a multimer, i.e. whether it has an overlap that passes the cutoffs.
+    ///
+    /// This is the same as `first_monomer_end_index(seq).is_some()` and doesn't look for any further copies, so it is cheaper than finding the monomer.
+    pub fn is_multimer(self, seq: &[u8]) -> bool {
+        self.first_monomer_end_index(seq).is_some()
+    }
+
+    /// The end index of each copy that is trimmed off while monomerizing, from the end of the sequence towards the start.
+    ///
+    /// The last boundary is the end of the monomer, the same as `last_monomer_end_index(seq)` unless the monomer length is limited, so the number of copies found is one more than the number of boundaries.
+    /// The copy after the first boundary may be partial.
+    /// Read from right to left, `seq[..boundaries[i]]` is the sequence left after trimming `i + 1` copies, so the copies are the slices between consecutive boundaries (and the end of the sequence).
+    pub fn monomer_boundaries(&self, seq: &[u8]) -> Vec<usize> {
+        let monomerizer = self.for_sequence(seq);
+        let mut boundaries = Vec::new();
+        let mut end = seq.len();
+        while let Some(index) = monomerizer.first_monomer_end_index(&seq[..end]) {
             debug!(
-                "new monomer: {}\n",
-                std::str::from_utf8(&seq[..monomer_index]).unwrap()
+                "new monomer index: {}\nnew monomer: {}\n",
+                index,
+                std::str::from_utf8(&seq[..index]).unwrap()
             );
-            let new_monomer = self.first_monomer_end_index(&seq[..monomer_index]);
-            debug!("new monomer index: {:?}\n", new_monomer);
-            if new_monomer.is_none() {
-                debug!("no new monomer found");
-                break;
-            }
-            monomerized = new_monomer;
+            boundaries.push(index);
+            end = index;
+        }
+        debug!("no new monomer found");
+        boundaries
+    }
+
+    /// How many times the sequence is trimmed down to a shorter copy while monomerizing, i.e. how many times `first_monomer_end_index` succeeds in `last_monomer_end_index`.
+    ///
+    /// This is 0 for a monomer, 1 for a dimer, 2 for a trimer, and so on, counting a trailing partial copy as a whole one.
+    pub fn monomer_count(&self, seq: &[u8]) -> usize {
+        let monomerizer = self.for_sequence(seq);
+        let mut count = 0;
+        let mut end = seq.len();
+        while let Some(index) = monomerizer.first_monomer_end_index(&seq[..end]) {
+            count += 1;
+            end = index;
         }
+        count
+    }
+
+    /// The end index of the monomer, found by trimming copies off the end of the sequence for as long as there are any.
+    ///
+    /// Returns `None` if the sequence isn't a multimer or if the monomer is outside `min_monomer_len` and `max_monomer_len`.
+    pub fn last_monomer_end_index(self, seq: &[u8]) -> Option<usize> {
+        self.within_monomer_len(self.find_last_monomer_end_index(seq))
+    }
+
+    /// Reject a monomer end index (i.e. monomer length) outside `min_monomer_len` and `max_monomer_len`.
+    fn within_monomer_len(&self, end: Option<usize>) -> Option<usize> {
+        end.filter(|&len| {
+            len >= self.min_monomer_len.unwrap_or(0)
+                && len <= self.max_monomer_len.unwrap_or(usize::MAX)
+        })
+    }
+
+    fn find_last_monomer_end_index(self, seq: &[u8]) -> Option<usize> {
+        let monomerized = self.monomer_boundaries(seq).last().copied();
         debug!(
             "Final monomer: {:?}\n{}",
             monomerized,
@@ -120,18 +336,75 @@ impl Monomerizer {
     }
 
     pub fn last_monomer_end_index_sensitive(&self, seq: &[u8]) -> Option<usize> {
+        let monomerizer = self.for_sequence(seq);
         // First, we monomerize as normal
-        let monomer_index = self.last_monomer_end_index(seq);
+        let monomer_index = monomerizer.find_last_monomer_end_index(seq);
         let monomer = &seq[..monomer_index.unwrap_or(seq.len())];
 
         let rc = dna::revcomp(monomer);
         // debug!("monomer: {:?}", std::str::from_utf8(&rc).unwrap());
-        let rc_monomer_index = self.first_monomer_end_index(&rc);
+        let rc_monomer_index = monomerizer.first_monomer_end_index(&rc);
         // debug!("rc monomer index: {:?}", rc_monomer_index);
-        match rc_monomer_index {
+        self.within_monomer_len(match rc_monomer_index {
             None => monomer_index,
             Some(index) => Some(monomer_index.unwrap_or(seq.len()) - (monomer.len() - index)),
+        })
+    }
+
+    /// The end index of the sequence without its terminal inverted repeat, i.e. without a 3' end that is the reverse complement of its 5' end.
+    ///
+    /// The reverse complement of the 3' seed has to occur within the first `seed_len` bases, where the 5' copy of the repeat starts.
+    /// The repeat is then extended inwards for as long as it passes the overlap cutoffs, without the two copies overlapping, and the 3' copy is trimmed off.
+    /// Mismatches are always counted position by position (respecting `iupac_aware`), whatever the `overlap_metric`.
+    ///
+    /// This only looks for an inverted repeat and is independent of the tandem repeats that [`Monomerizer::last_monomer_end_index`] trims,
+    /// so to trim both, look for the inverted repeat in the monomer.
+    /// Returns `None` if there is no inverted repeat or if the trimmed sequence is outside `min_monomer_len` and `max_monomer_len`.
+    pub fn inverted_repeat_end_index(&self, seq: &[u8]) -> Option<usize> {
+        let monomerizer = self.for_sequence(seq);
+        let (len, seed_len) = (seq.len(), monomerizer.seed_len);
+        if len < 2 * seed_len {
+            return None;
         }
+
+        let seed = dna::revcomp(&seq[len - seed_len..]);
+        let differ = |a: u8, b: u8| match monomerizer.iupac_aware {
+            true => iupac_hamming(&[a], &[b]) > 0,
+            false => a != b,
+        };
+        let start = (0..seed_len.min(len - 2 * seed_len + 1)).find(|&start| {
+            seq[start..start + seed_len]
+                .iter()
+                .zip(&seed)
+                .all(|(&a, &b)| !differ(a, b))
+        })?;
+
+        // extend the repeat inwards, keeping the longest one that passes the cutoffs
+        let (mut repeat_len, mut mismatches) = (seed_len, 0);
+        for k in seed_len + 1..=(len - start) / 2 {
+            if differ(seq[start + k - 1], dna::complement(seq[len - k])) {
+                mismatches += 1;
+            }
+            if mismatches <= monomerizer.max_overlap_dist(k) {
+                repeat_len = k;
+            }
+        }
+        debug!("inverted repeat: start: {}, length: {}", start, repeat_len);
+
+        self.within_monomer_len(Some(len - repeat_len))
+    }
+
+    /// Monomerize like [`Monomerizer::last_monomer_end_index`], also returning the repeat that the monomer was cut at and how well it matched.
+    ///
+    /// For a trimer or longer, this is the last repeat trimmed off, which directly follows the monomer.
+    pub fn monomerize_detailed(&self, seq: &[u8]) -> Option<Overlap> {
+        let monomerizer = self.for_sequence(seq);
+        let mut overlap = monomerizer.first_overlap(seq)?;
+        while let Some(next) = monomerizer.first_overlap(&seq[..overlap.monomer_end]) {
+            overlap = next;
+        }
+        self.within_monomer_len(Some(overlap.monomer_end))?;
+        Some(overlap)
     }
 
     /// A helper function to compute to get a slice of the monomer from a sequence.
@@ -143,6 +416,11 @@ impl Monomerizer {
         }
     }
 
+    /// The canonical form of the monomer, as [`crate::canonicalize()`] of [`Monomerizer::monomerize`] but without copying the monomer first.
+    pub fn monomerize_canonical(&self, seq: &[u8]) -> Vec<u8> {
+        crate::canonicalize(self.monomerize(seq))
+    }
+
     pub fn monomerize_sensitive(self, seq: &[u8]) -> &[u8] {
         let end = self.last_monomer_end_index_sensitive(seq);
         match end {
@@ -150,11 +428,171 @@ impl Monomerizer {
             Some(end) => &seq[..end],
         }
     }
+
+    /// Monomerize each record of a FASTA file as it is read, one at a time on the calling thread.
+    ///
+    /// With `normalize`, overlaps are looked for in an uppercase copy of each sequence in which U is T and anything other than ACGT is N,
+    /// so that e.g. soft-masked repeats still match. The monomer is always cut from the record as it was read.
+    /// Reading errors are passed on, after which the iterator shouldn't be used any further.
+    pub fn monomerize_records<R: Read>(
+        self,
+        reader: R,
+        normalize: bool,
+    ) -> impl Iterator<Item = io::Result<MonomerizedRecord>> {
+        fasta::Reader::new(reader).records().map(move |record| {
+            let record = record?;
+            let end = match normalize {
+                true => self.last_monomer_end_index(&normalize_bases(record.seq())),
+                false => self.last_monomer_end_index(record.seq()),
+            };
+            Ok(MonomerizedRecord { record, end })
+        })
+    }
+}
+
+/// The bases an IUPAC code can stand for, as a bit set of A, C, G, and T (or U), or 0 for anything else.
+fn iupac_bases(base: u8) -> u8 {
+    match base.to_ascii_uppercase() {
+        b'A' => 0b0001,
+        b'C' => 0b0010,
+        b'G' => 0b0100,
+        b'T' | b'U' => 0b1000,
+        b'R' => 0b0101,
+        b'Y' => 0b1010,
+        b'S' => 0b0110,
+        b'W' => 0b1001,
+        b'K' => 0b1100,
+        b'M' => 0b0011,
+        b'B' => 0b1110,
+        b'D' => 0b1101,
+        b'H' => 0b1011,
+        b'V' => 0b0111,
+        b'N' => 0b1111,
+        _ => 0,
+    }
+}
+
+/// The number of positions at which `a` and `b` can't be the same base, treating IUPAC ambiguity codes as any base they could stand for.
+///
+/// Other bytes only match themselves.
+fn iupac_hamming(a: &[u8], b: &[u8]) -> u64 {
+    a.iter()
+        .zip(b)
+        .filter(|&(&x, &y)| x != y && iupac_bases(x) & iupac_bases(y) == 0)
+        .count() as u64
+}
+
+/// The Hamming distance between an overlap and the seed it was found from, with ambiguity codes matching if `iupac_aware`.
+fn overlap_distance(a: &[u8], b: &[u8], iupac_aware: bool) -> u64 {
+    match iupac_aware {
+        true => iupac_hamming(a, b),
+        false => hamming(a, b),
+    }
+}
+
+/// The number of positions at which `a` and `b` differ, using SIMD if the CPU supports it.
+///
+/// The SIMD routines are only used once the CPU is checked for them at runtime, so a binary built on a newer machine doesn't crash with an illegal instruction on an older one.
+/// Building with the `scalar-hamming` feature skips SIMD entirely.
+fn hamming(a: &[u8], b: &[u8]) -> u64 {
+    if !cfg!(feature = "scalar-hamming") && simd_available() {
+        simd::hamming(a, b)
+    } else {
+        scalar_hamming(a, b)
+    }
+}
+
+/// Whether the CPU has the instructions needed for SIMD Hamming distances (SSE4.1 on x86).
+///
+/// Other architectures have no SIMD routines, so the SIMD Hamming distance is already scalar there.
+fn simd_available() -> bool {
+    #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+    {
+        // the result is cached, so this is cheap enough to check for every overlap
+        is_x86_feature_detected!("sse4.1")
+    }
+    #[cfg(not(any(target_arch = "x86", target_arch = "x86_64")))]
+    {
+        true
+    }
+}
+
+/// The number of positions at which `a` and `b` differ, without SIMD.
+fn scalar_hamming(a: &[u8], b: &[u8]) -> u64 {
+    a.iter().zip(b).filter(|(x, y)| x != y).count() as u64
+}
+
+/// The length of the shortest border of at least `min_len` of each prefix of `seq` (indexed by the prefix's length), or 0 if there is none.
+///
+/// A border is a proper prefix that is also a suffix. The borders of a prefix are its longest border and that border's borders,
+/// so the shortest long enough one is found from the shorter prefixes' in constant time, after computing the KMP failure function.
+/// Not used yet: trimming exact overlaps with it wasn't faster than searching for the seed, even on long tandem arrays.
+#[allow(dead_code)]
+fn shortest_borders(seq: &[u8], min_len: usize) -> Vec<usize> {
+    // failure[i] is the length of the longest border of seq[..i]
+    let mut failure = vec![0; seq.len() + 1];
+    for i in 1..seq.len() {
+        let mut k = failure[i];
+        while k > 0 && seq[i] != seq[k] {
+            k = failure[k];
+        }
+        if seq[i] == seq[k] {
+            k += 1;
+        }
+        failure[i + 1] = k;
+    }
+
+    let mut shortest = vec![0; seq.len() + 1];
+    for len in 1..=seq.len() {
+        let longest = failure[len];
+        if longest >= min_len.max(1) {
+            shortest[len] = match shortest[longest] {
+                0 => longest,
+                shorter => shorter,
+            };
+        }
+    }
+    shortest
+}
+
+/// Uppercase a sequence, writing U as T and anything other than ACGT as N.
+fn normalize_bases(seq: &[u8]) -> Vec<u8> {
+    seq.iter()
+        .map(|base| match base.to_ascii_uppercase() {
+            base @ (b'A' | b'C' | b'G' | b'T') => base,
+            b'U' => b'T',
+            _ => b'N',
+        })
+        .collect()
+}
+
+/// The mean identity of every pair of copies when `seq` is tiled with a monomer of length `monomer_len`.
+///
+/// Unlike the overlap cutoffs of [`Monomerizer`], which compare each copy to the first one as it is trimmed, this compares all copies with each other,
+/// so copies that drift apart in different places are caught even if each is close enough to the first one.
+/// A trailing partial copy is compared over its length. With fewer than two copies, the identity is 1.
+pub fn copy_identity(seq: &[u8], monomer_len: usize) -> f64 {
+    if monomer_len == 0 {
+        return 1.0;
+    }
+    let copies = seq.chunks(monomer_len).collect::<Vec<_>>();
+    let (mut total, mut pairs) = (0.0, 0);
+    for (i, copy) in copies.iter().enumerate() {
+        for other in &copies[i + 1..] {
+            let len = copy.len().min(other.len());
+            total += 1.0 - hamming(&copy[..len], &other[..len]) as f64 / len as f64;
+            pairs += 1;
+        }
+    }
+    match pairs {
+        0 => 1.0,
+        _ => total / pairs as f64,
+    }
 }
 
 #[cfg(test)]
 mod test {
-    use crate::monomerize::Monomerizer;
+    use crate::monomerize::{Monomerizer, OverlapCombine};
     use rstest::rstest;
 
     mod basic_tests {
@@ -387,6 +825,185 @@ mod test {
             // make sure the monomer is correct
             assert_eq!(monomer, known_monomer.as_bytes());
         }
+
+        #[rstest]
+        #[case(b"ATGCATGC", true)] // dimer
+        #[case(b"ATGCAATGCAATGCA", true)] // trimer
+        #[case(b"ATGCAGGTCA", false)] // monomer
+        #[case(b"ATGCATGA", false)] // mismatch in the overlap
+        #[case(b"ATG", false)] // shorter than the seed
+        fn is_multimer(#[case] seq: &[u8], #[case] expected: bool) {
+            let m = Monomerizer::builder().seed_len(4).build().unwrap();
+            assert_eq!(m.is_multimer(seq), expected);
+        }
+
+        #[rstest]
+        #[case(b"ATGCATGC", &[4])] // dimer
+        #[case(b"ATGCATGCATGC", &[8, 4])] // trimer
+        #[case(b"ATGCAATGCAATGCAATG", &[15, 10, 5])] // 3.6 copies
+        #[case(b"ATGCAGGTCA", &[])] // monomer
+        fn monomer_boundaries(#[case] seq: &[u8], #[case] expected: &[usize]) {
+            let m = Monomerizer::builder().seed_len(3).build().unwrap();
+            assert_eq!(m.monomer_boundaries(seq), expected);
+            assert_eq!(m.last_monomer_end_index(seq), expected.last().copied());
+        }
+
+        #[rstest]
+        #[case(b"GCATGCAT")] // dimer, rotated away from its canonical form
+        #[case(b"TTGCATTGCATTG")] // 2.6 copies
+        #[case(b"ATGCAGGTCA")] // monomer
+        #[case(b"")]
+        fn monomerize_canonical(#[case] seq: &[u8]) {
+            let m = Monomerizer::builder().seed_len(3).build().unwrap();
+            assert_eq!(
+                m.monomerize_canonical(seq),
+                crate::canonicalize(m.monomerize(seq))
+            );
+        }
+
+        #[test]
+        fn monomer_boundaries_reconstruct_copies() {
+            let seq = b"ATGCAATGCAATGCAATG";
+            let m = Monomerizer::builder().seed_len(3).build().unwrap();
+            let boundaries = m.monomer_boundaries(seq);
+
+            // read right to left, the boundaries split the multimer into its copies
+            let ends = std::iter::once(seq.len()).chain(boundaries.iter().copied());
+            let starts = boundaries.iter().copied().chain(std::iter::once(0));
+            let copies = ends
+                .zip(starts)
+                .map(|(end, start)| &seq[start..end])
+                .collect::<Vec<_>>();
+            assert_eq!(copies, [&b"ATG"[..], b"ATGCA", b"ATGCA", b"ATGCA"].to_vec());
+        }
+
+        #[rstest]
+        #[case(None, None, b"ATGC")]
+        #[case(Some(4), Some(4), b"ATGC")]
+        #[case(Some(5), None, b"ATGCATGCATGC")]
+        #[case(None, Some(3), b"ATGCATGCATGC")]
+        fn monomer_len_range(
+            #[case] min: Option<usize>,
+            #[case] max: Option<usize>,
+            #[case] expected: &[u8],
+        ) {
+            let mut builder = Monomerizer::builder();
+            builder.seed_len(3);
+            if let Some(min) = min {
+                builder.min_monomer_len(min);
+            }
+            if let Some(max) = max {
+                builder.max_monomer_len(max);
+            }
+            let m = builder.build().unwrap();
+            assert_eq!(m.monomerize(b"ATGCATGCATGC"), expected);
+            assert_eq!(m.monomerize_sensitive(b"ATGCATGCATGC"), expected);
+        }
+
+        #[rstest]
+        #[case(b"ATGCATGC", 1)] // dimer
+        #[case(b"ATGCATGCATGC", 2)] // trimer
+        #[case(b"ATGCAATGCAATGCAATG", 3)] // 3.6 copies
+        #[case(b"ATGCAGGTCA", 0)] // monomer
+        #[case(b"", 0)]
+        fn monomer_count(#[case] seq: &[u8], #[case] expected: usize) {
+            let m = Monomerizer::builder().seed_len(3).build().unwrap();
+            assert_eq!(m.monomer_count(seq), expected);
+            assert_eq!(m.monomer_count(seq), m.monomer_boundaries(seq).len());
+        }
+    }
+
+    mod overlap_metric {
+        use super::*;
+        use crate::monomerize::OverlapMetric;
+
+        #[rstest]
+        #[case(OverlapMetric::Hamming, false)]
+        #[case(OverlapMetric::Edit, true)]
+        fn deletion_in_overlap(#[case] metric: OverlapMetric, #[case] monomerized: bool) {
+            let monomer = b"ACGTTGCAAGGCTTACGATCGTAGCTAGGCTAACGGTCCA";
+            // the second copy is missing its fourth base
+            let input = [&monomer[..], &monomer[..3], &monomer[4..25]].concat();
+
+            let m = Monomerizer::builder()
+                .overlap_dist(2)
+                .overlap_metric(metric)
+                .seed_len(6)
+                .build()
+                .unwrap();
+            let output = m.monomerize(&input);
+            match monomerized {
+                // the copies are compared over the same length, so the boundary is off by the net length of the indels
+                true => assert_eq!(output, &monomer[..monomer.len() - 1]),
+                false => assert_eq!(output, input),
+            }
+        }
+
+        #[test]
+        fn scalar_hamming_matches_simd() {
+            use crate::monomerize::{hamming, scalar_hamming};
+            for (a, b) in [
+                (&b""[..], &b""[..]),
+                (b"ACGT", b"ACGT"),
+                (b"ACGT", b"TGCA"),
+                (b"ACGTN", b"ACGTA"),
+            ] {
+                assert_eq!(scalar_hamming(a, b), hamming(a, b));
+            }
+            // long enough for several SIMD lanes and a remainder
+            let a = b"ACGT".repeat(100);
+            let b = b"ACGA".repeat(100);
+            assert_eq!(scalar_hamming(&a, &b), 100);
+            assert_eq!(hamming(&a, &b), 100);
+        }
+    }
+
+    mod iupac_aware {
+        use super::*;
+
+        #[rstest]
+        // an N in the seed
+        #[case(
+            b"ACGTTGCAAGGCTTACGATCACGTTGCAAGNCTT",
+            false,
+            b"ACGTTGCAAGGCTTACGATCACGTTGCAAGNCTT"
+        )]
+        #[case(b"ACGTTGCAAGGCTTACGATCACGTTGCAAGNCTT", true, b"ACGTTGCAAGGCTTACGATC")]
+        // ambiguity codes in the rest of the overlap
+        #[case(
+            b"ACGTTGCAAGGCTTACGATCRCGTYGCAAGGCTT",
+            false,
+            b"ACGTTGCAAGGCTTACGATCRCGTYGCAAGGCTT"
+        )]
+        #[case(b"ACGTTGCAAGGCTTACGATCRCGTYGCAAGGCTT", true, b"ACGTTGCAAGGCTTACGATC")]
+        // R can't be a C or T
+        #[case(
+            b"ACGTTGCAAGGCTTACGATCACGRTGCAAGGCTT",
+            true,
+            b"ACGTTGCAAGGCTTACGATCACGRTGCAAGGCTT"
+        )]
+        fn ambiguity_codes_match(
+            #[case] seq: &[u8],
+            #[case] iupac_aware: bool,
+            #[case] expected: &[u8],
+        ) {
+            let m = Monomerizer::builder()
+                .seed_len(5)
+                .iupac_aware(iupac_aware)
+                .build()
+                .unwrap();
+            assert_eq!(m.monomerize(seq), expected);
+        }
+
+        #[test]
+        fn iupac_hamming_counts_incompatible_positions() {
+            use crate::monomerize::iupac_hamming;
+            assert_eq!(iupac_hamming(b"ACGT", b"ACGT"), 0);
+            assert_eq!(iupac_hamming(b"NNNN", b"ACGT"), 0);
+            assert_eq!(iupac_hamming(b"RYKM", b"AAAA"), 2);
+            assert_eq!(iupac_hamming(b"acgu", b"ACGT"), 0);
+            assert_eq!(iupac_hamming(b"A-", b"A*"), 1);
+        }
     }
 
     mod validation {
@@ -397,6 +1014,17 @@ mod test {
             Monomerizer::builder().build().unwrap().monomerize(b"");
         }
 
+        #[test]
+        #[should_panic(expected = "greater than max_monomer_len")]
+        fn monomer_len_range_inverted() {
+            Monomerizer::builder()
+                .seed_len(4)
+                .min_monomer_len(10)
+                .max_monomer_len(5)
+                .build()
+                .unwrap();
+        }
+
         #[test]
         #[should_panic(expected = "at least 1 and at most 63")]
         fn seed_too_long() {
@@ -418,6 +1046,194 @@ mod test {
         }
     }
 
+    mod detailed {
+        use super::*;
+        use crate::monomerize::Overlap;
+
+        const MONOMER: &[u8] = b"ATGCGTACGTTAGCCTAGGC";
+
+        #[test]
+        fn dimer_with_mismatch() {
+            let mut seq = [MONOMER, &MONOMER[..10]].concat();
+            seq[22] = b'A';
+            let m = Monomerizer::builder()
+                .seed_len(5)
+                .overlap_dist(1)
+                .build()
+                .unwrap();
+            assert_eq!(
+                m.monomerize_detailed(&seq),
+                Some(Overlap {
+                    monomer_end: 20,
+                    overlap_start: 20,
+                    overlap_len: 10,
+                    mismatches: 1,
+                })
+            );
+        }
+
+        #[test]
+        fn trimer_reports_last_repeat() {
+            let seq = MONOMER.repeat(3);
+            let m = Monomerizer::builder().seed_len(5).build().unwrap();
+            assert_eq!(
+                m.monomerize_detailed(&seq),
+                Some(Overlap {
+                    monomer_end: 20,
+                    overlap_start: 20,
+                    overlap_len: 20,
+                    mismatches: 0,
+                })
+            );
+        }
+
+        #[test]
+        fn monomer_has_no_overlap() {
+            let m = Monomerizer::builder().seed_len(5).build().unwrap();
+            assert_eq!(m.monomerize_detailed(MONOMER), None);
+        }
+
+        #[test]
+        fn respects_monomer_len() {
+            let m = Monomerizer::builder()
+                .seed_len(5)
+                .max_monomer_len(10)
+                .build()
+                .unwrap();
+            assert_eq!(m.monomerize_detailed(&MONOMER.repeat(2)), None);
+        }
+    }
+
+    mod inverted_repeat {
+        use super::*;
+        use bio::alphabets::dna;
+
+        const REPEAT: &[u8] = b"ATGCGTACGTTAGCC";
+
+        /// The repeat, then `inner`, then the reverse complement of the repeat (with a mismatch at `mismatch` if given).
+        fn hairpin(prefix: &[u8], inner: &[u8], mismatch: Option<usize>) -> Vec<u8> {
+            let mut rc = dna::revcomp(REPEAT);
+            if let Some(i) = mismatch {
+                rc[i] = dna::complement(rc[i]);
+            }
+            [prefix, REPEAT, inner, &rc].concat()
+        }
+
+        #[rstest]
+        #[case(b"")]
+        #[case(b"GG")]
+        fn trims_the_3_prime_copy(#[case] prefix: &[u8]) {
+            let seq = hairpin(prefix, b"AAAAAAAAAA", None);
+            let m = Monomerizer::builder().seed_len(5).build().unwrap();
+            assert_eq!(
+                m.inverted_repeat_end_index(&seq),
+                Some(seq.len() - REPEAT.len())
+            );
+        }
+
+        #[test]
+        fn mismatches_follow_the_overlap_cutoffs() {
+            let seq = hairpin(b"", b"AAAAAAAAAA", Some(3));
+            let exact = Monomerizer::builder().seed_len(5).build().unwrap();
+            // only the inner part of the repeat, up to the mismatch, is found
+            assert_eq!(exact.inverted_repeat_end_index(&seq), Some(seq.len() - 11));
+
+            let tolerant = Monomerizer::builder()
+                .seed_len(5)
+                .overlap_dist(1)
+                .build()
+                .unwrap();
+            assert_eq!(
+                tolerant.inverted_repeat_end_index(&seq),
+                Some(seq.len() - REPEAT.len())
+            );
+        }
+
+        #[test]
+        fn tandem_repeat_is_not_inverted() {
+            let seq = [REPEAT, b"AAAAAAAAAA", REPEAT].concat();
+            let m = Monomerizer::builder().seed_len(5).build().unwrap();
+            assert_eq!(m.inverted_repeat_end_index(&seq), None);
+            assert_eq!(m.last_monomer_end_index(&seq), Some(25));
+        }
+    }
+
+    mod records {
+        use super::*;
+
+        const MONOMER: &str = "ACGTTGCAAGGCTTACGACCTGA";
+
+        #[rstest]
+        #[case(false, None)]
+        #[case(true, Some(23))]
+        fn normalization_is_opt_in(#[case] normalize: bool, #[case] end: Option<usize>) {
+            // the first copy is soft-masked, so the seed only matches it once normalized
+            let input = format!(
+                ">dimer masked\n{}{}\n>monomer\n{}\n",
+                MONOMER.to_ascii_lowercase(),
+                MONOMER,
+                MONOMER
+            );
+            let m = Monomerizer::builder().seed_len(10).build().unwrap();
+            let records = m
+                .monomerize_records(input.as_bytes(), normalize)
+                .collect::<Result<Vec<_>, _>>()
+                .unwrap();
+
+            assert_eq!(records.len(), 2);
+            assert_eq!(records[0].record.id(), "dimer");
+            assert_eq!(records[0].record.desc(), Some("masked"));
+            assert_eq!(records[0].end, end);
+            assert_eq!(
+                records[0].monomer(),
+                &records[0].record.seq()[..end.unwrap_or(46)]
+            );
+            assert_eq!(records[1].end, None);
+            assert_eq!(records[1].monomer(), MONOMER.as_bytes());
+        }
+
+        #[test]
+        fn passes_on_read_errors() {
+            let m = Monomerizer::builder().seed_len(10).build().unwrap();
+            let mut records = m.monomerize_records(&b"ACGT\n"[..], false);
+            assert!(records.next().unwrap().is_err());
+        }
+    }
+
+    mod auto_seed {
+        use super::*;
+        use crate::monomerize::auto_seed_len;
+
+        #[rstest]
+        #[case(0, 5)]
+        #[case(10, 7)]
+        #[case(100, 13)]
+        #[case(1000, 20)]
+        #[case(usize::MAX, 63)]
+        fn seed_len_grows_with_length(#[case] len: usize, #[case] expected: usize) {
+            assert_eq!(auto_seed_len(len), expected);
+        }
+
+        #[test]
+        fn monomerizes_without_seed_len() {
+            // a 400 nt monomer that doesn't repeat within itself
+            let mut state = 1u32;
+            let monomer = (0..400)
+                .map(|_| {
+                    state = state.wrapping_mul(1_103_515_245).wrapping_add(12_345);
+                    b"ACGT"[(state >> 16) as usize % 4]
+                })
+                .collect::<Vec<_>>();
+            let trimer = monomer.repeat(3);
+
+            let m = Monomerizer::builder().auto_seed_len().build().unwrap();
+            assert_eq!(m.monomerize(&trimer), monomer.as_slice());
+            assert_eq!(m.monomer_count(&trimer), 2);
+            // the seed is picked once for the whole sequence
+            assert_eq!(m.for_sequence(&trimer).seed_len, auto_seed_len(1200));
+        }
+    }
+
     mod overlap_percent {
         use super::*;
         use pretty_assertions::assert_eq;
@@ -477,27 +1293,192 @@ mod test {
             assert_eq!(m.monomerize(input), output);
         }
 
+        #[rstest]
+        #[case(10, 0.95, 1)] // 9.5 nt required
+        #[case(19, 0.95, 1)] // 18.05 nt required
+        #[case(20, 0.95, 1)] // 19 nt required
+        #[case(40, 0.95, 2)] // 38 nt required
+        #[case(41, 0.95, 3)] // 38.95 nt required
+        #[case(100, 0.9, 10)] // 90 nt required
+        #[case(7, 1.0, 0)]
+        #[case(7, 0.0, 7)]
+        fn max_overlap_dist_is_relative_to_overlap(
+            #[case] overlap_len: usize,
+            #[case] identity: f64,
+            #[case] max_dist: u64,
+        ) {
+            let m = Monomerizer::builder()
+                .overlap_min_identity(identity)
+                .seed_len(4)
+                .build()
+                .unwrap();
+            assert_eq!(m.max_overlap_dist(overlap_len), max_dist);
+        }
+
+        #[rstest]
+        #[case(20, OverlapCombine::Stricter, 1)] // the identity allows 1 and the count 2
+        #[case(20, OverlapCombine::Looser, 2)]
+        #[case(100, OverlapCombine::Stricter, 2)] // the identity allows 5 and the count 2
+        #[case(100, OverlapCombine::Looser, 5)]
+        fn max_overlap_dist_combines_both_cutoffs(
+            #[case] overlap_len: usize,
+            #[case] combine: OverlapCombine,
+            #[case] max_dist: u64,
+        ) {
+            let m = Monomerizer::builder()
+                .overlap_dist(2)
+                .overlap_min_identity(0.95)
+                .overlap_combine(combine)
+                .seed_len(4)
+                .build()
+                .unwrap();
+            assert_eq!(m.max_overlap_dist(overlap_len), max_dist);
+        }
+
+        #[rstest]
+        /// A dimer whose overlap of `overlap_len` nt has `mismatches` mismatches in it is only monomerized if the identity over the whole overlap is high enough.
+        fn identity_is_computed_over_whole_overlap(
+            #[values(12, 20, 33, 50)] overlap_len: usize,
+            #[values(0, 1, 2, 3)] mismatches: usize,
+            #[values(0.9, 0.95)] identity: f64,
+        ) {
+            let monomer = b"ACGTTGCAAGGCTTACGATCGTAGCTAGGCTAACGGTCCATGACTTGCAGTCAGGATCCA";
+            // the seed is taken from the end of the sequence, so put the mismatches at the start of the overlap
+            let mut overlap = monomer[..overlap_len].to_vec();
+            for base in overlap.iter_mut().take(mismatches) {
+                *base = match *base {
+                    b'A' => b'C',
+                    _ => b'A',
+                };
+            }
+            let input = [&monomer[..], &overlap].concat();
+
+            let m = Monomerizer::builder()
+                .overlap_min_identity(identity)
+                .seed_len(4)
+                .build()
+                .unwrap();
+            let expected: &[u8] = match (overlap_len - mismatches) as f64
+                >= (overlap_len as f64 * identity).floor()
+            {
+                true => monomer,
+                false => &input,
+            };
+            assert_eq!(
+                m.first_monomer_end_index(&input).map(|end| &input[..end]),
+                (expected.len() < input.len()).then_some(expected),
+                "overlap_len: {}, mismatches: {}, identity: {}",
+                overlap_len,
+                mismatches,
+                identity
+            );
+        }
+
+        #[rstest]
+        #[case(10, 0.05, 0)] // 0.5 mismatches allowed
+        #[case(19, 0.05, 0)] // 0.95 mismatches allowed
+        #[case(20, 0.05, 1)]
+        #[case(41, 0.05, 2)] // 2.05 mismatches allowed
+        #[case(100, 0.29, 29)]
+        #[case(100, 0.1, 10)]
+        #[case(7, 0.0, 0)]
+        #[case(7, 1.0, 7)]
+        fn max_overlap_dist_scales_with_mismatch_rate(
+            #[case] overlap_len: usize,
+            #[case] rate: f64,
+            #[case] max_dist: u64,
+        ) {
+            let m = Monomerizer::builder()
+                .overlap_mismatch_rate(rate)
+                .seed_len(4)
+                .build()
+                .unwrap();
+            assert_eq!(m.max_overlap_dist(overlap_len), max_dist);
+        }
+
+        #[rstest]
+        /// A mismatch rate never allows more mismatches than the equivalent identity, since it rounds the other way.
+        fn mismatch_rate_is_at_least_as_strict_as_identity(
+            #[values(1, 7, 10, 19, 20, 41, 100)] overlap_len: usize,
+            #[values(0.0, 0.05, 0.1, 0.29, 1.0)] rate: f64,
+        ) {
+            let by_rate = Monomerizer::builder()
+                .overlap_mismatch_rate(rate)
+                .seed_len(4)
+                .build()
+                .unwrap();
+            let by_identity = Monomerizer::builder()
+                .overlap_min_identity(1.0 - rate)
+                .seed_len(4)
+                .build()
+                .unwrap();
+            assert!(
+                by_rate.max_overlap_dist(overlap_len) <= by_identity.max_overlap_dist(overlap_len)
+            );
+        }
+
         #[test]
-        #[should_panic(expected = "overlap_dist and overlap_min_identity")]
-        fn overlap_percentage_and_dist_panics() {
+        #[should_panic(expected = "overlap_mismatch_rate is set")]
+        fn mismatch_rate_and_dist_panics() {
+            Monomerizer::builder()
+                .overlap_dist(1)
+                .overlap_mismatch_rate(0.05)
+                .seed_len(4)
+                .build()
+                .unwrap();
+        }
+
+        #[rstest]
+        #[case(OverlapCombine::Stricter, false)]
+        #[case(OverlapCombine::Looser, true)]
+        fn overlap_percentage_and_dist_are_combined(
+            #[case] combine: OverlapCombine,
+            #[case] monomerized: bool,
+        ) {
+            // the overlap has 2 mismatches, which the identity allows but the count doesn't
             let input = b"TGCCCATGCGCCAGCGCAGATGCGAATGCGCCAGCGCAG";
+            let output = b"TGCCCATGCGCCAGCGCAGA";
 
             let m = Monomerizer::builder()
                 .overlap_dist(1)
-                .overlap_min_identity(0.95)
+                .overlap_min_identity(0.90)
+                .overlap_combine(combine)
                 .seed_len(4)
                 .build()
                 .unwrap();
+            let expected: &[u8] = match monomerized {
+                true => output,
+                false => input,
+            };
+            assert_eq!(m.monomerize(input), expected);
+        }
+    }
+
+    mod copy_identity {
+        use crate::monomerize::copy_identity;
 
-            m.monomerize(input);
+        #[test]
+        fn identical_copies() {
+            assert_eq!(copy_identity(b"ACGTACGTAC", 4), 1.0);
+            assert_eq!(copy_identity(b"ACGT", 4), 1.0);
+        }
+
+        #[test]
+        fn drifting_copies() {
+            // each copy differs from the first in one base, but from each other in two
+            let identity = copy_identity(b"ACGTTCGTAGGT", 4);
+            assert!((identity - (0.75 + 0.75 + 0.5) / 3.0).abs() < 1e-9);
+        }
+
+        #[test]
+        fn partial_copy() {
+            assert_eq!(copy_identity(b"ACGTACGTTC", 4), (1.0 + 0.5 + 0.5) / 3.0);
         }
     }
 
     mod sensitive {
-        use crate::canonicalize;
-
         use super::*;
-        use pretty_assertions::{assert_eq, assert_ne};
+        use pretty_assertions::assert_eq;
 
         #[test]
         fn sensitive_monomerization() {
@@ -527,7 +1508,7 @@ mod test {
                         .seed_len(10)
                         .build()
                         .unwrap();
-                    prop_assert_eq!(m.clone().monomerize(concatenated.as_bytes()), input.as_bytes());
+                    prop_assert_eq!(m.monomerize(concatenated.as_bytes()), input.as_bytes());
                     // prop_assert_eq!(m.clone().monomerize_sensitive(concatenated.as_bytes()), input.as_bytes());
                 }
                 #[test]
@@ -549,6 +1530,18 @@ mod test {
                         .unwrap();
                     prop_assert_eq!(m.monomerize(&concat), &concat[..input.len()]);
                 }
+                #[test]
+                fn shortest_borders_are_shortest(seq in "[AC]{0,60}", min_len in 0..6usize) {
+                    use crate::monomerize::shortest_borders;
+                    let borders = shortest_borders(seq.as_bytes(), min_len);
+                    for len in 0..=seq.len() {
+                        let prefix = &seq.as_bytes()[..len];
+                        let expected = (min_len.max(1)..len)
+                            .find(|&border| prefix.starts_with(&prefix[len - border..]))
+                            .unwrap_or(0);
+                        prop_assert_eq!(borders[len], expected);
+                    }
+                }
         }
     }
 }
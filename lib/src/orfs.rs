@@ -3,10 +3,12 @@ use std::{collections::HashSet, vec};
 use aho_corasick::AhoCorasick;
 
 #[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Orf {
     /// The index of the start codon's first nucleotide. Zero-indexed.
     pub start: usize,
-    /// The index of the stop codon's first nucleotide. Might be `None` if there is no stop codon. Zero-indexed.
+    /// The index of the stop codon's first nucleotide. Zero-indexed.
+    /// This is `None` for a stop-less circular ORF, whose reading frame has no stop codon at all (see [`Orf::is_stopless_circular`]).
     pub stop: Option<usize>,
     /// How many times did this ORF wrap around the origin
     pub wraps: usize,
@@ -16,19 +18,25 @@ pub struct Orf {
 
 // this function converts an Orf into a string with the ORF sequence
 impl Orf {
+    /// The sequence of the ORF, with or without its stop codon. A stop-less circular ORF has no stop codon, so all of it is returned either way.
     pub fn seq_with_opts(&self, seq: &[u8], include_stop: bool) -> String {
+        self.seq_trimmed(
+            seq,
+            match include_stop || self.is_stopless_circular() {
+                true => 0,
+                false => 3,
+            },
+        )
+    }
+
+    /// The sequence of the ORF without its last `trim` nucleotides, e.g. the length of a stop codon of a non-triplet code.
+    pub fn seq_trimmed(&self, seq: &[u8], trim: usize) -> String {
         // use a cyclical iterator to get the nucleotides, starting at the start codon
         let nucleotides = seq
             .iter()
             .cycle()
             .skip(self.start)
-            .take(
-                self.length
-                    - match include_stop {
-                        true => 0,
-                        false => 3,
-                    },
-            )
+            .take(self.length - trim)
             .copied()
             .collect::<Vec<_>>();
         String::from_utf8(nucleotides).unwrap()
@@ -36,8 +44,77 @@ impl Orf {
     pub fn seq(&self, seq: &[u8]) -> String {
         self.seq_with_opts(seq, true)
     }
+
+    /// Whether the ORF never reaches a stop codon, so it codes around the circle forever.
+    ///
+    /// Such an ORF has [`STOPLESS_WRAPS`] wraps, and its length is one full period of the reading frame:
+    /// the length of the sequence if it is a multiple of three, or three times the length otherwise, since reading then passes through all three frames before repeating.
+    pub fn is_stopless_circular(&self) -> bool {
+        self.stop.is_none()
+    }
+
+    /// The reading frame the ORF starts in, from 0 to 2.
+    ///
+    /// If the length of the sequence isn't a multiple of three, an ORF that wraps around the origin continues in a different frame than this.
+    pub fn frame(&self) -> usize {
+        self.start % 3
+    }
+
+    /// The number of positions of a circular sequence of length `seq_len` covered by both ORFs, including their stop codons.
+    ///
+    /// Both ORFs must be on the same strand. An ORF at least as long as the sequence covers every position.
+    pub fn overlap(&self, other: &Orf, seq_len: usize) -> usize {
+        if self.length >= seq_len || other.length >= seq_len {
+            return self.length.min(other.length).min(seq_len);
+        }
+        // both are shorter than the sequence, so either may reach around the origin into the other, but no further
+        let shared = |start: usize, length: usize, other_start: usize, other_length: usize| {
+            (start + length)
+                .min(other_start + other_length)
+                .saturating_sub(start.max(other_start))
+        };
+        shared(self.start, self.length, other.start, other.length)
+            + shared(self.start + seq_len, self.length, other.start, other.length)
+            + shared(self.start, self.length, other.start + seq_len, other.length)
+    }
+
+    /// Whether the ORFs share any position of a circular sequence of length `seq_len`.
+    pub fn overlaps(&self, other: &Orf, seq_len: usize) -> bool {
+        self.overlap(other, seq_len) > 0
+    }
+
+    /// Translate the ORF into a protein sequence using `table`, reading around the origin of `seq` like [`Orf::seq`].
+    ///
+    /// Translation stops at the stop codon, which is left out. A stop-less circular ORF is translated over its whole length.
+    pub fn protein(&self, seq: &[u8], table: &CodonTable) -> String {
+        self.protein_with_opts(seq, table, false, None)
+    }
+
+    /// Translate the ORF like [`Orf::protein`], but with its stop codon translated as `*` if `include_stop` is set,
+    /// and with its first codon translated as methionine if it is one of `start_codons` (see [`CodonTable::translate_with_initiator`]).
+    pub fn protein_with_opts(
+        &self,
+        seq: &[u8],
+        table: &CodonTable,
+        include_stop: bool,
+        start_codons: Option<&[&str]>,
+    ) -> String {
+        let nucleotides = self.seq_with_opts(seq, include_stop);
+        match start_codons {
+            Some(start_codons) => {
+                table.translate_with_initiator(nucleotides.as_bytes(), start_codons)
+            }
+            None => table.translate(nucleotides.as_bytes()),
+        }
+    }
 }
 
+/// The number of wraps reported for a stop-less circular ORF, which is the most any ORF can have.
+pub const STOPLESS_WRAPS: usize = 3;
+
+/// Find the ORFs of a circular sequence, in the forward strand only.
+///
+/// Sequences shorter than a codon have no ORFs.
 pub fn find_orfs(seq: &str) -> Vec<Orf> {
     // Step 1: Find all stop and start codons by frame
     let start_codons = ["ATG"];
@@ -53,8 +130,19 @@ pub fn find_orfs(seq: &str) -> Vec<Orf> {
     find_orfs_with_indices(seq.len(), starts, stops)
 }
 
+/// Find the ORFs of a circular sequence like [`find_orfs`], but with the start and stop codons of a genetic code.
+pub fn find_orfs_with_table(seq: &str, table: &CodonTable) -> Vec<Orf> {
+    let (starts, stops) = start_stop_codon_indices_by_frame_with_table(seq, table);
+    find_orfs_with_indices(seq.len(), starts, stops)
+}
+
 /// A helper function to add the last two codons to a computed codon index
 pub fn add_last_codons(seq: &str, codons: &[&str], codon_indices_by_frame: &mut [Vec<usize>]) {
+    // a sequence shorter than a codon has no codons, wrapping or not
+    if seq.len() < 3 {
+        return;
+    }
+
     // Handle the last two codons wrapping around
     let penultimate_codon = format!("{}{}", &seq[seq.len() - 2..], &seq[..1]);
     debug_assert!(penultimate_codon.len() == 3);
@@ -77,6 +165,39 @@ pub fn start_stop_codon_indices_by_frame_naive(
 ) -> (Vec<Vec<usize>>, Vec<Vec<usize>>) {
     let mut start_codon_indices_by_frame = vec![Vec::new(), Vec::new(), Vec::new()];
     let mut stop_codon_indices_by_frame = vec![Vec::new(), Vec::new(), Vec::new()];
+    start_stop_codon_indices_by_frame_naive_into(
+        seq,
+        start_codons,
+        stop_codons,
+        &mut start_codon_indices_by_frame,
+        &mut stop_codon_indices_by_frame,
+    );
+    (start_codon_indices_by_frame, stop_codon_indices_by_frame)
+}
+
+/// Like [`start_stop_codon_indices_by_frame_naive`], but with the start and stop codons of a genetic code.
+pub fn start_stop_codon_indices_by_frame_with_table(
+    seq: &str,
+    table: &CodonTable,
+) -> (Vec<Vec<usize>>, Vec<Vec<usize>>) {
+    start_stop_codon_indices_by_frame_naive(seq, &table.start_codons(), &table.stop_codons())
+}
+
+/// Like [`start_stop_codon_indices_by_frame_naive`], but writes into existing vectors (one per frame), so that they can be reused between sequences.
+pub fn start_stop_codon_indices_by_frame_naive_into(
+    seq: &str,
+    start_codons: &[&str],
+    stop_codons: &[&str],
+    start_codon_indices_by_frame: &mut [Vec<usize>],
+    stop_codon_indices_by_frame: &mut [Vec<usize>],
+) {
+    start_codon_indices_by_frame.iter_mut().for_each(Vec::clear);
+    stop_codon_indices_by_frame.iter_mut().for_each(Vec::clear);
+
+    // a sequence shorter than a codon has no codons
+    if seq.len() < 3 {
+        return;
+    }
 
     for i in 0..seq.len() - 2 {
         let codon = &seq[i..i + 3];
@@ -87,12 +208,135 @@ pub fn start_stop_codon_indices_by_frame_naive(
         }
     }
 
-    add_last_codons(seq, start_codons, &mut start_codon_indices_by_frame);
-    add_last_codons(seq, stop_codons, &mut stop_codon_indices_by_frame);
+    add_last_codons(seq, start_codons, start_codon_indices_by_frame);
+    add_last_codons(seq, stop_codons, stop_codon_indices_by_frame);
+}
+
+/// Computes the indices of start and stop codons of `codon_size` nucleotides in a sequence, for each of the `codon_size` frames, using a simple sliding window.
+///
+/// Like [`start_stop_codon_indices_by_frame_ambiguous_into`], codons wrap around the origin, and a codon that is both a start and a stop codon is treated as a start codon.
+/// IUPAC ambiguity codes only match what they stand for if `ambiguous` is set. Codons of other lengths never match.
+/// This is slower than the triplet-specific functions, so it's meant for non-triplet codes such as those with quadruplet codons.
+pub fn start_stop_codon_indices_by_frame_sized_into(
+    seq: &str,
+    codon_size: usize,
+    start_codons: &[&str],
+    stop_codons: &[&str],
+    ambiguous: bool,
+    start_codon_indices_by_frame: &mut [Vec<usize>],
+    stop_codon_indices_by_frame: &mut [Vec<usize>],
+) {
+    assert_eq!(start_codon_indices_by_frame.len(), codon_size);
+    assert_eq!(stop_codon_indices_by_frame.len(), codon_size);
+    start_codon_indices_by_frame.iter_mut().for_each(Vec::clear);
+    stop_codon_indices_by_frame.iter_mut().for_each(Vec::clear);
+
+    // a sequence shorter than a codon has no codons, rather than ones made up by reading around it more than once
+    if seq.len() < codon_size {
+        return;
+    }
 
+    let seq = seq.as_bytes();
+    let matches_any = |i: usize, codons: &[&str]| {
+        codons.iter().any(|codon| {
+            codon.len() == codon_size
+                && codon.bytes().enumerate().all(|(j, target)| {
+                    let base = seq[(i + j) % seq.len()];
+                    match ambiguous {
+                        true => iupac_matches(base, target),
+                        false => base == target,
+                    }
+                })
+        })
+    };
+
+    for i in 0..seq.len() {
+        if matches_any(i, start_codons) {
+            start_codon_indices_by_frame[i % codon_size].push(i);
+        } else if matches_any(i, stop_codons) {
+            stop_codon_indices_by_frame[i % codon_size].push(i);
+        }
+    }
+}
+
+/// Whether a base could be `target` (one of A, C, G, or T), expanding IUPAC ambiguity codes.
+///
+/// N is not expanded, since it usually marks unknown sequence rather than an ambiguous base.
+pub fn iupac_matches(base: u8, target: u8) -> bool {
+    let bases: &[u8] = match base.to_ascii_uppercase() {
+        b'U' => b"T",
+        b'R' => b"AG",
+        b'Y' => b"CT",
+        b'S' => b"CG",
+        b'W' => b"AT",
+        b'K' => b"GT",
+        b'M' => b"AC",
+        b'B' => b"CGT",
+        b'D' => b"AGT",
+        b'H' => b"ACT",
+        b'V' => b"ACG",
+        base => return base == target,
+    };
+    bases.contains(&target)
+}
+
+/// Computes the indices of start and stop codons in a sequence using a simple sliding window, allowing IUPAC ambiguity codes to match.
+///
+/// For example, `ATR` matches `ATG` since R could be a G. If a codon could be either a start or a stop codon, it is treated as a start codon.
+/// For unambiguous sequences, this gives the same result as [`start_stop_codon_indices_by_frame_naive`].
+pub fn start_stop_codon_indices_by_frame_ambiguous(
+    seq: &str,
+    start_codons: &[&str],
+    stop_codons: &[&str],
+) -> (Vec<Vec<usize>>, Vec<Vec<usize>>) {
+    let mut start_codon_indices_by_frame = vec![Vec::new(), Vec::new(), Vec::new()];
+    let mut stop_codon_indices_by_frame = vec![Vec::new(), Vec::new(), Vec::new()];
+    start_stop_codon_indices_by_frame_ambiguous_into(
+        seq,
+        start_codons,
+        stop_codons,
+        &mut start_codon_indices_by_frame,
+        &mut stop_codon_indices_by_frame,
+    );
     (start_codon_indices_by_frame, stop_codon_indices_by_frame)
 }
 
+/// Like [`start_stop_codon_indices_by_frame_ambiguous`], but writes into existing vectors (one per frame), so that they can be reused between sequences.
+pub fn start_stop_codon_indices_by_frame_ambiguous_into(
+    seq: &str,
+    start_codons: &[&str],
+    stop_codons: &[&str],
+    start_codon_indices_by_frame: &mut [Vec<usize>],
+    stop_codon_indices_by_frame: &mut [Vec<usize>],
+) {
+    start_codon_indices_by_frame.iter_mut().for_each(Vec::clear);
+    stop_codon_indices_by_frame.iter_mut().for_each(Vec::clear);
+
+    // a sequence shorter than a codon has no codons, rather than ones made up by reading around it more than once
+    if seq.len() < 3 {
+        return;
+    }
+
+    let seq = seq.as_bytes();
+    let matches_any = |i: usize, codons: &[&str]| {
+        codons.iter().any(|codon| {
+            codon
+                .bytes()
+                .enumerate()
+                .all(|(j, target)| iupac_matches(seq[(i + j) % seq.len()], target))
+        })
+    };
+
+    // the last two codons wrap around the origin
+    for i in 0..seq.len() {
+        if matches_any(i, start_codons) {
+            start_codon_indices_by_frame[i % 3].push(i);
+        } else if matches_any(i, stop_codons) {
+            stop_codon_indices_by_frame[i % 3].push(i);
+        }
+    }
+}
+
 /// Compute the indices of all start and stop codons in the sequence by frame using a Rust iterator
 pub fn start_stop_codon_indices_by_frame_iter(
     seq: &str,
@@ -157,8 +401,49 @@ pub fn find_orfs_with_indices(
     start_codon_indices_by_frame: Vec<Vec<usize>>,
     stop_codon_indices_by_frame: Vec<Vec<usize>>,
 ) -> Vec<Orf> {
-    // Find the longest ORF for each start codon
     let mut orfs = Vec::new();
+    find_orfs_with_indices_into(
+        seq_len,
+        &start_codon_indices_by_frame,
+        &stop_codon_indices_by_frame,
+        &mut orfs,
+    );
+    orfs
+}
+
+/// Like [`find_orfs_with_indices`], but writes into an existing vector, so that it can be reused between sequences.
+pub fn find_orfs_with_indices_into(
+    seq_len: usize,
+    start_codon_indices_by_frame: &[Vec<usize>],
+    stop_codon_indices_by_frame: &[Vec<usize>],
+    orfs: &mut Vec<Orf>,
+) {
+    // Find the longest ORF for each start codon
+    orfs.clear();
+
+    // A stop-less reading frame is reported once, from its first start codon.
+    // Each frame is its own reading frame when the length is a multiple of 3, but otherwise all three frames form a single one.
+    let mut stopless = [None::<usize>; 3]; // index into `orfs` by frame
+    let stopless_frame = |frame: usize| match seq_len.is_multiple_of(3) {
+        true => frame,
+        false => 0,
+    };
+    let mut push_stopless = |orfs: &mut Vec<Orf>, start: usize, length: usize| {
+        let orf = Orf {
+            start,
+            stop: None,
+            wraps: STOPLESS_WRAPS,
+            length,
+        };
+        match stopless[stopless_frame(start % 3)] {
+            Some(i) if orfs[i].start <= start => {}
+            Some(i) => orfs[i] = orf,
+            None => {
+                stopless[stopless_frame(start % 3)] = Some(orfs.len());
+                orfs.push(orf);
+            }
+        }
+    };
 
     for &start_codon_index in start_codon_indices_by_frame.iter().flatten() {
         let current_frame = start_codon_index % 3;
@@ -167,7 +452,7 @@ pub fn find_orfs_with_indices(
 
         // Find the next stop codon in the current frame
         // The normal case has the sequence being a multiple of 3, so any wrap around is in the same frame
-        if seq_len % 3 == 0 {
+        if seq_len.is_multiple_of(3) {
             // Find greater than or equal to the start codon index
             let stop_codon_index = stop_codon_indices_by_frame[current_frame]
                 .iter()
@@ -180,24 +465,25 @@ pub fn find_orfs_with_indices(
                         .find(|&i| i < start_codon_index)
                 });
 
+            // No stop codon, so the ORF is the entire sequence, repeated forever
+            let Some(stop) = stop_codon_index else {
+                push_stopless(orfs, start_codon_index, seq_len);
+                continue;
+            };
+
             orfs.push(Orf {
                 start: start_codon_index,
-                stop: stop_codon_index,
-                wraps: if stop_codon_index < Some(start_codon_index)
-                    || (stop_codon_index.is_some() && seq_len - stop_codon_index.unwrap() < 3)
-                {
+                stop: Some(stop),
+                wraps: if stop < start_codon_index || seq_len - stop < 3 {
                     1
                 } else {
                     0
                 },
-                length: match stop_codon_index {
-                    Some(stop) => match stop >= start_codon_index {
-                        // Case 1: The stop codon is after the start codon
-                        true => stop - start_codon_index + 3,
-                        // Case 2: The stop codon is before the start codon, so the ORF wraps around
-                        false => stop + seq_len - start_codon_index + 3,
-                    },
-                    None => seq_len, // No stop codon, so the ORF is the entire sequence
+                length: match stop >= start_codon_index {
+                    // Case 1: The stop codon is after the start codon
+                    true => stop - start_codon_index + 3,
+                    // Case 2: The stop codon is before the start codon, so the ORF wraps around
+                    false => stop + seq_len - start_codon_index + 3,
                 },
             });
             continue;
@@ -286,38 +572,559 @@ pub fn find_orfs_with_indices(
             orf_length += start_codon_index;
             // There is no stop codon in the next frame and we have already checked all frames
             // This means that the ORF wraps around the sequence infinitely
-            orfs.push(Orf {
-                start: start_codon_index,
-                stop: None,
-                wraps: 3,
-                length: orf_length,
-            });
+            // Having read through all three frames back to the start codon, orf_length is one full period: 3 * seq_len
+            debug_assert_eq!(orf_length, 3 * seq_len);
+            push_stopless(orfs, start_codon_index, orf_length);
         }
     }
+}
 
-    orfs
+/// The greatest common divisor of two numbers.
+fn gcd(a: usize, b: usize) -> usize {
+    match b {
+        0 => a,
+        _ => gcd(b, a % b),
+    }
+}
+
+/// Like [`find_orfs_with_indices_into`], but for codons of `codon_size` nucleotides instead of three, with codon indices for each of the `codon_size` frames.
+///
+/// Reading continues across the origin in whichever frame it lands in, and returns to the start codon after the least common multiple of the sequence length and the codon size.
+/// An ORF can therefore wrap around the origin up to `codon_size` times, and a stop-less ORF has `codon_size` wraps, which is [`STOPLESS_WRAPS`] for triplets.
+/// With `codon_size` 3, this gives the same ORFs as [`find_orfs_with_indices_into`].
+pub fn find_orfs_with_codon_size_into(
+    seq_len: usize,
+    codon_size: usize,
+    start_codon_indices_by_frame: &[Vec<usize>],
+    stop_codon_indices_by_frame: &[Vec<usize>],
+    orfs: &mut Vec<Orf>,
+) {
+    orfs.clear();
+
+    // frames that reading passes through one after another form a reading frame, and a stop-less one is reported once, from its first start codon
+    let reading_frames = gcd(seq_len, codon_size);
+    let period = seq_len / reading_frames * codon_size;
+    let mut stopless = vec![None::<usize>; reading_frames]; // index into `orfs` by reading frame
+
+    for &start in start_codon_indices_by_frame.iter().flatten() {
+        let (mut pos, mut read, mut wraps) = (start, 0, 0);
+        let stop = loop {
+            let frame = pos % codon_size;
+            // once reading is back in the frame of the start codon, it ends at the start codon
+            let back = wraps > 0 && frame == start % codon_size;
+            let end = match back {
+                true => start,
+                false => seq_len,
+            };
+            if let Some(stop) = stop_codon_indices_by_frame[frame]
+                .iter()
+                .copied()
+                .find(|&i| i >= pos && i < end)
+            {
+                break Some(stop);
+            }
+            if back {
+                break None;
+            }
+            // continue from the first codon of this frame that crosses the origin, which may cross it more than once if the sequence is shorter than a codon
+            let next = pos + (seq_len - pos).div_ceil(codon_size) * codon_size;
+            read += next - pos;
+            wraps += next / seq_len;
+            pos = next % seq_len;
+        };
+
+        match stop {
+            Some(stop) => orfs.push(Orf {
+                start,
+                stop: Some(stop),
+                // so does a stop codon that crosses the origin
+                wraps: wraps + (stop + codon_size - 1) / seq_len,
+                length: read + stop - pos + codon_size,
+            }),
+            None => {
+                debug_assert_eq!(read + start - pos, period);
+                let orf = Orf {
+                    start,
+                    stop: None,
+                    wraps: codon_size,
+                    length: period,
+                };
+                match stopless[start % reading_frames] {
+                    Some(i) if orfs[i].start <= start => {}
+                    Some(i) => orfs[i] = orf,
+                    None => {
+                        stopless[start % reading_frames] = Some(orfs.len());
+                        orfs.push(orf);
+                    }
+                }
+            }
+        }
+    }
 }
 
 /// For each stop codon, keep only the longest ORF
+///
+/// If several ORFs for the same stop codon are equally long, the one with the smallest start is kept, so the result doesn't depend on the order of `orfs`.
 pub fn longest_orfs(orfs: &mut Vec<Orf>) -> Vec<Orf> {
     // For each stop codon, keep only the longest ORF
-    orfs.sort_by_key(|orf| orf.length); // TODO: make this an unstable sort for performance (if it makes a difference)
+    // Once reversed, this orders the ORFs from longest to shortest and, among equally long ones, by ascending start
+    orfs.sort_unstable_by_key(|orf| (orf.length, std::cmp::Reverse(orf.start)));
     orfs.reverse();
     let mut longest_orfs = Vec::new();
     let mut seen_stop_codons = HashSet::new(); // TODO: check performance of HashSet vs. Vec vs alternative hasher
     for orf in orfs {
-        if !seen_stop_codons.contains(&orf.stop) {
-            seen_stop_codons.insert(orf.stop);
+        // stop-less ORFs are already unique per reading frame, so they are kept apart by their start instead
+        let key = (orf.stop, orf.stop.is_none().then_some(orf.start));
+        if !seen_stop_codons.contains(&key) {
+            seen_stop_codons.insert(key);
             longest_orfs.push(*orf);
         }
     }
     longest_orfs
 }
 
+/// For each stop codon, keep only the longest ORF, unless a shorter ORF for the same stop starts with the `preferred` codon within `window` nucleotides downstream of the longest one's start.
+/// In that case, the longest ORF starting with the preferred codon is kept instead.
+///
+/// This models the preference for the canonical start codon (ATG) over alternative ones (e.g. GTG or TTG) slightly upstream of it.
+pub fn longest_orfs_preferring(
+    orfs: &mut Vec<Orf>,
+    seq: &[u8],
+    preferred: &[u8],
+    window: usize,
+) -> Vec<Orf> {
+    let starts_with_preferred = |orf: &Orf| {
+        preferred
+            .iter()
+            .enumerate()
+            .all(|(i, &base)| seq[(orf.start + i) % seq.len()] == base)
+    };
+
+    let mut chosen = longest_orfs(orfs);
+    for best in chosen.iter_mut() {
+        if starts_with_preferred(best) {
+            continue;
+        }
+        // `orfs` is sorted from longest to shortest, so the first match is the longest preferred ORF
+        if let Some(preferred_orf) = orfs.iter().find(|orf| {
+            orf.stop == best.stop
+                && best.length - orf.length <= window
+                && starts_with_preferred(orf)
+        }) {
+            *best = *preferred_orf;
+        }
+    }
+    chosen
+}
+
+/// The codons in TCAG order, which is the order of the NCBI genetic code listings.
+const CODONS: [&str; 64] = [
+    "TTT", "TTC", "TTA", "TTG", "TCT", "TCC", "TCA", "TCG", "TAT", "TAC", "TAA", "TAG", "TGT",
+    "TGC", "TGA", "TGG", "CTT", "CTC", "CTA", "CTG", "CCT", "CCC", "CCA", "CCG", "CAT", "CAC",
+    "CAA", "CAG", "CGT", "CGC", "CGA", "CGG", "ATT", "ATC", "ATA", "ATG", "ACT", "ACC", "ACA",
+    "ACG", "AAT", "AAC", "AAA", "AAG", "AGT", "AGC", "AGA", "AGG", "GTT", "GTC", "GTA", "GTG",
+    "GCT", "GCC", "GCA", "GCG", "GAT", "GAC", "GAA", "GAG", "GGT", "GGC", "GGA", "GGG",
+];
+
+/// The NCBI genetic codes as (table number, amino acids, starts), copied from the AAs and Starts lines of NCBI's gc.prt.
+///
+/// Tables 27, 28, and 31 are left out, since their stop codons also code for amino acids depending on context.
+const NCBI_TABLES: &[(u8, &[u8; 64], &[u8; 64])] = &[
+    // Standard
+    (
+        1,
+        b"FFLLSSSSYY**CC*WLLLLPPPPHHQQRRRRIIIMTTTTNNKKSSRRVVVVAAAADDEEGGGG",
+        b"---M------**--*----M---------------M----------------------------",
+    ),
+    // Vertebrate Mitochondrial
+    (
+        2,
+        b"FFLLSSSSYY**CCWWLLLLPPPPHHQQRRRRIIMMTTTTNNKKSS**VVVVAAAADDEEGGGG",
+        b"----------**--------------------MMMM----------**---M------------",
+    ),
+    // Yeast Mitochondrial
+    (
+        3,
+        b"FFLLSSSSYY**CCWWTTTTPPPPHHQQRRRRIIMMTTTTNNKKSSRRVVVVAAAADDEEGGGG",
+        b"----------**----------------------MM---------------M------------",
+    ),
+    // Mold, Protozoan, and Coelenterate Mitochondrial and Mycoplasma/Spiroplasma
+    (
+        4,
+        b"FFLLSSSSYY**CCWWLLLLPPPPHHQQRRRRIIIMTTTTNNKKSSRRVVVVAAAADDEEGGGG",
+        b"--MM------**-------M------------MMMM---------------M------------",
+    ),
+    // Invertebrate Mitochondrial
+    (
+        5,
+        b"FFLLSSSSYY**CCWWLLLLPPPPHHQQRRRRIIMMTTTTNNKKSSSSVVVVAAAADDEEGGGG",
+        b"---M------**--------------------MMMM---------------M------------",
+    ),
+    // Ciliate, Dasycladacean and Hexamita Nuclear
+    (
+        6,
+        b"FFLLSSSSYYQQCC*WLLLLPPPPHHQQRRRRIIIMTTTTNNKKSSRRVVVVAAAADDEEGGGG",
+        b"--------------*--------------------M----------------------------",
+    ),
+    // Echinoderm and Flatworm Mitochondrial
+    (
+        9,
+        b"FFLLSSSSYY**CCWWLLLLPPPPHHQQRRRRIIIMTTTTNNNKSSSSVVVVAAAADDEEGGGG",
+        b"----------**-----------------------M---------------M------------",
+    ),
+    // Euplotid Nuclear
+    (
+        10,
+        b"FFLLSSSSYY**CCCWLLLLPPPPHHQQRRRRIIIMTTTTNNKKSSRRVVVVAAAADDEEGGGG",
+        b"----------**-----------------------M----------------------------",
+    ),
+    // Bacterial, Archaeal and Plant Plastid
+    (
+        11,
+        b"FFLLSSSSYY**CC*WLLLLPPPPHHQQRRRRIIIMTTTTNNKKSSRRVVVVAAAADDEEGGGG",
+        b"---M------**--*----M------------MMMM---------------M------------",
+    ),
+    // Alternative Yeast Nuclear
+    (
+        12,
+        b"FFLLSSSSYY**CC*WLLLSPPPPHHQQRRRRIIIMTTTTNNKKSSRRVVVVAAAADDEEGGGG",
+        b"----------**--*----M---------------M----------------------------",
+    ),
+    // Ascidian Mitochondrial
+    (
+        13,
+        b"FFLLSSSSYY**CCWWLLLLPPPPHHQQRRRRIIMMTTTTNNKKSSGGVVVVAAAADDEEGGGG",
+        b"---M------**----------------------MM---------------M------------",
+    ),
+    // Alternative Flatworm Mitochondrial
+    (
+        14,
+        b"FFLLSSSSYYY*CCWWLLLLPPPPHHQQRRRRIIIMTTTTNNNKSSSSVVVVAAAADDEEGGGG",
+        b"-----------*-----------------------M----------------------------",
+    ),
+    // Blepharisma Nuclear
+    (
+        15,
+        b"FFLLSSSSYY*QCC*WLLLLPPPPHHQQRRRRIIIMTTTTNNKKSSRRVVVVAAAADDEEGGGG",
+        b"----------*---*--------------------M----------------------------",
+    ),
+    // Chlorophycean Mitochondrial
+    (
+        16,
+        b"FFLLSSSSYY*LCC*WLLLLPPPPHHQQRRRRIIIMTTTTNNKKSSRRVVVVAAAADDEEGGGG",
+        b"----------*---*--------------------M----------------------------",
+    ),
+    // Trematode Mitochondrial
+    (
+        21,
+        b"FFLLSSSSYY**CCWWLLLLPPPPHHQQRRRRIIMMTTTTNNNKSSSSVVVVAAAADDEEGGGG",
+        b"----------**-----------------------M---------------M------------",
+    ),
+    // Scenedesmus obliquus Mitochondrial
+    (
+        22,
+        b"FFLLSS*SYY*LCC*WLLLLPPPPHHQQRRRRIIIMTTTTNNKKSSRRVVVVAAAADDEEGGGG",
+        b"------*---*---*--------------------M----------------------------",
+    ),
+    // Thraustochytrium Mitochondrial
+    (
+        23,
+        b"FF*LSSSSYY**CC*WLLLLPPPPHHQQRRRRIIIMTTTTNNKKSSRRVVVVAAAADDEEGGGG",
+        b"--*-------**--*-----------------M--M---------------M------------",
+    ),
+    // Rhabdopleuridae Mitochondrial
+    (
+        24,
+        b"FFLLSSSSYY**CCWWLLLLPPPPHHQQRRRRIIIMTTTTNNKKSSSKVVVVAAAADDEEGGGG",
+        b"---M------**-------M---------------M---------------M------------",
+    ),
+    // Candidate Division SR1 and Gracilibacteria
+    (
+        25,
+        b"FFLLSSSSYY**CCGWLLLLPPPPHHQQRRRRIIIMTTTTNNKKSSRRVVVVAAAADDEEGGGG",
+        b"---M------**-----------------------M---------------M------------",
+    ),
+    // Pachysolen tannophilus Nuclear
+    (
+        26,
+        b"FFLLSSSSYY**CC*WLLLAPPPPHHQQRRRRIIIMTTTTNNKKSSRRVVVVAAAADDEEGGGG",
+        b"----------**--*----M---------------M----------------------------",
+    ),
+    // Mesodinium Nuclear
+    (
+        29,
+        b"FFLLSSSSYYYYCC*WLLLLPPPPHHQQRRRRIIIMTTTTNNKKSSRRVVVVAAAADDEEGGGG",
+        b"--------------*--------------------M----------------------------",
+    ),
+    // Peritrich Nuclear
+    (
+        30,
+        b"FFLLSSSSYYEECC*WLLLLPPPPHHQQRRRRIIIMTTTTNNKKSSRRVVVVAAAADDEEGGGG",
+        b"--------------*--------------------M----------------------------",
+    ),
+    // Cephalodiscidae Mitochondrial
+    (
+        33,
+        b"FFLLSSSSYYY*CCWWLLLLPPPPHHQQRRRRIIIMTTTTNNKKSSSKVVVVAAAADDEEGGGG",
+        b"---M-------*-------M---------------M---------------M------------",
+    ),
+];
+
+/// A genetic code, mapping each codon to the amino acid it encodes and telling which codons can start translation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CodonTable {
+    /// The one-letter amino acid of each codon, in TCAG order (TTT, TTC, TTA, TTG, TCT, ...), with `*` for stop codons
+    amino_acids: [u8; 64],
+    /// `M` for each codon that can start translation, in the same order
+    starts: [u8; 64],
+}
+
+impl CodonTable {
+    /// A table from the amino acids and starts of its 64 codons in TCAG order, as in the NCBI genetic code listings.
+    ///
+    /// Stop codons are `*` in `amino_acids` and start codons are `M` in `starts`, whatever else is there.
+    pub fn new(amino_acids: &[u8; 64], starts: &[u8; 64]) -> Self {
+        CodonTable {
+            amino_acids: *amino_acids,
+            starts: *starts,
+        }
+    }
+
+    /// The NCBI genetic code with this table number (the `transl_table` of GenBank records), if there is one.
+    pub fn ncbi(id: u8) -> Option<Self> {
+        NCBI_TABLES
+            .iter()
+            .find(|(table_id, _, _)| *table_id == id)
+            .map(|(_, amino_acids, starts)| CodonTable::new(amino_acids, starts))
+    }
+
+    /// The standard genetic code (NCBI table 1).
+    pub fn standard() -> Self {
+        CodonTable::ncbi(1).unwrap()
+    }
+
+    /// The codons that can start translation, in TCAG order.
+    pub fn start_codons(&self) -> Vec<&'static str> {
+        CODONS
+            .iter()
+            .zip(self.starts)
+            .filter(|&(_, start)| start == b'M')
+            .map(|(&codon, _)| codon)
+            .collect()
+    }
+
+    /// The codons that stop translation, in TCAG order.
+    pub fn stop_codons(&self) -> Vec<&'static str> {
+        CODONS
+            .iter()
+            .zip(self.amino_acids)
+            .filter(|&(_, amino_acid)| amino_acid == b'*')
+            .map(|(&codon, _)| codon)
+            .collect()
+    }
+
+    /// Translate a nucleotide sequence into a protein sequence.
+    ///
+    /// Translation starts at the first nucleotide and any trailing partial codon is ignored.
+    /// Stop codons are translated as `*` and codons containing anything other than ACGTU (in either case) as `X`.
+    pub fn translate(&self, seq: &[u8]) -> String {
+        seq.chunks_exact(3)
+            .map(|codon| {
+                let mut index = 0;
+                for nucleotide in codon {
+                    index = index * 4
+                        + match nucleotide.to_ascii_uppercase() {
+                            b'T' | b'U' => 0,
+                            b'C' => 1,
+                            b'A' => 2,
+                            b'G' => 3,
+                            _ => return 'X',
+                        };
+                }
+                self.amino_acids[index] as char
+            })
+            .collect()
+    }
+
+    /// Translate an ORF into a protein sequence like [`CodonTable::translate`], but with the initiator codon translated as methionine.
+    ///
+    /// Alternative start codons such as GTG and TTG are still read by the initiator tRNA, so they code for M rather than their usual amino acid when they begin an ORF.
+    /// The first codon is only changed if it is one of `start_codons`, compared case-insensitively and with U read as T.
+    pub fn translate_with_initiator(&self, seq: &[u8], start_codons: &[&str]) -> String {
+        let mut protein = self.translate(seq);
+        let is_start_codon = seq.len() >= 3
+            && start_codons.iter().any(|codon| {
+                codon.len() == 3
+                    && seq[..3].iter().zip(codon.bytes()).all(|(&base, target)| {
+                        let base = match base.to_ascii_uppercase() {
+                            b'U' => b'T',
+                            base => base,
+                        };
+                        base == target.to_ascii_uppercase()
+                    })
+            });
+        if is_start_codon {
+            protein.replace_range(..1, "M");
+        }
+        protein
+    }
+}
+
+impl Default for CodonTable {
+    fn default() -> Self {
+        CodonTable::standard()
+    }
+}
+
+/// Translate a nucleotide sequence into a protein sequence using the standard genetic code.
+///
+/// See [`CodonTable::translate`].
+pub fn translate(seq: &[u8]) -> String {
+    CodonTable::standard().translate(seq)
+}
+
+/// Translate an ORF into a protein sequence like [`translate`], but with the initiator codon translated as methionine.
+///
+/// See [`CodonTable::translate_with_initiator`].
+pub fn translate_with_initiator(seq: &[u8], start_codons: &[&str]) -> String {
+    CodonTable::standard().translate_with_initiator(seq, start_codons)
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
 
+    #[test]
+    fn sequences_shorter_than_a_codon_have_no_orfs() {
+        for seq in ["", "A", "AT"] {
+            assert_eq!(find_orfs(seq), vec![]);
+            assert_eq!(find_orfs_with_table(seq, &CodonTable::standard()), vec![]);
+
+            // the codon indices that `circkit orfs` reuses between records are cleared rather than left stale
+            let mut starts = vec![vec![0], vec![], vec![]];
+            let mut stops = vec![vec![], vec![1], vec![]];
+            start_stop_codon_indices_by_frame_naive_into(
+                seq,
+                &["ATG"],
+                &["TAA", "TAG", "TGA"],
+                &mut starts,
+                &mut stops,
+            );
+            assert_eq!(starts, vec![Vec::<usize>::new(); 3]);
+            assert_eq!(stops, vec![Vec::<usize>::new(); 3]);
+
+            // reading around a short sequence repeatedly would make up codons, e.g. ATA from AT
+            let (starts, stops) =
+                start_stop_codon_indices_by_frame_ambiguous(seq, &["ATA", "AAA"], &["TAT", "TTT"]);
+            assert_eq!(starts, vec![Vec::<usize>::new(); 3]);
+            assert_eq!(stops, vec![Vec::<usize>::new(); 3]);
+
+            let mut starts = vec![Vec::new(); 4];
+            let mut stops = vec![Vec::new(); 4];
+            start_stop_codon_indices_by_frame_sized_into(
+                seq,
+                4,
+                &["ATAT"],
+                &["TATA"],
+                true,
+                &mut starts,
+                &mut stops,
+            );
+            assert_eq!(starts, vec![Vec::<usize>::new(); 4]);
+            assert_eq!(stops, vec![Vec::<usize>::new(); 4]);
+        }
+    }
+
+    #[test]
+    fn overlap_across_origin() {
+        let orf = |start, length| Orf {
+            start,
+            stop: None,
+            wraps: 0,
+            length,
+        };
+        assert_eq!(orf(0, 9).overlap(&orf(4, 9), 20), 5);
+        assert_eq!(orf(4, 9).overlap(&orf(0, 9), 20), 5);
+        assert!(!orf(0, 9).overlaps(&orf(9, 9), 20));
+        // 15..24 wraps to 15..20 and 0..4
+        assert_eq!(orf(15, 9).overlap(&orf(1, 6), 20), 3);
+        assert_eq!(orf(1, 6).overlap(&orf(15, 9), 20), 3);
+        // each reaches around the origin into the other's start
+        assert_eq!(orf(2, 16).overlap(&orf(15, 9), 20), 3 + 2);
+        // an ORF longer than the sequence covers all of it
+        assert_eq!(orf(3, 60).overlap(&orf(15, 9), 20), 9);
+        assert_eq!(orf(1, 4).frame(), 1);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_round_trip() {
+        let orfs = [
+            Orf {
+                start: 4,
+                stop: Some(10),
+                wraps: 1,
+                length: 12,
+            },
+            Orf {
+                start: 0,
+                stop: None,
+                wraps: STOPLESS_WRAPS,
+                length: 30,
+            },
+        ];
+        let json = serde_json::to_string(&orfs).unwrap();
+        assert_eq!(
+            json,
+            r#"[{"start":4,"stop":10,"wraps":1,"length":12},{"start":0,"stop":null,"wraps":3,"length":30}]"#
+        );
+        assert_eq!(serde_json::from_str::<Vec<Orf>>(&json).unwrap(), orfs);
+    }
+
+    #[test]
+    fn prefer_atg_within_window() {
+        let seq = "GTGAAAATGCCCCCCTAA";
+        //         ^^^   ^^^      ^^^
+        let (starts, stops) =
+            start_stop_codon_indices_by_frame_naive(seq, &["ATG", "GTG"], &["TAA", "TAG", "TGA"]);
+        let mut orfs = find_orfs_with_indices(seq.len(), starts, stops);
+
+        // the GTG is the longest ORF, so it wins by default
+        assert_eq!(longest_orfs(&mut orfs.clone())[0].start, 0);
+        // the ATG is 6 nt downstream, so it's only preferred if the window allows it
+        assert_eq!(
+            longest_orfs_preferring(&mut orfs.clone(), seq.as_bytes(), b"ATG", 6)[0].start,
+            6
+        );
+        assert_eq!(
+            longest_orfs_preferring(&mut orfs, seq.as_bytes(), b"ATG", 5)[0].start,
+            0
+        );
+    }
+
+    #[test]
+    fn ambiguous_codons() {
+        let seq = "ATRCAACCCTRRGGG";
+        //         ^^^      ^^^
+        let (starts, stops) =
+            start_stop_codon_indices_by_frame_ambiguous(seq, &["ATG"], &["TAA", "TAG", "TGA"]);
+        assert_eq!(starts, vec![vec![0], vec![], vec![]]);
+        assert_eq!(stops, vec![vec![9], vec![], vec![]]);
+
+        // without expanding the ambiguity codes, nothing matches
+        let (starts, stops) =
+            start_stop_codon_indices_by_frame_naive(seq, &["ATG"], &["TAA", "TAG", "TGA"]);
+        assert_eq!(starts, vec![Vec::<usize>::new(); 3]);
+        assert_eq!(stops, vec![Vec::<usize>::new(); 3]);
+    }
+
+    #[test]
+    fn n_is_not_expanded() {
+        assert!(!iupac_matches(b'N', b'A'));
+        assert!(iupac_matches(b'r', b'G'));
+        assert!(!iupac_matches(b'R', b'C'));
+    }
+
     #[test]
     fn regular_linear_orf() {
         let seq: &str = "AAAATGCCCCCCCCCTAA";
@@ -568,6 +1375,290 @@ mod test {
         );
     }
 
+    #[test]
+    fn stopless_frame_is_reported_once() {
+        // frame 0 has two start codons and no stop codon, so it codes around the circle forever
+        let seq = "ATGGCCATGGCC";
+        let orfs = find_orfs(seq);
+        assert_eq!(
+            orfs,
+            vec![Orf {
+                start: 0,
+                stop: None,
+                wraps: STOPLESS_WRAPS,
+                length: 12
+            }]
+        );
+        assert!(orfs[0].is_stopless_circular());
+    }
+
+    #[test]
+    fn stopless_frames_merge_when_not_mod_0() {
+        // the length isn't a multiple of 3, so reading passes through all three frames before repeating
+        let seq = "GCCATGGCCATGGC";
+        //            ^^^   ^^^
+        let orfs = find_orfs(seq);
+        assert_eq!(
+            orfs,
+            vec![Orf {
+                start: 3,
+                stop: None,
+                wraps: STOPLESS_WRAPS,
+                length: 42
+            }]
+        );
+    }
+
+    #[test]
+    fn longest_orfs_keeps_stopless_frames_apart() {
+        // frames 0 and 1 both have a start codon but no stop codon
+        let seq = "ATGCATGCCGCC";
+        //         ^^^ ^^^
+        let mut orfs = find_orfs(seq);
+        assert_eq!(orfs.len(), 2);
+        assert_eq!(longest_orfs(&mut orfs).len(), 2);
+    }
+
+    #[test]
+    fn longest_orfs_tie_break() {
+        // equally long ORFs for the same stop codon, e.g. from different passes around a circular sequence
+        let orf = |start| Orf {
+            start,
+            stop: Some(9),
+            wraps: 0,
+            length: 12,
+        };
+        for mut orfs in [vec![orf(3), orf(0)], vec![orf(0), orf(3)]] {
+            assert_eq!(longest_orfs(&mut orfs), vec![orf(0)]);
+        }
+    }
+
+    #[test]
+    fn translate_orf() {
+        let seq = "AATGCCCGGGTTTTAAC";
+        let orfs = find_orfs(seq);
+        assert_eq!(translate(orfs[0].seq(seq.as_bytes()).as_bytes()), "MPGF*");
+    }
+
+    #[test]
+    fn initiator_is_methionine() {
+        let start_codons = ["ATG", "GTG", "TTG"];
+        assert_eq!(translate(b"GTGAAATAA"), "VK*");
+        assert_eq!(translate_with_initiator(b"GTGAAATAA", &start_codons), "MK*");
+        assert_eq!(translate_with_initiator(b"uugaaa", &start_codons), "MK");
+        // only recognized start codons are changed
+        assert_eq!(translate_with_initiator(b"GTGAAA", &["ATG"]), "VK");
+        assert_eq!(translate_with_initiator(b"", &start_codons), "");
+    }
+
+    #[test]
+    fn orf_protein_stops_at_stop_codon() {
+        let table = CodonTable::standard();
+        let seq = b"AATGCCCGGGTTTTAAC";
+        let orf = find_orfs(std::str::from_utf8(seq).unwrap())[0];
+        assert_eq!(orf.protein(seq, &table), "MPGF");
+
+        // reading around the origin, the ORF is ATG AAA TAA
+        let seq = b"AAATAAATG";
+        let orf = Orf {
+            start: 6,
+            stop: Some(3),
+            wraps: 1,
+            length: 9,
+        };
+        assert_eq!(orf.protein(seq, &table), "MK");
+
+        // a stop-less circular ORF is translated over its whole length
+        let orf = Orf {
+            start: 0,
+            stop: None,
+            wraps: STOPLESS_WRAPS,
+            length: 6,
+        };
+        assert_eq!(orf.protein(b"ATGAAA", &table), "MK");
+
+        // other genetic codes, here the vertebrate mitochondrial code (NCBI table 2), where TGA is tryptophan
+        let mitochondrial = CodonTable::ncbi(2).unwrap();
+        let orf = Orf {
+            start: 0,
+            stop: Some(6),
+            wraps: 0,
+            length: 9,
+        };
+        assert_eq!(orf.protein(b"ATGTGATAA", &table), "M*");
+        assert_eq!(orf.protein(b"ATGTGATAA", &mitochondrial), "MW");
+    }
+
+    #[test]
+    fn protein_with_opts() {
+        let table = CodonTable::standard();
+        // a GTG start, which is valine when it isn't the initiator
+        let orf = Orf {
+            start: 0,
+            stop: Some(6),
+            wraps: 0,
+            length: 9,
+        };
+        let seq = b"GTGAAATAA";
+        assert_eq!(orf.protein_with_opts(seq, &table, false, None), "VK");
+        assert_eq!(orf.protein_with_opts(seq, &table, true, None), "VK*");
+        assert_eq!(
+            orf.protein_with_opts(seq, &table, true, Some(&["ATG", "GTG"])),
+            "MK*"
+        );
+
+        // a stop-less circular ORF has no stop codon to include or leave out
+        let orf = Orf {
+            start: 0,
+            stop: None,
+            wraps: STOPLESS_WRAPS,
+            length: 9,
+        };
+        for include_stop in [false, true] {
+            assert_eq!(
+                orf.protein_with_opts(b"ATGAAAAAA", &table, include_stop, Some(&["ATG"])),
+                "MKK"
+            );
+        }
+    }
+
+    #[test]
+    fn quadruplet_codons() {
+        // ATGC starts and TAAG stops, with one codon between them
+        let seq = "CCATGCGGGGTAAGCC";
+        let (start_codons, stop_codons) = (["ATGC"], ["TAAG"]);
+        let mut starts = vec![Vec::new(); 4];
+        let mut stops = vec![Vec::new(); 4];
+        start_stop_codon_indices_by_frame_sized_into(
+            seq,
+            4,
+            &start_codons,
+            &stop_codons,
+            false,
+            &mut starts,
+            &mut stops,
+        );
+        assert_eq!(starts, [vec![], vec![], vec![2], vec![]]);
+        assert_eq!(stops, [vec![], vec![], vec![10], vec![]]);
+
+        let mut orfs = Vec::new();
+        find_orfs_with_codon_size_into(seq.len(), 4, &starts, &stops, &mut orfs);
+        assert_eq!(
+            orfs,
+            [Orf {
+                start: 2,
+                stop: Some(10),
+                wraps: 0,
+                length: 12,
+            }]
+        );
+        assert_eq!(orfs[0].seq(seq.as_bytes()), "ATGCGGGGTAAG");
+
+        // with a length of 17, reading passes through every frame before returning to the start codon
+        let seq = "ATGCCCCCCCCCCCCCC";
+        start_stop_codon_indices_by_frame_sized_into(
+            seq,
+            4,
+            &start_codons,
+            &stop_codons,
+            false,
+            &mut starts,
+            &mut stops,
+        );
+        find_orfs_with_codon_size_into(seq.len(), 4, &starts, &stops, &mut orfs);
+        assert_eq!(
+            orfs,
+            [Orf {
+                start: 0,
+                stop: None,
+                wraps: 4,
+                length: 68,
+            }]
+        );
+
+        // the stop codon TAAG crosses the origin, which counts as a wrap
+        let seq = "AGCCCCATGCCCCCCCCCTA";
+        start_stop_codon_indices_by_frame_sized_into(
+            seq,
+            4,
+            &start_codons,
+            &stop_codons,
+            false,
+            &mut starts,
+            &mut stops,
+        );
+        find_orfs_with_codon_size_into(seq.len(), 4, &starts, &stops, &mut orfs);
+        assert_eq!(
+            orfs,
+            [Orf {
+                start: 6,
+                stop: Some(18),
+                wraps: 1,
+                length: 16,
+            }]
+        );
+    }
+
+    #[test]
+    fn ncbi_tables() {
+        let standard = CodonTable::standard();
+        assert_eq!(standard, CodonTable::ncbi(1).unwrap());
+        assert_eq!(standard.start_codons(), ["TTG", "CTG", "ATG"]);
+        assert_eq!(standard.stop_codons(), ["TAA", "TAG", "TGA"]);
+
+        let bacterial = CodonTable::ncbi(11).unwrap();
+        assert_eq!(
+            bacterial.start_codons(),
+            ["TTG", "CTG", "ATT", "ATC", "ATA", "ATG", "GTG"]
+        );
+        assert_eq!(
+            bacterial.translate(b"ATGTGA"),
+            standard.translate(b"ATGTGA")
+        );
+
+        let mycoplasma = CodonTable::ncbi(4).unwrap();
+        assert_eq!(mycoplasma.stop_codons(), ["TAA", "TAG"]);
+        assert_eq!(mycoplasma.translate(b"TGA"), "W");
+
+        let mitochondrial = CodonTable::ncbi(2).unwrap();
+        assert_eq!(mitochondrial.stop_codons(), ["TAA", "TAG", "AGA", "AGG"]);
+        assert_eq!(
+            mitochondrial.translate_with_initiator(b"ATTATA", &mitochondrial.start_codons()),
+            "MM"
+        );
+
+        for id in [0, 7, 8, 27, 28, 31, 255] {
+            assert_eq!(CodonTable::ncbi(id), None);
+        }
+    }
+
+    #[test]
+    fn find_orfs_with_table_uses_its_codons() {
+        // TGA is a stop codon in the standard code but tryptophan in Mycoplasma
+        let seq = "ATGTGAAAATAA";
+        let standard = find_orfs_with_table(seq, &CodonTable::standard());
+        assert!(standard
+            .iter()
+            .any(|orf| orf.start == 0 && orf.stop == Some(3)));
+        let mycoplasma = find_orfs_with_table(seq, &CodonTable::ncbi(4).unwrap());
+        assert!(mycoplasma
+            .iter()
+            .any(|orf| orf.start == 0 && orf.stop == Some(9)));
+        assert_eq!(
+            start_stop_codon_indices_by_frame_with_table(seq, &CodonTable::standard()),
+            start_stop_codon_indices_by_frame_naive(
+                seq,
+                &["TTG", "CTG", "ATG"],
+                &["TAA", "TAG", "TGA"]
+            )
+        );
+    }
+
+    #[test]
+    fn translate_ignores_partial_codon_and_ambiguity() {
+        assert_eq!(translate(b"auggcNAAAT"), "MXK");
+    }
+
     /// Use proptest to ensure that the orfs are the same as the ones generated by Rust-Bio
     mod fuzz {
         use super::*;
@@ -577,7 +1668,7 @@ mod test {
             #[test]
             fn test_bio_orfs(seq in "[ATGC]{3,300}") {
                 let finder = Finder::new(vec![b"ATG"], vec![b"TAA", b"TAG", b"TGA"], 0);
-                let bio_orfs: Vec<bio::seq_analysis::orf::Orf> = finder.find_all(seq.as_bytes()).into_iter().collect::<Vec<_>>();
+                let bio_orfs: Vec<bio::seq_analysis::orf::Orf> = finder.find_all(seq.as_bytes()).collect::<Vec<_>>();
                 let circkit_orfs: Vec<Orf> = find_orfs(&seq);
 
                 // for each bio orf, make sure there is a circkit orf that is the same
@@ -590,7 +1681,7 @@ mod test {
                     prop_assert!(circkit_orf.is_some(), "Circkit ORF: {:?} not found in bio orf: {:?}", circkit_orf, bio_orf);
 
                     // We know that Rust-Bio doesn't work on wrapped ORFs, so any one that matches should not be wrapped
-                    prop_assert_eq!(circkit_orf.unwrap().wraps, 0, "Circkit ORF: {:?} ({:?}) should not be a wrapped ORF", circkit_orf, circkit_orf.unwrap().seq(&seq.as_bytes()));
+                    prop_assert_eq!(circkit_orf.unwrap().wraps, 0, "Circkit ORF: {:?} ({:?}) should not be a wrapped ORF", circkit_orf, circkit_orf.unwrap().seq(seq.as_bytes()));
 
                     if circkit_orf.is_some() {
                         prop_assert_eq!((circkit_orf.unwrap().start % 3) as i8, bio_orf.offset, "Circkit {:?} not in same offset as Bio {:?}", circkit_orf, bio_orf);
@@ -618,7 +1709,7 @@ mod test {
                 // Quadruple the sequence and make sure that the ORFs are the same
                 let dup_seq = format!("{}{}{}{}", seq, seq, seq, seq);
                 let finder = Finder::new(vec![b"ATG"], vec![b"TAA", b"TAG", b"TGA"], 0);
-                let bio_orfs: Vec<bio::seq_analysis::orf::Orf> = finder.find_all(dup_seq.as_bytes()).into_iter().collect::<Vec<_>>();
+                let bio_orfs: Vec<bio::seq_analysis::orf::Orf> = finder.find_all(dup_seq.as_bytes()).collect::<Vec<_>>();
                 let circkit_orfs: Vec<Orf> = find_orfs(&seq);
 
                 // for each bio orf, make sure there is a circkit orf that is the same
@@ -654,7 +1745,7 @@ mod test {
                 let mut orfs = find_orfs(&seq);
                 let longest_orfs = longest_orfs(&mut orfs);
                 for orf in &longest_orfs {
-                    prop_assert!(orfs.contains(&orf), "Longest ORF: {:?} is not in all ORFs: {:?}", orf, orfs);
+                    prop_assert!(orfs.contains(orf), "Longest ORF: {:?} is not in all ORFs: {:?}", orf, orfs);
                 }
                 prop_assert!(longest_orfs.len() <= orfs.len(), "Longest ORFs: {:?} is not a subset of all ORFs: {:?}", longest_orfs, orfs);
             }
@@ -663,9 +1754,51 @@ mod test {
             fn indexing_is_identical(seq in "[ATGC]{3,300}"){
                 let start_codons = ["ATG"];
                 let stop_codons = ["TAA", "TAG", "TGA"];
-                let ac = aho_corasick::AhoCorasick::new(&["ATG", "TAA", "TAG", "TGA"]).unwrap();
+                let ac = aho_corasick::AhoCorasick::new(["ATG", "TAA", "TAG", "TGA"]).unwrap();
                 prop_assert_eq!(start_stop_codon_indices_by_frame_naive(&seq, &start_codons, &stop_codons), start_stop_codon_indices_by_frame_iter(&seq, &start_codons, &stop_codons));
                 prop_assert_eq!(start_stop_codon_indices_by_frame_naive(&seq, &start_codons, &stop_codons), start_stop_codon_indices_by_frame_aho_corasick(&seq, &start_codons, &stop_codons, &ac));
+                prop_assert_eq!(start_stop_codon_indices_by_frame_naive(&seq, &start_codons, &stop_codons), start_stop_codon_indices_by_frame_ambiguous(&seq, &start_codons, &stop_codons));
+                let (mut starts, mut stops) = (vec![Vec::new(); 3], vec![Vec::new(); 3]);
+                start_stop_codon_indices_by_frame_sized_into(&seq, 3, &start_codons, &stop_codons, false, &mut starts, &mut stops);
+                prop_assert_eq!(start_stop_codon_indices_by_frame_naive(&seq, &start_codons, &stop_codons), (starts, stops));
+            }
+
+            #[test]
+            fn codon_size_three_finds_the_same_orfs(seq in "[ATGC]{3,300}", short in "[ATGC]{3,40}") {
+                for seq in [seq, short] {
+                    let (starts, stops) = start_stop_codon_indices_by_frame_naive(&seq, &["ATG", "GTG"], &["TAA", "TAG", "TGA"]);
+                    let mut orfs = Vec::new();
+                    find_orfs_with_codon_size_into(seq.len(), 3, &starts, &stops, &mut orfs);
+                    prop_assert_eq!(orfs, find_orfs_with_indices(seq.len(), starts, stops));
+                }
+            }
+
+            #[test]
+            fn orfs_end_in_stop_codons_of_any_size(seq in "[AC]{1,60}", codon_size in 1..6usize) {
+                let (start_codons, stop_codons) = (["A".repeat(codon_size)], ["C".repeat(codon_size)]);
+                let start_codons = start_codons.iter().map(String::as_str).collect::<Vec<_>>();
+                let stop_codons = stop_codons.iter().map(String::as_str).collect::<Vec<_>>();
+                let (mut starts, mut stops) = (vec![Vec::new(); codon_size], vec![Vec::new(); codon_size]);
+                start_stop_codon_indices_by_frame_sized_into(&seq, codon_size, &start_codons, &stop_codons, false, &mut starts, &mut stops);
+                let mut orfs = Vec::new();
+                find_orfs_with_codon_size_into(seq.len(), codon_size, &starts, &stops, &mut orfs);
+                for orf in orfs {
+                    prop_assert_eq!(orf.length % codon_size, 0);
+                    prop_assert!(orf.wraps <= codon_size);
+                    let orf_seq = orf.seq(seq.as_bytes());
+                    prop_assert!(orf_seq.starts_with(start_codons[0]));
+                    match orf.stop {
+                        Some(_) => prop_assert!(orf_seq.ends_with(stop_codons[0])),
+                        None => prop_assert_eq!(orf.length, seq.len() / gcd(seq.len(), codon_size) * codon_size),
+                    }
+                    // no stop codon before the one ending the ORF
+                    let codons = orf_seq.as_bytes().chunks(codon_size).collect::<Vec<_>>();
+                    let inner = match orf.stop {
+                        Some(_) => &codons[..codons.len() - 1],
+                        None => &codons[..],
+                    };
+                    prop_assert!(inner.iter().all(|&codon| codon != stop_codons[0].as_bytes()));
+                }
             }
         }
     }
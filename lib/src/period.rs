@@ -0,0 +1,108 @@
+/// Compute the period of a circular sequence, i.e. the length of the smallest unit that tiles it exactly.
+///
+/// A sequence without any repeating structure has a period equal to its length.
+/// Since the sequence is circular, only periods that divide its length are considered.
+/// This uses the KMP failure function, so it runs in linear time.
+pub fn period(seq: &[u8]) -> usize {
+    let n = seq.len();
+    if n == 0 {
+        return 0;
+    }
+
+    // failure[i] is the length of the longest proper prefix of seq[..=i] that is also a suffix of it
+    let mut failure = vec![0; n];
+    let mut k = 0;
+    for i in 1..n {
+        while k > 0 && seq[i] != seq[k] {
+            k = failure[k - 1];
+        }
+        if seq[i] == seq[k] {
+            k += 1;
+        }
+        failure[i] = k;
+    }
+
+    // the smallest linear period is n - failure[n - 1], and the sequence only tiles if it divides n
+    let p = n - failure[n - 1];
+    if n.is_multiple_of(p) {
+        p
+    } else {
+        n
+    }
+}
+
+/// Compute the period of a circular sequence, allowing the copies of the repeating unit to differ.
+///
+/// For each candidate period, the copies are aligned and the number of mismatches is the number of bases that disagree with the consensus of their column.
+/// The smallest period that divides the sequence length and has at most `max_mismatch` mismatches is returned.
+/// With `max_mismatch` set to 0, this is equivalent to [`period`].
+pub fn period_with_mismatches(seq: &[u8], max_mismatch: usize) -> usize {
+    let n = seq.len();
+    if max_mismatch == 0 {
+        return period(seq);
+    }
+
+    (1..n)
+        .filter(|&p| n.is_multiple_of(p))
+        .find(|&p| consensus_mismatches(seq, p, max_mismatch) <= max_mismatch)
+        .unwrap_or(n)
+}
+
+/// Count the bases that disagree with the consensus when `seq` is split into copies of length `p`.
+///
+/// Counting stops early once `limit` is exceeded.
+fn consensus_mismatches(seq: &[u8], p: usize, limit: usize) -> usize {
+    let copies = seq.len() / p;
+    let mut counts = [0usize; 256];
+    let mut mismatches = 0;
+
+    for column in 0..p {
+        let bases = seq[column..].iter().step_by(p);
+        let mut most_common = 0;
+        for &base in bases.clone() {
+            counts[base as usize] += 1;
+            most_common = most_common.max(counts[base as usize]);
+        }
+        for &base in bases {
+            counts[base as usize] = 0;
+        }
+
+        mismatches += copies - most_common;
+        if mismatches > limit {
+            break;
+        }
+    }
+    mismatches
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case(b"", 0)]
+    #[case(b"A", 1)]
+    #[case(b"AAAA", 1)]
+    #[case(b"ATGATGATG", 3)]
+    #[case(b"ATGCATGC", 4)]
+    #[case(b"ATGCA", 5)]
+    // the linear period is 3 but it doesn't tile the circle
+    #[case(b"ATGATGAT", 8)]
+    fn exact(#[case] seq: &[u8], #[case] expected: usize) {
+        assert_eq!(period(seq), expected);
+    }
+
+    #[rstest]
+    #[case(b"ATGATGATG", 0, 3)]
+    #[case(b"ATGATCATG", 0, 9)]
+    #[case(b"ATGATCATG", 1, 3)]
+    // a mutation in one of only two copies is still a single disagreement
+    #[case(b"ATGCATGG", 1, 4)]
+    #[case(b"ATGATCATT", 1, 9)]
+    #[case(b"ATGATCATT", 2, 3)]
+    #[case(b"ATGCA", 10, 1)]
+    fn tolerant(#[case] seq: &[u8], #[case] max_mismatch: usize, #[case] expected: usize) {
+        assert_eq!(period_with_mismatches(seq, max_mismatch), expected);
+    }
+}
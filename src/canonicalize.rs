@@ -1,40 +1,284 @@
 use crate::{
     commands::Command,
-    utils::{input_to_reader, output_to_writer},
+    profile::{self, Stage},
+    uniq::{uniq, DedupBy, StrandConvention},
+    utils::{
+        auxiliary_output_to_writer, input_to_reader, normalize_in, output_to_writer, thread_count,
+        write_record, LengthFilter, OutputAlphabet,
+    },
 };
+use circkit::canonicalize::lmsr_index;
+use log::warn;
 use seq_io::{fasta::Record, parallel::parallel_fasta};
 
+/// Whether a sequence only contains nucleotides (and line breaks), i.e. normalization won't replace anything with N.
+pub fn is_valid(seq: &[u8]) -> bool {
+    seq.iter().all(|b| {
+        matches!(
+            b.to_ascii_uppercase(),
+            b'A' | b'C' | b'G' | b'T' | b'U' | b'N' | b'\n' | b'\r'
+        )
+    })
+}
+
+/// The alphabet whose base order canonicalization uses.
+#[derive(clap::ArgEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CanonicalAlphabet {
+    /// Compare bases by byte value, so U sorts after T
+    Dna,
+    /// Rank U where T would be and complement A as U, so RNA canonicalizes like its DNA equivalent.
+    /// Sequences are written with U in place of T
+    Rna,
+}
+
+/// What to write for a record, as decided by a worker.
+#[derive(Default)]
+enum Canonical {
+    /// The record is outside the length range, so nothing is written
+    #[default]
+    Skipped,
+    /// The record contains invalid characters and is written unchanged
+    Passthrough,
+    /// The canonical forms to write, each with the rotation applied, whether it is of the reverse complement, and whether it is the (normalized) input unchanged
+    Forms(Vec<(Vec<u8>, usize, bool, bool)>),
+}
+
+/// The index in the canonical sequence of what was position 0 of the input, given the rotation reported by [`circkit::canonicalize_with_offset`].
+///
+/// On the reverse strand, position 0 of the input is the last base of the reverse complement.
+pub fn origin_position(len: usize, offset: usize, reverse: bool) -> usize {
+    match reverse {
+        false => (len - offset) % len,
+        true => (2 * len - 1 - offset) % len,
+    }
+}
+
+/// Rotate `seq` so that it starts at index `i`.
+fn rotate(seq: &[u8], i: usize) -> Vec<u8> {
+    [&seq[i..], &seq[..i]].concat()
+}
+
+/// Lowercase the bases of a canonical form that were lowercase in the input, given which input bases were (`lowercase`) and the rotation and strand it was made with.
+fn restore_case(seq: &mut [u8], lowercase: &[bool], offset: usize, reverse: bool) {
+    let n = lowercase.len();
+    for (i, base) in seq.iter_mut().enumerate() {
+        // the reverse complement reads the input backwards
+        let position = match reverse {
+            false => (offset + i) % n,
+            true => n - 1 - (offset + i) % n,
+        };
+        if lowercase[position] {
+            *base = base.to_ascii_lowercase();
+        }
+    }
+}
+
 pub fn canonicalize(cmd: &Command) -> anyhow::Result<()> {
     match cmd {
         Command::Canonicalize {
             input,
             output,
-            threads,
+            keep_original,
+            passthrough_invalid,
+            annotate_offset,
+            report_canonical_equal_original,
+            offset_bed,
+            linear,
+            both_strands,
+            alphabet,
+            rna,
+            unique,
+            preserve_case,
+            min_length,
+            max_length,
+            parallel,
         } => {
+            // --rna is a shorthand for --alphabet rna
+            let alphabet = match rna {
+                true => &CanonicalAlphabet::Rna,
+                false => alphabet,
+            };
+
+            // deduplicating canonicalizes the records anyway, so leave it all to uniq
+            if *unique {
+                return uniq(&Command::Uniq {
+                    input: input.clone(),
+                    output: output.clone(),
+                    canonicalize: true,
+                    strand_convention: StrandConvention::Lexmin,
+                    rna: *alphabet == CanonicalAlphabet::Rna,
+                    dedup_by: DedupBy::Sequence,
+                    min_length: *min_length,
+                    max_length: *max_length,
+                    table: None,
+                    table_all: None,
+                    parallel: *parallel,
+                });
+            }
+            let length_filter = LengthFilter::new(*min_length, *max_length)?;
             let reader = input_to_reader(input)?;
             let mut writer = output_to_writer(output)?;
+            let mut bed_writer = match offset_bed {
+                Some(_) => Some(auxiliary_output_to_writer(offset_bed)?),
+                None => None,
+            };
 
             parallel_fasta(
                 reader,
-                *threads,
-                64,
-                |record, seq| {
+                thread_count(parallel.threads),
+                parallel.batch_size,
+                |record, canonical: &mut Canonical| {
                     // runs in worker
+                    let _timer = profile::Timer::start(Stage::Compute);
+
+                    if !length_filter.keep(record.seq()) {
+                        *canonical = Canonical::Skipped;
+                        return;
+                    }
+
+                    if *passthrough_invalid && !is_valid(record.seq()) {
+                        *canonical = Canonical::Passthrough;
+                        return;
+                    }
+
+                    let normalized = normalize_in(
+                        record.seq(),
+                        match alphabet {
+                            CanonicalAlphabet::Dna => OutputAlphabet::Dna,
+                            CanonicalAlphabet::Rna => OutputAlphabet::Rna,
+                        },
+                    );
 
-                    let normalized = match needletail::sequence::normalize(record.seq(), false) {
-                        Some(x) => x,
-                        None => record.seq().to_vec(),
+                    let forms = match (linear, both_strands) {
+                        (true, _) => {
+                            let seq = circkit::canonicalize_linear(&normalized);
+                            let reverse = seq != normalized;
+                            vec![(seq, 0, reverse)]
+                        }
+                        (false, true) => {
+                            let revcomp = bio::alphabets::dna::revcomp(&normalized);
+                            let forward_index = lmsr_index(&normalized);
+                            let revcomp_index = lmsr_index(&revcomp);
+                            vec![
+                                (rotate(&normalized, forward_index), forward_index, false),
+                                (rotate(&revcomp, revcomp_index), revcomp_index, true),
+                            ]
+                        }
+                        (false, false) => match alphabet {
+                            CanonicalAlphabet::Dna => {
+                                vec![circkit::canonicalize_with_offset(&normalized)]
+                            }
+                            CanonicalAlphabet::Rna => {
+                                vec![circkit::canonicalize_with_order(
+                                    &normalized,
+                                    circkit::canonicalize::rna_rank,
+                                    circkit::canonicalize::rna_complement,
+                                )]
+                            }
+                        },
                     };
 
-                    *seq = circkit::canonicalize(&normalized);
+                    // compared before the case is restored, so that only the rotation and strand matter
+                    let mut forms = forms
+                        .into_iter()
+                        .map(|(seq, offset, reverse)| {
+                            let unchanged = seq == normalized;
+                            (seq, offset, reverse, unchanged)
+                        })
+                        .collect::<Vec<_>>();
+
+                    if *preserve_case {
+                        // normalization drops whitespace, so the remaining bases line up with the normalized ones
+                        let lowercase = record
+                            .seq()
+                            .iter()
+                            .filter(|b| !b.is_ascii_whitespace())
+                            .map(u8::is_ascii_lowercase)
+                            .collect::<Vec<_>>();
+                        for (seq, offset, reverse, _) in forms.iter_mut() {
+                            restore_case(seq, &lowercase, *offset, *reverse);
+                        }
+                    }
+                    *canonical = Canonical::Forms(forms);
                 },
-                |record, seq| {
+                |record, canonical| {
                     // runs in main thread
-                    writer.write_all(b">").unwrap();
-                    writer.write_all(record.head()).unwrap();
-                    writer.write_all(b"\n").unwrap();
-                    writer.write_all(seq).unwrap();
-                    writer.write_all(b"\n").unwrap();
+                    let forms = match canonical {
+                        Canonical::Forms(forms) => forms,
+                        Canonical::Skipped => return None,
+                        Canonical::Passthrough => {
+                            warn!(
+                                "{} contains characters other than A, C, G, T, U, and N, so it was written unchanged",
+                                record.id().unwrap_or("A record")
+                            );
+                            write_record(&mut writer, &[record.head()], &[&record.full_seq()])
+                                .unwrap();
+                            return None;
+                        }
+                    };
+
+                    if let (Some(bed_writer), [(seq, offset, reverse, _)]) =
+                        (&mut bed_writer, forms.as_slice())
+                    {
+                        if !seq.is_empty() {
+                            let position = origin_position(seq.len(), *offset, *reverse);
+                            writeln!(
+                                bed_writer,
+                                "{}\t{}\t{}\torigin\t0\t{}",
+                                record.id().unwrap(),
+                                position,
+                                position + 1,
+                                if *reverse { '-' } else { '+' }
+                            )
+                            .expect("failed to write to BED file");
+                        }
+                    }
+
+                    for (seq, offset, reverse, unchanged) in forms.iter() {
+                        let strand = if *reverse { "-" } else { "+" };
+                        let annotation = match annotate_offset {
+                            true => format!(" rot={} strand={}", offset, strand),
+                            false => String::new(),
+                        };
+                        let strand_suffix: &[u8] = match (both_strands, reverse) {
+                            (true, false) => b" +",
+                            (true, true) => b" -",
+                            (false, _) => b"",
+                        };
+                        let canonical_suffix: &[u8] = match keep_original {
+                            true => b" canonical",
+                            false => b"",
+                        };
+                        let unchanged_suffix: &[u8] =
+                            match (report_canonical_equal_original, unchanged) {
+                                (true, true) => b" already_canonical=true",
+                                (true, false) => b" already_canonical=false",
+                                (false, _) => b"",
+                            };
+
+                        write_record(
+                            &mut writer,
+                            &[
+                                record.head(),
+                                strand_suffix,
+                                canonical_suffix,
+                                annotation.as_bytes(),
+                                unchanged_suffix,
+                            ],
+                            &[seq],
+                        )
+                        .unwrap();
+                    }
+
+                    // the original is written as it appeared in the input, before normalization
+                    if *keep_original {
+                        write_record(
+                            &mut writer,
+                            &[record.head(), b" original"],
+                            &[&record.full_seq()],
+                        )
+                        .unwrap();
+                    }
 
                     // Some(value) will stop the reader, and the value will be returned.
                     // In the case of never stopping, we need to give the compiler a hint about the
@@ -44,6 +288,10 @@ pub fn canonicalize(cmd: &Command) -> anyhow::Result<()> {
                 },
             )?;
             writer.flush()?;
+            if let Some(mut bed_writer) = bed_writer {
+                bed_writer.flush()?;
+            }
+            length_filter.report();
         }
         _ => panic!("input command is not for canonicalize"),
     }
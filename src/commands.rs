@@ -1,7 +1,15 @@
 use clap::{Parser, Subcommand};
 use std::path::PathBuf;
 
-use crate::orfs::Strand;
+use crate::{
+    canonicalize::CanonicalAlphabet,
+    gen::GenKind,
+    monomerize::{OverlapCombine, OverlapMetric, SeedLength},
+    orfs::{OrfFormat, Partial, Strand},
+    pipeline::Transform,
+    uniq::{DedupBy, StrandConvention},
+    utils::{InputFormat, OutputAlphabet, OutputFormat},
+};
 
 #[derive(Parser)]
 #[clap(name = "circkit", author, version, about, long_about = None)]
@@ -11,13 +19,72 @@ pub struct Cli {
     // Level of verbosity.
     #[clap(flatten)]
     pub verbose: clap_verbosity_flag::Verbosity,
+    /// Print the wall-clock time spent reading, computing, and writing to stderr when finished
+    #[clap(long, global = true)]
+    pub profile: bool,
+    /// Memory-map uncompressed input files instead of reading them through a buffer.
+    /// Compressed files, stdin, and other non-regular files are read normally
+    #[clap(long, global = true)]
+    pub mmap: bool,
+    /// Append " length=N" to every output header, where N is the length of the written sequence
+    #[clap(long, global = true)]
+    pub append_length: bool,
+    /// Write every output sequence as DNA (U as T) or RNA (T as U) [default: unchanged]
+    #[clap(long, global = true, arg_enum)]
+    pub output_alphabet: Option<OutputAlphabet>,
+    /// The format of the output sequences: FASTA, or txt with one sequence per line and no headers.
+    /// If not given, it is inferred from the extension of the output file (.txt, possibly compressed, is txt), and is FASTA otherwise
+    #[clap(long, global = true, arg_enum)]
+    pub output_format: Option<OutputFormat>,
+    /// Truncate every output sequence to at most N bases, e.g. for models that take fixed-size inputs.
+    /// Any --append-length is computed after truncation
+    #[clap(long, global = true, value_name = "N", value_parser = clap::value_parser!(u64).range(1..))]
+    pub trim_to_length: Option<u64>,
+    /// Discard all output instead of writing it, for measuring throughput without disk IO
+    #[clap(long, global = true, hide = true)]
+    pub null_output: bool,
+    /// Write each output file under a temporary name and only move it into place once the command has succeeded.
+    /// This way, an interrupted or failed run never leaves a partial output file behind
+    #[clap(long, global = true)]
+    pub atomic: bool,
+    /// Split the output into files of at most N bytes (before compression), numbered from 1 before the extension, e.g. out.1.fasta.gz.
+    /// A new file is only started between records, so a record longer than N bytes gets a file of its own.
+    /// Requires --output
+    #[clap(long, global = true, value_name = "N", value_parser = clap::value_parser!(u64).range(1..))]
+    pub chunk_bytes: Option<u64>,
+    /// A path for a histogram of the lengths of all output sequences, as a CSV or TSV file depending on the extension.
+    /// The following columns are output: min_length, max_length, count.
+    /// Lengths are bucketed by powers of two (1, 2-3, 4-7, ...) and are measured after --trim-to-length
+    #[clap(long, global = true, value_name = "PATH")]
+    pub histogram: Option<PathBuf>,
+    /// Exit with code 4 if no records were written
+    #[clap(long, global = true)]
+    pub fail_on_empty: bool,
+    /// Only process the record with this id.
+    /// If the input file has a samtools faidx index next to it (e.g. in.fasta.fai), the record is read directly instead of scanning the whole file
+    #[clap(long, global = true)]
+    pub id: Option<String>,
+    /// The format of the input records. JSON Lines input is converted to FASTA as it is read
+    #[clap(long, global = true, arg_enum, default_value = "fasta")]
+    pub input_format: InputFormat,
+    /// Skip this many records at the start of the input.
+    /// Together with --limit, this allows splitting the input into shards without splitting the file
+    #[clap(long, global = true, default_value_t = 0)]
+    pub skip: usize,
+    /// Stop after reading this many records (after any skipped with --skip)
+    #[clap(long, global = true)]
+    pub limit: Option<usize>,
+    /// Skip records without a single unambiguous base (e.g. all N), which have no meaningful canonical form, monomer, or ORFs, with a warning for each.
+    /// Empty records aren't skipped
+    #[clap(long, global = true)]
+    pub skip_all_n: bool,
 }
 
 #[derive(Subcommand, Debug)]
 pub enum Command {
     /// Find monomers of (potentially) circular or multimeric sequences
     Monomerize {
-        /// Input FASTA file. May be gzip, bzip, xz, or zstd compressed [default: stdin]
+        /// Input FASTA file, or - for stdin. May be gzip, bzip, xz, or zstd compressed [default: stdin]
         input: Option<PathBuf>,
 
         #[clap(short, long)]
@@ -30,22 +97,76 @@ pub enum Command {
         /// Using this flag will roughly double the runtime, since each sequence must now be processed twice.
         sensitive: bool,
 
-        /// The length of the seed to search for.
-        /// Must be less than or equal to the length of the sequence but should be much smaller to be meaningful
-        #[clap(long, default_value = "10", value_parser = clap::value_parser!(u64).range(5..=64))]
-        seed_length: u64,
+        /// Trim exactly this many bases off the end of each sequence instead of searching for the overlap.
+        /// For circular contigs whose assembler reports how long the overlap between their ends is.
+        /// Sequences that aren't longer than this are treated as not monomerized
+        #[clap(
+            long,
+            value_name = "N",
+            value_parser = clap::value_parser!(u64).range(1..),
+            conflicts_with_all = &["sensitive", "seed-percent", "overlap_cutoffs", "min-identity", "require-copies", "table-overlap", "check"]
+        )]
+        end_overlap: Option<u64>,
+
+        /// Also trim a terminal inverted repeat, i.e. a 3' end that is the reverse complement of the 5' end, as in some viral genomes.
+        /// This is independent of the tandem repeats that are trimmed otherwise: the inverted repeat is looked for in whatever they leave, so sequences with either or both are trimmed.
+        /// The reverse complement of the 3' seed has to occur within the first --seed-length bases, and mismatches are counted by position
+        #[clap(long, conflicts_with_all = &["check", "table-overlap"])]
+        inverted: bool,
+
+        /// The length of the seed to search for, or auto to pick it for each sequence from its length.
+        /// Must be less than or equal to the length of the sequence but should be much smaller to be meaningful.
+        /// Auto grows with the log of the length, e.g. 13 at 100 nt and 20 at 1 kb, and is clamped to between 5 and 63
+        #[clap(long, default_value = "10")]
+        seed_length: SeedLength,
+
+        /// Set the seed length of each sequence to this fraction of its length instead of using a fixed --seed-length.
+        /// The result is clamped to between 5 and 63, the longest seed the monomerizer supports.
+        /// Useful when sequence lengths vary widely
+        #[clap(long, conflicts_with = "seed-length")]
+        seed_percent: Option<f64>,
 
         // Overlap similarity cutoffs
         #[clap(long, group = "overlap_cutoffs")]
         /// The maximum number of mismatches to allow in the overlap.
-        /// Conflicts with --min-identity
+        /// If neither this nor --min-identity is set, overlaps must match exactly.
+        /// Can be combined with --min-identity, see --overlap-combine
         max_mismatch: Option<u64>,
 
+        /// The maximum number of mismatches to allow in the overlap, as a fraction of the overlap length.
+        /// Unlike --max-mismatch, this scales with the overlap, so long overlaps may have more mismatches than short ones.
+        /// This is the complement of --min-identity, except that the number of mismatches allowed is rounded down, so it is never looser than --min-identity 1-F.
+        /// Conflicts with --max-mismatch and --min-identity
+        #[clap(long, group = "overlap_cutoffs")]
+        max_mismatch_rate: Option<f64>,
+
         /// The minimum identity the overlapping region before being considered mismatched.
-        /// Conflicts with --max-mismatch
-        #[clap(long, conflicts_with = "overlap_cutoffs")]
+        /// The identity is computed over the whole overlap, and the number of matching bases required is rounded down to the nearest nt.
+        /// Conflicts with --max-mismatch-rate. Can be combined with --max-mismatch, see --overlap-combine
+        #[clap(long, conflicts_with = "max-mismatch-rate")]
         min_identity: Option<f64>,
 
+        /// How to combine --max-mismatch and --min-identity when both are given.
+        /// "stricter" allows the fewer mismatches of the two, so an overlap must pass both cutoffs, and "looser" allows the more, so it must pass either
+        #[clap(long, arg_enum, default_value_t = OverlapCombine::Stricter)]
+        overlap_combine: OverlapCombine,
+
+        /// How to count the differences between the copies in an overlap, which the mismatch cutoffs then apply to.
+        /// "hamming" counts mismatched positions, so a single insertion or deletion makes the rest of the overlap mismatch.
+        /// "edit" counts substitutions, insertions, and deletions, which suits long noisy reads but is slower.
+        /// With "edit", the monomer boundary is off by the net length of any indels in the overlap
+        #[clap(long, arg_enum, default_value_t = OverlapMetric::Hamming)]
+        overlap_metric: OverlapMetric,
+
+        /// Print how many mismatches the overlap cutoffs allow in an overlap of --explain-overlap nt, and the identity that amounts to, then exit without reading the input.
+        /// --max-mismatch allows the same number of mismatches however long the overlap is, so it is stricter for long overlaps than for short ones
+        #[clap(long)]
+        explain: bool,
+
+        /// The overlap length (in nt) to describe the stringency of the overlap cutoffs at, for --explain and the log
+        #[clap(long, default_value_t = 200, value_name = "N", value_parser = clap::value_parser!(u64).range(1..))]
+        explain_overlap: u64,
+
         /// Minimum length of the overlap (in nt) required to keep the monomer.
         /// If the overlap is shorter than this, the monomer is discarded unless --keep-all is used, in which case the original sequence (without trimming) is output.
         /// Can be combined with --min-overlap-percent for more stringent filtering.
@@ -59,6 +180,18 @@ pub enum Command {
         #[clap(long)]
         min_overlap_percent: Option<f64>,
 
+        /// The minimum number of whole copies of the monomer the sequence must contain to keep the monomer.
+        /// A trailing partial copy is not counted, so e.g. a 2.5x sequence has 2 copies.
+        /// If --keep-all is used, sequences with too few copies are still output but as the original sequence.
+        #[clap(long)]
+        require_copies: Option<usize>,
+
+        /// The minimum mean identity between every pair of copies of the monomer, from 0.0 to 1.0.
+        /// Unlike --min-identity, which compares each copy to the first one as it is trimmed, this compares all copies with each other, rejecting multimers whose copies drift apart.
+        /// If --keep-all is used, rejected sequences are still output but as the original sequence.
+        #[clap(long, value_name = "F")]
+        min_copy_identity: Option<f64>,
+
         /// The minimum length of the monomer to keep (in nt).
         #[clap(long, default_value_t = 0)]
         min_length: usize,
@@ -73,58 +206,173 @@ pub enum Command {
         #[clap(short, long)]
         keep_all: bool,
 
+        /// Write each monomerized sequence in full instead of trimming it, with the overlap (everything after the monomer) in lowercase.
+        /// Useful for seeing what would be trimmed
+        #[clap(long, requires = "keep-all")]
+        mask_overlap: bool,
+
         /// A path for the monomerization metadata for each sequence.
-        /// The following columns are output: id, original_length, monomer_length.
+        /// The following columns are output: id, original_length, monomer_length, copies.
+        /// The copies column is the number of tandem copies found (e.g. 2 for a dimer), counting a trailing partial copy, and is 1 for sequences that weren't monomerized.
+        /// With --inverted, a sequence only trimmed at an inverted repeat has 1 copy.
         /// The file is output as a CSV or TSV depending on the file extension.
         /// Note that if no sequences are output, the output table will be an empty file.
         #[clap(long)]
         table: Option<PathBuf>,
 
-        /// The number of threads to use.
-        /// If not specified, the number of logical cores is used.
-        #[clap(short, long, default_value_t = num_cpus::get().try_into().unwrap())]
-        threads: u32,
+        /// Add overlap_len and mismatches columns to --table, describing the repeat each monomer was cut at.
+        /// For a trimer or longer, this is the last repeat trimmed off. Both are empty for sequences that weren't monomerized
+        #[clap(long, requires = "table", conflicts_with = "sensitive")]
+        table_overlap: bool,
+
+        /// A FASTA file path for the overlapping region of each monomerized sequence, i.e. the part trimmed from its end.
+        /// Useful for inspecting the repeated junction.
+        /// Sequences that were not monomerized are not written, even with --keep-all
+        #[clap(long)]
+        overlap_output: Option<PathBuf>,
+
+        /// Instead of writing monomers, write whether each sequence is a multimer as a TSV of id and true or false.
+        /// Only the first overlap is looked for, so this is faster than monomerizing.
+        /// The length and overlap filters are not applied
+        #[clap(long, conflicts_with_all = &["keep-all", "table", "overlap-output", "sensitive"])]
+        check: bool,
+
+        /// Look for overlaps in RNA space, keeping U instead of converting it to T first.
+        /// Monomers are always cut from the input as written, so their bases are unchanged either way
+        #[clap(long)]
+        rna: bool,
 
-        #[clap(long, hidden = true, default_value_t = 64)]
-        batch_size: usize,
+        #[clap(flatten)]
+        parallel: ParallelOptions,
     },
     /// Concatenate sequences to themselves
     Cat {
-        /// Input FASTA file. May be gzip, bzip, xz, or zstd compressed [default: stdin]
+        /// Input FASTA file, or - for stdin. May be gzip, bzip, xz, or zstd compressed [default: stdin]
         input: Option<PathBuf>,
 
         /// Output FASTA file path [default: stdout]
         #[clap(short, long)]
         output: Option<PathBuf>,
+
+        /// A character (e.g. N) to insert between the two copies.
+        /// Useful for preventing aligners from finding spurious matches that span both copies
+        #[clap(long)]
+        spacer: Option<char>,
+
+        /// The number of spacer characters to insert between the two copies
+        #[clap(long, default_value_t = 1, requires = "spacer")]
+        spacer_len: usize,
+
+        #[clap(flatten)]
+        parallel: ParallelOptions,
     },
 
     /// Deconcatenate sequences to themselves
     Decat {
-        /// Input FASTA file. May be gzip, bzip, xz, or zstd compressed [default: stdin]
+        /// Input FASTA file, or - for stdin. May be gzip, bzip, xz, or zstd compressed [default: stdin]
         input: Option<PathBuf>,
 
         /// Output FASTA file path [default: stdout]
         #[clap(short, long)]
         output: Option<PathBuf>,
+
+        /// The spacer character that was inserted between the two copies by `cat --spacer`
+        #[clap(long)]
+        spacer: Option<char>,
+
+        /// The number of spacer characters between the two copies
+        #[clap(long, default_value_t = 1, requires = "spacer")]
+        spacer_len: usize,
+
+        /// Fail instead of warning when a sequence has an odd length (excluding the spacer), since it can't be two copies of the same sequence
+        #[clap(long)]
+        strict: bool,
+
+        #[clap(flatten)]
+        parallel: ParallelOptions,
     },
 
     /// Normalize circular sequences.
     Canonicalize {
-        /// Input FASTA file. May be gzip, bzip, xz, or zstd compressed [default: stdin]
+        /// Input FASTA file, or - for stdin. May be gzip, bzip, xz, or zstd compressed [default: stdin]
         input: Option<PathBuf>,
 
         /// Output FASTA file path [default: stdout]
         #[clap(short, long)]
         output: Option<PathBuf>,
 
-        /// The number of threads to use.
-        /// If not specified, the number of logical cores is used.
-        #[clap(short, long, default_value_t = num_cpus::get().try_into().unwrap())]
-        threads: u32,
+        /// Also write each original sequence as a second record.
+        /// The canonical record's header is suffixed with " canonical" and the original's with " original"
+        #[clap(long)]
+        keep_original: bool,
+
+        /// Write sequences containing characters other than A, C, G, T, U, and N unchanged instead of canonicalizing them.
+        /// Normalization would otherwise replace those characters with N.
+        /// A warning is logged for each such sequence
+        #[clap(long)]
+        passthrough_invalid: bool,
+
+        /// Append the rotation applied to each sequence to its header as ` rot=N strand=+/-`.
+        /// The canonical sequence starts at 0-based position N of the input (strand=+) or of its reverse complement (strand=-)
+        #[clap(long)]
+        annotate_offset: bool,
+
+        /// Append whether each sequence was already canonical to its header as ` already_canonical=true/false`, e.g. for QC.
+        /// A sequence is already canonical if its canonical form is the input itself, i.e. rotated by 0 on the forward strand, after normalization and ignoring case.
+        /// Sequences written unchanged by --passthrough-invalid aren't tagged
+        #[clap(long)]
+        report_canonical_equal_original: bool,
+
+        /// A path for a 6-column BED file (chrom, start, end, name, score, strand) marking where position 0 of each input sequence lands in its canonical sequence.
+        /// Each line is a single-base feature named "origin", with strand "-" if the canonical sequence is of the reverse complement.
+        /// Sequences written unchanged by --passthrough-invalid are left out
+        #[clap(long, conflicts_with = "both-strands")]
+        offset_bed: Option<PathBuf>,
+
+        /// Treat the sequences as linear, so they are never rotated.
+        /// Only the strand is canonicalized: the lexicographically smaller of each sequence and its reverse complement is output
+        #[clap(long)]
+        linear: bool,
+
+        /// Write two records per sequence: the minimal rotation of the sequence itself (with " +" appended to the header) and of its reverse complement (with " -").
+        /// The lexicographically smaller of the two is what is output without this flag
+        #[clap(long, conflicts_with = "linear")]
+        both_strands: bool,
+
+        /// The alphabet whose base order to canonicalize by.
+        /// With rna, U is ranked where T would be, so an RNA sequence canonicalizes to the same form as its DNA equivalent (with T written as U)
+        #[clap(long, arg_enum, default_value_t = CanonicalAlphabet::Dna, conflicts_with_all = &["linear", "both-strands"])]
+        alphabet: CanonicalAlphabet,
+
+        /// Canonicalize the sequences as RNA, complementing A as U, and write them with U in place of T.
+        /// Equivalent to --alphabet rna
+        #[clap(long, conflicts_with_all = &["alphabet", "linear", "both-strands"])]
+        rna: bool,
+
+        /// Only write the first record of each set of records with the same canonical form, the same as uniq --canonicalize.
+        /// Records are compared on both strands and in every rotation
+        #[clap(long, conflicts_with_all = &["keep-original", "passthrough-invalid", "annotate-offset", "report-canonical-equal-original", "offset-bed", "linear", "both-strands", "preserve-case"])]
+        unique: bool,
+
+        /// Keep lowercase bases (e.g. soft-masked repeats) lowercase in the output.
+        /// Canonicalization still compares the bases case-insensitively, so the rotation and strand chosen are the same either way
+        #[clap(long)]
+        preserve_case: bool,
+
+        /// Skip sequences shorter than this (in nt) instead of canonicalizing them
+        #[clap(long, default_value_t = 0)]
+        min_length: usize,
+
+        /// Skip sequences longer than this (in nt) instead of canonicalizing them
+        #[clap(long)]
+        max_length: Option<usize>,
+
+        #[clap(flatten)]
+        parallel: ParallelOptions,
     },
     /// Deduplicate circular sequences
     Uniq {
-        /// Input FASTA file. May be gzip, bzip, xz, or zstd compressed [default: stdin]
+        /// Input FASTA file, or - for stdin. May be gzip, bzip, xz, or zstd compressed [default: stdin]
         input: Option<PathBuf>,
 
         /// Output FASTA file path [default: stdout]
@@ -136,21 +384,55 @@ pub enum Command {
         #[clap(short, long, alias = "norm", alias = "canonicalize", alias = "canon")]
         canonicalize: bool,
 
+        /// Which of the equivalent forms of each sequence to output with --canonicalize.
+        /// Deduplication is the same regardless
+        #[clap(long, arg_enum, default_value_t = StrandConvention::Lexmin, requires = "canonicalize")]
+        strand_convention: StrandConvention,
+
+        /// Treat the sequences as RNA, complementing A as U, so that --canonicalize writes them with U in place of T.
+        /// Duplicates are found the same way either way, since U is compared as T
+        #[clap(long, conflicts_with = "strand-convention")]
+        rna: bool,
+
+        /// What makes two sequences duplicates.
+        /// "sequence" compares their canonical sequences, so rotations and reverse complements are duplicates.
+        /// "header" compares their ids, e.g. to remove accessions that were included twice, and keeps the first sequence with each id even if later ones differ.
+        /// With "header", every duplicate is counted as forward in the tables
+        #[clap(long, arg_enum, default_value_t = DedupBy::Sequence)]
+        dedup_by: DedupBy,
+
+        /// Skip sequences shorter than this (in nt) before deduplicating them.
+        /// Skipped sequences are neither output nor counted as duplicates
+        #[clap(long, default_value_t = 0)]
+        min_length: usize,
+
+        /// Skip sequences longer than this (in nt) before deduplicating them
+        #[clap(long)]
+        max_length: Option<usize>,
+
         /// A path for deduplication metadata.
-        /// The following columns are output: id, duplicate_id.
+        /// The following columns are output: id, duplicate_id, forward_count, reverse_count.
+        /// There is a row for each duplicate, and id is the first sequence it is identical to.
+        /// The counts are of the sequences identical to id (including itself) that are on the same strand as it (forward) or on the opposite one (reverse).
         /// The file is output as a CSV or TSV depending on the file extension.
         /// Note that if no sequences are output, the output table will be an empty file.
         #[clap(long)]
         table: Option<PathBuf>,
 
-        /// The number of threads to use. If not specified, the number of logical cores is used.
-        #[clap(short, long, default_value_t = num_cpus::get().try_into().unwrap())]
-        threads: u32,
+        /// A path for the complete clustering, with a row for every output sequence, including those without duplicates.
+        /// The following columns are output: id, size, forward_count, reverse_count, members.
+        /// The size is the number of sequences identical to id (including itself), and members lists their ids, separated by commas and starting with id.
+        /// The file is output as a CSV or TSV depending on the file extension.
+        #[clap(long)]
+        table_all: Option<PathBuf>,
+
+        #[clap(flatten)]
+        parallel: ParallelOptions,
     },
 
     /// Rotate circular sequences to the left or right
     Rotate {
-        /// Input FASTA file. May be gzip, bzip, xz, or zstd compressed [default: stdin]
+        /// Input FASTA file, or - for stdin. May be gzip, bzip, xz, or zstd compressed [default: stdin]
         input: Option<PathBuf>,
 
         /// Output FASTA file path [default: stdout]
@@ -166,14 +448,31 @@ pub enum Command {
 
         /// The percentage of the sequence to rotate.
         /// This must be expressed as a decimal, e.g. 0.5 for 50%.
+        /// As with --bases, positive numbers rotate to the right and negative numbers rotate to the left.
+        /// The number of bases is truncated towards zero, so --percent 0.5 on a sequence of length 100 is the same as --bases 50.
         /// This flag is mutually exclusive with --bases.
-        #[clap(short, long, conflicts_with = "bases_group")]
+        #[clap(
+            short,
+            long,
+            allow_hyphen_values = true,
+            conflicts_with = "bases_group"
+        )]
         percent: Option<f64>,
+
+        /// Rotate each sequence so it begins with the first occurrence of this base or short motif (case-insensitive), which is the smallest rotation to the left that does so.
+        /// Occurrences spanning the origin count.
+        /// Sequences without an occurrence are written unchanged, with a warning.
+        /// This flag is mutually exclusive with --bases and --percent.
+        #[clap(long, value_name = "BASE", conflicts_with_all = &["bases_group", "percent"])]
+        start_with: Option<String>,
+
+        #[clap(flatten)]
+        parallel: ParallelOptions,
     },
 
     /// Find ORFs in circular sequences
     Orfs {
-        /// Input FASTA file. May be gzip, bzip, xz, or zstd compressed [default: stdin]
+        /// Input FASTA file, or - for stdin. May be gzip, bzip, xz, or zstd compressed [default: stdin]
         input: Option<PathBuf>,
 
         /// Output FASTA file path [default: stdout]
@@ -194,15 +493,58 @@ pub enum Command {
         #[clap(long, default_value = "TAA,TAG,TGA")]
         stop_codons: String,
 
+        /// The number of nucleotides per codon, e.g. 4 for an expanded genetic code with quadruplet codons.
+        /// Reading frames, wraps, and the stop codon excluded without --include-stop all follow the codon size, and every start and stop codon must be this long.
+        /// Translation, --trans-table, --prefer-atg, --orf-overlap-frames, and --format gff3 assume triplets, so they require the default of 3
+        #[clap(long, default_value_t = 3, value_name = "N", value_parser = clap::value_parser!(u64).range(1..))]
+        codon_size: u64,
+
+        /// Use the start and stop codons of this NCBI genetic code (the transl_table of GenBank records) instead of --start-codons and --stop-codons, e.g. 11 for bacteria or 2 for vertebrate mitochondria.
+        /// Translations with --protein and --cluster-proteins use it as well.
+        /// Tables 27, 28, and 31, whose stop codons can also code for amino acids, aren't supported
+        #[clap(long, value_name = "N", conflicts_with_all = &["start-codons", "stop-codons"])]
+        trans_table: Option<u8>,
+
+        /// Whether to prefer an ATG start codon over other start codons for the same stop codon.
+        /// By default, the longest ORF (i.e. the most upstream start codon) is reported for each stop codon.
+        /// With this flag, a shorter ORF starting with ATG is reported instead if it starts within --prefer-atg-window nt of the longest one
+        #[clap(long, action)]
+        prefer_atg: bool,
+
+        /// How far downstream of the most upstream start codon (in nt) an ATG may be and still be preferred.
+        /// Only used with --prefer-atg
+        #[clap(long, default_value = "30")]
+        prefer_atg_window: usize,
+
+        /// Thin out the ORFs so that no two starts on the same strand are within N nt of each other (across the origin, too).
+        /// ORFs are kept greedily from longest to shortest, and the output order is unchanged
+        #[clap(long, alias = "min-internal-distance", value_name = "N")]
+        min_spacing: Option<usize>,
+
+        /// Expand IUPAC ambiguity codes when matching start and stop codons, so that e.g. ATR matches ATG.
+        /// Without this, ambiguity codes are replaced with N and never match.
+        /// N itself is never expanded
+        #[clap(long, action)]
+        ambiguous_codons: bool,
+
         /// Whether to include the stop codon in the output sequence
         #[clap(long, action)]
         include_stop: bool,
 
         /// Whether to require a stop codon in the ORF. Required by default.
-        /// If enabled, partial ORFs are allowed (e.g. ATG AAA GTC)
-        #[clap(long, action)]
+        /// If enabled, partial ORFs are allowed (e.g. ATG AAA GTC).
+        /// Equivalent to --partial 3prime
+        #[clap(long, action, conflicts_with = "partial")]
         no_stop_required: bool,
 
+        /// Which partial ORFs to report.
+        /// "none" requires every ORF to end in a stop codon.
+        /// "3prime" also reports ORFs that begin with a start codon but never reach a stop codon.
+        /// On a circular sequence, such an ORF codes around the circle forever, so it is reported once per reading frame, with 3 wraps and a length of one full period of the frame.
+        /// "both" additionally reports open reading frames with neither a start nor a stop codon, which begin at the first base of the frame
+        #[clap(long, arg_enum, default_value_t = Partial::None)]
+        partial: Partial,
+
         /// When present, the minimum number of wraps around the origin an ORF must have in order to be output.
         /// Values greater than 0 mean that ORFs must take advantage or sequence circularity.
         #[clap(long, default_value = "0")]
@@ -210,9 +552,9 @@ pub enum Command {
 
         /// When present, the maximum number of wraps around the origin an ORF can have in order to be output.
         /// Setting this to 0 means that this function acts as a traditional ORF finder.
-        /// The most possible wraps is 3.
-        #[clap(long, default_value = "3")]
-        max_wraps: usize,
+        /// The most possible wraps is the codon size, i.e. 3 for triplet codons [default: the codon size]
+        #[clap(long)]
+        max_wraps: Option<usize>,
 
         /// The strands in which to search for ORFs
         #[clap(long, arg_enum, default_value_t = Strand::Both)]
@@ -226,9 +568,9 @@ pub enum Command {
         min_ratio: f64,
 
         /// A path for the ORF-finding metadata for each sequence.
-        /// The following columns are output: orf_id, seq_id, start, stop, wraps, length, and ratio.
+        /// The following columns are output: orf_id, seq_id, start, stop, wraps, length, ratio, and stopless_circular, followed by protein with --protein.
         /// Both start and stop are 0-indexed.
-        /// When --no-stop-required is used, the stop column may be empty.
+        /// When --no-stop-required or --partial is used, the stop column may be empty, in which case stopless_circular is true.
         /// Note that the length is the length of the ORF and not the length of the sequence.
         /// The --include-stop flag is taken into account when calculating the length.
         /// The wraps field corresponds to the number of wraps around the origin.
@@ -238,9 +580,214 @@ pub enum Command {
         #[clap(long)]
         table: Option<PathBuf>,
 
-        /// The number of threads to use.
-        /// If not specified, the number of logical cores is used.
-        #[clap(short, long, default_value_t = num_cpus::get().try_into().unwrap())]
-        threads: u32,
+        /// A path for the location of each ORF as 6-column BED (chrom, start, end, name, score, strand).
+        /// Coordinates are 0-based and half-open on the forward strand, and the --include-stop flag is taken into account.
+        /// ORFs that cross the origin are split into one line per pass around the sequence, each with the same name
+        #[clap(long)]
+        bed: Option<PathBuf>,
+
+        /// The format of the output: the sequence of each ORF as FASTA, or GFF3 features locating the ORFs in their sequences.
+        /// GFF3 has a region line for each sequence, marked Is_circular=true, and a CDS line for each ORF with its 1-based coordinates on the forward strand, strand, and phase.
+        /// As with --bed, ORFs that cross the origin are split into one line per pass around the sequence, each with the same ID, and the --include-stop flag is taken into account
+        #[clap(long, arg_enum, default_value_t = OrfFormat::Fasta, conflicts_with_all = &["protein", "cluster-proteins", "origin-normalize"])]
+        format: OrfFormat,
+
+        /// Report ORF coordinates relative to the start of the lexicographically minimal rotation of each sequence instead of its first base.
+        /// Each sequence is rotated to this origin before finding ORFs, so the same circular gene gets the same coordinates (and ORF ids) however the input was rotated.
+        /// BED and GFF3 coordinates have to refer to the input sequence, so this can't be combined with --bed or --format gff3
+        #[clap(long, conflicts_with = "bed")]
+        origin_normalize: bool,
+
+        /// Report pairs of ORFs on the same strand that overlap (across the origin, too) but start in different reading frames, as overlapping genes often do in dense viral genomes.
+        /// The pairs are written to --overlaps-table
+        #[clap(long, requires = "overlaps-table")]
+        orf_overlap_frames: bool,
+
+        /// A path for the pairs of ORFs found by --orf-overlap-frames.
+        /// The following columns are output: seq_id, orf_id, other_orf_id, frame, other_frame, overlap.
+        /// Frames are 0-based and taken from each ORF's start on its own strand, and the overlap is the number of nt shared, including stop codons.
+        /// The file is output as a CSV or TSV depending on the file extension.
+        #[clap(long, requires = "orf-overlap-frames")]
+        overlaps_table: Option<PathBuf>,
+
+        /// Output each ORF translated into protein instead of as a nucleotide sequence, using the standard genetic code unless --trans-table is given.
+        /// Stop codons are translated as "*", so they only appear with --include-stop.
+        /// With --table, the protein is also added to the table as a protein column
+        #[clap(long, alias = "translate", conflicts_with = "cluster-proteins")]
+        protein: bool,
+
+        /// Translate the first codon of each ORF as methionine (M) when it is one of the --start-codons, as the initiator tRNA does, even for alternative start codons like GTG.
+        /// Applies to --protein and --cluster-proteins
+        #[clap(long, default_value_t = true, action = clap::ArgAction::Set, value_name = "BOOL")]
+        initiator_met: bool,
+
+        /// Whether to cluster the translated ORFs by protein sequence instead of outputting nucleotide sequences.
+        /// All ORFs are buffered until the input is exhausted and then clustered greedily, longest protein first.
+        /// The output FASTA contains one representative protein per cluster.
+        #[clap(long, action)]
+        cluster_proteins: bool,
+
+        /// The maximum edit distance between a protein and its cluster's representative.
+        /// The default of 0 only clusters identical proteins.
+        /// Only used with --cluster-proteins.
+        #[clap(long, default_value = "0")]
+        protein_max_dist: u32,
+
+        /// A path for the protein cluster membership of each ORF.
+        /// The following columns are output: id, member_id, distance.
+        /// The id is the representative ORF of the cluster and the distance is the edit distance between the member and representative proteins.
+        /// The file is output as a CSV or TSV depending on the file extension.
+        #[clap(long, requires = "cluster-proteins")]
+        cluster_table: Option<PathBuf>,
+
+        #[clap(flatten)]
+        parallel: ParallelOptions,
+    },
+
+    /// Apply a chain of transformations to each sequence in a single pass
+    Pipeline {
+        /// Input FASTA file, or - for stdin. May be gzip, bzip, xz, or zstd compressed [default: stdin]
+        input: Option<PathBuf>,
+
+        /// Output FASTA file path [default: stdout]
+        #[clap(short, long)]
+        output: Option<PathBuf>,
+
+        /// A comma-separated list of transforms to apply to each sequence, in order.
+        /// The available transforms are revcomp, rotate:N (with N as in `rotate --bases`), canonicalize, uppercase, and degap.
+        /// Transforms operate on the sequence as read, so use uppercase before canonicalize for mixed-case input.
+        /// For example: --transform degap,uppercase,rotate:-5,canonicalize
+        #[clap(
+            long,
+            required = true,
+            value_delimiter = ',',
+            allow_hyphen_values = true
+        )]
+        transform: Vec<Transform>,
+
+        #[clap(flatten)]
+        parallel: ParallelOptions,
+    },
+
+    /// Report the period (smallest repeating unit) of circular sequences
+    Period {
+        /// Input FASTA file, or - for stdin. May be gzip, bzip, xz, or zstd compressed [default: stdin]
+        input: Option<PathBuf>,
+
+        /// Output table path [default: stdout].
+        /// The following columns are output: id, length, period, copies.
+        /// The file is output as a CSV or TSV depending on the file extension, and as a CSV to stdout.
+        #[clap(short, long)]
+        output: Option<PathBuf>,
+
+        /// The maximum number of bases allowed to disagree with the consensus of the copies.
+        /// The default of 0 only detects exact repeats
+        #[clap(long, default_value_t = 0)]
+        max_mismatch: usize,
+
+        #[clap(flatten)]
+        parallel: ParallelOptions,
     },
+    /// Check circular sequences for common problems and report whether each one passes
+    Validate {
+        /// Input FASTA file, or - for stdin. May be gzip, bzip, xz, or zstd compressed [default: stdin]
+        input: Option<PathBuf>,
+
+        /// Output table path [default: stdout].
+        /// The following columns are output: id, length, valid_bases, canonical_idempotent, monomer, length_ok, pass.
+        /// valid_bases is whether the sequence only contains A, C, G, T, U, and N.
+        /// canonical_idempotent is whether canonicalizing the canonical form changes nothing.
+        /// monomer is whether the sequence has no overlap between its ends, i.e. monomerize would leave it unchanged.
+        /// length_ok is whether the length is within --min-length and --max-length.
+        /// pass is whether all of the checks passed.
+        /// The file is output as a CSV or TSV depending on the file extension, and as a CSV to stdout.
+        #[clap(short, long)]
+        output: Option<PathBuf>,
+
+        /// The length of the seed to use when checking for an overlap, as in monomerize
+        #[clap(long, default_value = "10", value_parser = clap::value_parser!(u64).range(5..=63))]
+        seed_length: u64,
+
+        /// The minimum length of a valid sequence (in nt)
+        #[clap(long, default_value_t = 1)]
+        min_length: usize,
+
+        /// The maximum length of a valid sequence (in nt)
+        #[clap(long)]
+        max_length: Option<usize>,
+
+        /// Exit with an error if any record fails, after writing the whole table
+        #[clap(long)]
+        strict: bool,
+
+        #[clap(flatten)]
+        parallel: ParallelOptions,
+    },
+
+    /// Export the positions at which each sequence matches itself, for drawing a dotplot with other tools.
+    /// Tandem repeats and the overlap that monomerize trims show up as diagonals offset by the repeat's period
+    Dotplot {
+        /// Input FASTA file, or - for stdin. May be gzip, bzip, xz, or zstd compressed [default: stdin]
+        input: Option<PathBuf>,
+
+        /// Output table path [default: stdout].
+        /// The following columns are output: id, i, j.
+        /// There is a row for every pair of 0-based positions i and j at which the same k-mer starts, reading around the origin.
+        /// Each pair appears in both orders, but a position is never paired with itself.
+        /// The file is output as a CSV or TSV depending on the file extension, and as a CSV to stdout.
+        #[clap(short, long)]
+        output: Option<PathBuf>,
+
+        /// The length of the k-mers to match.
+        /// Sequences shorter than this have no matches
+        #[clap(short, long, default_value = "11", value_parser = clap::value_parser!(u64).range(1..))]
+        kmer_length: u64,
+
+        #[clap(flatten)]
+        parallel: ParallelOptions,
+    },
+    /// Generate synthetic circular sequences for testing
+    #[clap(hide = true)]
+    GenTestData {
+        /// Output FASTA file path [default: stdout]
+        #[clap(short, long)]
+        output: Option<PathBuf>,
+
+        /// The kind of sequences to generate
+        #[clap(long, arg_enum, default_value_t = GenKind::Multimer)]
+        kind: GenKind,
+
+        /// The seed for the random number generator. The same seed and parameters always give the same output
+        #[clap(long, default_value_t = 0)]
+        seed: u64,
+
+        /// The number of sequences to generate
+        #[clap(short = 'n', long, default_value_t = 10)]
+        count: usize,
+
+        /// The length of each monomer (in nt)
+        #[clap(short, long, default_value_t = 100)]
+        length: usize,
+
+        /// The number of copies of the monomer in each multimer, e.g. 1.5 for a monomer followed by half of itself
+        #[clap(long, default_value_t = 2.0)]
+        copies: f64,
+
+        /// The probability of each base in the repeated copies being substituted
+        #[clap(long, default_value_t = 0.0)]
+        mutation_rate: f64,
+    },
+}
+
+/// How to spread the records over threads, which every command that processes records in parallel takes.
+#[derive(clap::Args, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ParallelOptions {
+    /// The number of threads to use.
+    /// If not specified, the number of logical cores is used.
+    #[clap(short, long, default_value_t = num_cpus::get().try_into().unwrap())]
+    pub threads: u32,
+
+    /// The number of records in each batch handed to a thread
+    #[clap(long, hidden = true, default_value_t = 64)]
+    pub batch_size: usize,
 }
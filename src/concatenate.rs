@@ -1,26 +1,74 @@
+use anyhow::anyhow;
+use log::warn;
+
 use crate::{
     commands::Command,
-    utils::{input_to_reader, output_to_writer},
+    exit::UsageError,
+    profile::{self, Stage},
+    utils::{input_to_reader, output_to_writer, thread_count, write_record},
 };
-use seq_io::fasta::Record;
+use seq_io::{fasta::Record, parallel::parallel_fasta};
+
+/// The bytes to put between the two copies, or nothing if no spacer was given.
+fn spacer_bytes(spacer: &Option<char>, spacer_len: usize) -> anyhow::Result<Vec<u8>> {
+    match spacer {
+        Some(spacer) if spacer.is_ascii() => Ok(vec![*spacer as u8; spacer_len]),
+        Some(spacer) => Err(UsageError::new(format!(
+            "The spacer must be an ASCII character but was {}",
+            spacer
+        ))
+        .into()),
+        None => Ok(Vec::new()),
+    }
+}
+
+/// What a worker found for a record in `decat`.
+#[derive(Default)]
+struct Deconcatenated {
+    /// The first of the two copies
+    copy: Vec<u8>,
+    /// Whether the sequence has an odd length (excluding the spacer)
+    odd: bool,
+    /// Whether the spacer isn't between the two copies
+    missing_spacer: bool,
+}
 
 /// Concatenate sequences to themselves.
 ///
 /// This can be useful when using circular sequences with tools that don't directly support circular sequences.
 pub fn concatenate(cmd: &Command) -> anyhow::Result<()> {
     match cmd {
-        Command::Cat { input, output } => {
-            let mut reader = input_to_reader(input)?;
+        Command::Cat {
+            input,
+            output,
+            spacer,
+            spacer_len,
+            parallel,
+        } => {
+            let spacer = spacer_bytes(spacer, *spacer_len)?;
+            let reader = input_to_reader(input)?;
             let mut writer = output_to_writer(output)?;
 
-            while let Some(Ok(record)) = reader.next() {
-                let full_seq = record.full_seq();
-                writer.write_all(b">")?;
-                writer.write_all(record.head())?;
-                writer.write_all(b"\n")?;
-                writer.write_all(&full_seq)?;
-                writer.write_all(&full_seq)?;
-                writer.write_all(b"\n")?;
+            let result = parallel_fasta(
+                reader,
+                thread_count(parallel.threads),
+                parallel.batch_size,
+                |record, doubled: &mut Vec<u8>| {
+                    // runs in worker
+                    let _timer = profile::Timer::start(Stage::Compute);
+                    let full_seq = record.full_seq();
+                    doubled.clear();
+                    doubled.extend_from_slice(&full_seq);
+                    doubled.extend_from_slice(&spacer);
+                    doubled.extend_from_slice(&full_seq);
+                },
+                |record, doubled| {
+                    // runs in main thread
+                    write_record(&mut writer, &[record.head()], &[doubled]).err()
+                },
+            )?;
+            if let Some(err) = result {
+                return Err(err.into());
             }
 
             writer.flush()?;
@@ -33,17 +81,65 @@ pub fn concatenate(cmd: &Command) -> anyhow::Result<()> {
 
 pub fn deconcatenate(cmd: &Command) -> anyhow::Result<()> {
     match cmd {
-        Command::Decat { input, output } => {
-            let mut reader = input_to_reader(input)?;
+        Command::Decat {
+            input,
+            output,
+            spacer,
+            spacer_len,
+            strict,
+            parallel,
+        } => {
+            let spacer = spacer_bytes(spacer, *spacer_len)?;
+            let reader = input_to_reader(input)?;
             let mut writer = output_to_writer(output)?;
 
-            while let Some(Ok(record)) = reader.next() {
-                let full_seq = record.full_seq();
-                writer.write_all(b">")?;
-                writer.write_all(record.head())?;
-                writer.write_all(b"\n")?;
-                writer.write_all(&full_seq[..full_seq.len() / 2])?;
-                writer.write_all(b"\n")?;
+            let result = parallel_fasta(
+                reader,
+                thread_count(parallel.threads),
+                parallel.batch_size,
+                |record, halves: &mut Deconcatenated| {
+                    // runs in worker
+                    let _timer = profile::Timer::start(Stage::Compute);
+                    let full_seq = record.full_seq();
+
+                    // a genuine doubling always has an even length, so the middle base would otherwise be silently dropped
+                    halves.odd = full_seq.len().saturating_sub(spacer.len()) % 2 == 1;
+
+                    // the spacer sits in the middle, between two copies of equal length
+                    let copy_len = full_seq.len().saturating_sub(spacer.len()) / 2;
+                    halves.missing_spacer = !spacer.is_empty()
+                        && full_seq.get(copy_len..copy_len + spacer.len()) != Some(&spacer[..]);
+
+                    halves.copy.clear();
+                    halves.copy.extend_from_slice(&full_seq[..copy_len]);
+                },
+                |record, halves| {
+                    // runs in main thread
+                    if halves.odd {
+                        let message = format!(
+                            "{} has an odd length, so it can't be two copies of the same sequence",
+                            String::from_utf8_lossy(record.head())
+                        );
+                        match strict {
+                            true => return Some(anyhow!(message)),
+                            false => warn!("{}", message),
+                        }
+                    }
+
+                    if halves.missing_spacer {
+                        return Some(anyhow!(
+                            "{} does not have the given spacer between its two copies",
+                            String::from_utf8_lossy(record.head())
+                        ));
+                    }
+
+                    write_record(&mut writer, &[record.head()], &[&halves.copy])
+                        .err()
+                        .map(Into::into)
+                },
+            )?;
+            if let Some(err) = result {
+                return Err(err);
             }
 
             writer.flush()?;
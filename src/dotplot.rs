@@ -0,0 +1,58 @@
+use seq_io::{fasta::Record, parallel::parallel_fasta};
+
+use crate::{
+    commands::Command,
+    profile::{self, Stage},
+    utils::{count_record, input_to_reader, table_output_to_writer, thread_count},
+};
+
+#[derive(serde::Serialize)]
+struct Row<'a> {
+    id: &'a str,
+    i: usize,
+    j: usize,
+}
+
+pub fn dotplot(cmd: &Command) -> anyhow::Result<()> {
+    match cmd {
+        Command::Dotplot {
+            input,
+            output,
+            kmer_length,
+            parallel,
+        } => {
+            let reader = input_to_reader(input)?;
+            let mut writer = table_output_to_writer(output)?;
+
+            parallel_fasta(
+                reader,
+                thread_count(parallel.threads),
+                parallel.batch_size,
+                |record, matches: &mut Vec<(usize, usize)>| {
+                    // runs in worker
+                    let _timer = profile::Timer::start(Stage::Compute);
+                    let normalized = match needletail::sequence::normalize(record.seq(), false) {
+                        Some(x) => x,
+                        None => record.seq().to_vec(),
+                    };
+
+                    *matches = circkit::dotplot::self_matches(&normalized, *kmer_length as usize);
+                },
+                |record, matches| {
+                    // runs in main thread
+                    let id = record.id().unwrap();
+                    for &(i, j) in matches.iter() {
+                        writer
+                            .serialize(Row { id, i, j })
+                            .expect("failed to write to table");
+                        count_record();
+                    }
+                    None::<()>
+                },
+            )?;
+            writer.flush()?;
+        }
+        _ => panic!("input command is not for dotplot"),
+    }
+    Ok(())
+}
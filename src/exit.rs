@@ -0,0 +1,61 @@
+//! Exit codes, so that scripts can tell different kinds of failure apart.
+
+use std::fmt;
+
+/// The exit code for each kind of failure.
+///
+/// Errors from parsing the command line itself are reported by clap, which also exits with [`ExitCode::Usage`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExitCode {
+    /// Any error not covered by a more specific code
+    Failure = 1,
+    /// Invalid arguments or an invalid combination of arguments
+    Usage = 2,
+    /// Reading the input or writing the output failed, e.g. because a file doesn't exist
+    Io = 3,
+    /// No records were written and `--fail-on-empty` was given
+    EmptyOutput = 4,
+}
+
+/// An error caused by invalid arguments that clap can't catch by itself.
+#[derive(Debug)]
+pub struct UsageError(String);
+
+impl UsageError {
+    pub fn new(message: impl Into<String>) -> Self {
+        UsageError(message.into())
+    }
+}
+
+impl fmt::Display for UsageError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for UsageError {}
+
+/// The error returned when `--fail-on-empty` is given and nothing was written.
+#[derive(Debug)]
+pub struct EmptyOutputError;
+
+impl fmt::Display for EmptyOutputError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("No records were written")
+    }
+}
+
+impl std::error::Error for EmptyOutputError {}
+
+/// Pick the exit code for an error by looking through its chain of causes.
+pub fn exit_code(err: &anyhow::Error) -> ExitCode {
+    if err.chain().any(|cause| cause.is::<UsageError>()) {
+        ExitCode::Usage
+    } else if err.chain().any(|cause| cause.is::<EmptyOutputError>()) {
+        ExitCode::EmptyOutput
+    } else if err.chain().any(|cause| cause.is::<std::io::Error>()) {
+        ExitCode::Io
+    } else {
+        ExitCode::Failure
+    }
+}
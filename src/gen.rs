@@ -0,0 +1,172 @@
+use crate::{
+    commands::Command,
+    exit::UsageError,
+    utils::{output_to_writer, write_record},
+};
+
+/// The kind of synthetic sequences to generate.
+#[derive(clap::ArgEnum, Clone, Copy, Debug, PartialEq)]
+pub enum GenKind {
+    /// Random monomers repeated --copies times, with --mutation-rate substitutions in the repeats
+    Multimer,
+    /// Random rotations of a single random monomer on either strand
+    Rotations,
+    /// Random monomers each containing an ORF that crosses the origin
+    Orfs,
+}
+
+/// A small deterministic random number generator (SplitMix64).
+///
+/// This is used instead of an external crate so that the generated sequences for a given `--seed` never change.
+pub struct Rng(u64);
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Rng(seed)
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// A number in `0..n`.
+    pub fn below(&mut self, n: usize) -> usize {
+        (self.next_u64() % n as u64) as usize
+    }
+
+    /// Whether an event with probability `p` happened.
+    pub fn chance(&mut self, p: f64) -> bool {
+        // the top 53 bits give a uniform float in [0, 1)
+        ((self.next_u64() >> 11) as f64 / (1u64 << 53) as f64) < p
+    }
+
+    pub fn base(&mut self) -> u8 {
+        b"ACGT"[self.below(4)]
+    }
+
+    pub fn seq(&mut self, len: usize) -> Vec<u8> {
+        (0..len).map(|_| self.base()).collect()
+    }
+}
+
+const STOP_CODONS: [&[u8]; 3] = [b"TAA", b"TAG", b"TGA"];
+
+/// Repeat `monomer` until the sequence is `copies` times as long, substituting bases in the repeats at `mutation_rate`.
+fn multimer(rng: &mut Rng, monomer: &[u8], copies: f64, mutation_rate: f64) -> Vec<u8> {
+    let len = (monomer.len() as f64 * copies).round() as usize;
+    let mut seq = monomer
+        .iter()
+        .cycle()
+        .take(len)
+        .copied()
+        .collect::<Vec<_>>();
+    for base in seq.iter_mut().skip(monomer.len()) {
+        if rng.chance(mutation_rate) {
+            // always substitute a different base
+            *base = b"ACGT"
+                .iter()
+                .filter(|&&b| b != *base)
+                .nth(rng.below(3))
+                .copied()
+                .unwrap();
+        }
+    }
+    seq
+}
+
+/// Plant an ORF of `orf_len` bases (including the stop codon) into `seq`, starting at `start` and wrapping around the origin if needed.
+fn plant_orf(rng: &mut Rng, seq: &mut [u8], start: usize, orf_len: usize) {
+    let mut orf = b"ATG".to_vec();
+    while orf.len() < orf_len - 3 {
+        let codon = [rng.base(), rng.base(), rng.base()];
+        if !STOP_CODONS.contains(&&codon[..]) {
+            orf.extend_from_slice(&codon);
+        }
+    }
+    orf.extend_from_slice(STOP_CODONS[rng.below(3)]);
+
+    let len = seq.len();
+    for (i, base) in orf.into_iter().enumerate() {
+        seq[(start + i) % len] = base;
+    }
+}
+
+pub fn gen_test_data(cmd: &Command) -> anyhow::Result<()> {
+    match cmd {
+        Command::GenTestData {
+            output,
+            kind,
+            seed,
+            count,
+            length,
+            copies,
+            mutation_rate,
+        } => {
+            if *length == 0 {
+                return Err(UsageError::new("length must be at least 1").into());
+            }
+            if *copies < 1.0 {
+                return Err(UsageError::new("copies must be at least 1.0").into());
+            }
+            if !(0.0..=1.0).contains(mutation_rate) {
+                return Err(UsageError::new("mutation_rate must be between 0.0 and 1.0").into());
+            }
+            if *kind == GenKind::Orfs && *length < 9 {
+                return Err(UsageError::new("length must be at least 9 to fit an ORF").into());
+            }
+
+            let mut rng = Rng::new(*seed);
+            let mut writer = output_to_writer(output)?;
+
+            // the rotations are all of the same monomer
+            let shared = rng.seq(*length);
+
+            for i in 0..*count {
+                let (head, seq) = match kind {
+                    GenKind::Multimer => {
+                        let monomer = rng.seq(*length);
+                        (
+                            format!("gen_{} monomer_length={}", i, length),
+                            multimer(&mut rng, &monomer, *copies, *mutation_rate),
+                        )
+                    }
+                    GenKind::Rotations => {
+                        let rotation = rng.below(*length);
+                        let mut seq = [&shared[rotation..], &shared[..rotation]].concat();
+                        let strand = match rng.chance(0.5) {
+                            true => {
+                                seq = bio::alphabets::dna::revcomp(&seq);
+                                '-'
+                            }
+                            false => '+',
+                        };
+                        (
+                            format!("gen_{} rotation={} strand={}", i, rotation, strand),
+                            seq,
+                        )
+                    }
+                    GenKind::Orfs => {
+                        let mut seq = rng.seq(*length);
+                        // about two thirds of the sequence, starting in the last third so that it crosses the origin
+                        let orf_len = (*length * 2 / 3) / 3 * 3;
+                        let start = *length - 1 - rng.below(*length / 3);
+                        plant_orf(&mut rng, &mut seq, start, orf_len);
+                        (
+                            format!("gen_{} orf_start={} orf_length={}", i, start, orf_len),
+                            seq,
+                        )
+                    }
+                };
+                write_record(&mut writer, &[head.as_bytes()], &[&seq])?;
+            }
+
+            writer.flush()?;
+            Ok(())
+        }
+        _ => panic!("input command is not for gen-test-data"),
+    }
+}
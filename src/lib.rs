@@ -1,8 +1,15 @@
 pub mod canonicalize;
 pub mod commands;
 pub mod concatenate;
+pub mod dotplot;
+pub mod exit;
+pub mod gen;
 pub mod monomerize;
 pub mod orfs;
+pub mod period;
+pub mod pipeline;
+pub mod profile;
 pub mod rotate;
 pub mod uniq;
 pub mod utils;
+pub mod validate;
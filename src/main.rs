@@ -2,23 +2,71 @@ use circkit_cli::{
     canonicalize::canonicalize,
     commands::{Cli, Command},
     concatenate::{concatenate, deconcatenate},
+    dotplot::dotplot,
+    exit::{exit_code, EmptyOutputError},
+    gen::gen_test_data,
     monomerize::monomerize,
     orfs::orfs,
+    period::period,
+    pipeline::pipeline,
+    profile,
     rotate::rotate,
     uniq::uniq,
+    utils::{
+        commit_outputs, discard_outputs, enable_histogram, records_written, set_atomic_output,
+        set_chunk_bytes, set_input_format, set_input_id, set_mmap_input, set_null_output,
+        set_output_format, set_output_options, set_record_range, set_skip_all_n, write_histogram,
+        OutputOptions,
+    },
+    validate::validate,
 };
 use clap::Parser;
 use human_panic::setup_panic;
 
-fn main() -> anyhow::Result<()> {
+// human-panic's `setup_panic!` still names the deprecated `PanicInfo` alias
+#[allow(deprecated)]
+fn main() {
     setup_panic!();
 
+    // clap exits with code 2 by itself if the arguments can't be parsed
     let cli = Cli::parse();
 
+    // the writers are all dropped by the time `run` returns, so the output files are complete
+    let result = run(&cli).and_then(|_| commit_outputs());
+    if let Err(err) = result {
+        discard_outputs();
+        // match the formatting of returning an error from `main`
+        eprintln!("Error: {:?}", err);
+        std::process::exit(exit_code(&err) as i32);
+    }
+}
+
+fn run(cli: &Cli) -> anyhow::Result<()> {
     env_logger::Builder::new()
         .filter_level(cli.verbose.log_level_filter())
         .init();
 
+    if cli.profile {
+        profile::enable();
+    }
+    set_mmap_input(cli.mmap);
+    set_atomic_output(cli.atomic);
+    set_null_output(cli.null_output);
+    set_chunk_bytes(cli.chunk_bytes.map(|n| n as usize));
+    set_input_format(cli.input_format);
+    set_input_id(cli.id.clone());
+    set_record_range(cli.skip, cli.limit);
+    set_skip_all_n(cli.skip_all_n);
+    set_output_format(cli.output_format);
+    set_output_options(OutputOptions {
+        append_length: cli.append_length,
+        alphabet: cli.output_alphabet,
+        trim_to_length: cli.trim_to_length.map(|n| n as usize),
+    });
+    if cli.histogram.is_some() {
+        enable_histogram();
+    }
+
     match &cli.command {
         Command::Monomerize { .. } => monomerize(&cli.command)?,
         Command::Cat { .. } => {
@@ -35,6 +83,20 @@ fn main() -> anyhow::Result<()> {
         }
         Command::Rotate { .. } => rotate(&cli.command)?,
         Command::Orfs { .. } => orfs(&cli.command)?,
+        Command::Pipeline { .. } => pipeline(&cli.command)?,
+        Command::Period { .. } => period(&cli.command)?,
+        Command::Validate { .. } => validate(&cli.command)?,
+        Command::Dotplot { .. } => dotplot(&cli.command)?,
+        Command::GenTestData { .. } => gen_test_data(&cli.command)?,
+    }
+    profile::report();
+
+    if let Some(path) = &cli.histogram {
+        write_histogram(path)?;
+    }
+
+    if cli.fail_on_empty && records_written() == 0 {
+        return Err(EmptyOutputError.into());
     }
     Ok(())
 }
@@ -1,16 +1,156 @@
-use anyhow::bail;
+use log::info;
 use seq_io::{fasta::Record, parallel::parallel_fasta};
+use std::str::FromStr;
 
 use crate::{
     commands::Command,
-    utils::{input_to_reader, output_to_writer, table_path_to_writer},
+    exit::UsageError,
+    profile::{self, Stage},
+    utils::{
+        auxiliary_output_to_writer, count_record, input_to_reader, normalize_in, output_to_writer,
+        table_path_to_writer, thread_count, write_record, OutputAlphabet,
+    },
 };
 
+/// How to combine `--max-mismatch` and `--min-identity`.
+#[derive(clap::ArgEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OverlapCombine {
+    /// Allow the fewer mismatches of the two
+    Stricter,
+    /// Allow the more mismatches of the two
+    Looser,
+}
+
+/// How to count the differences in an overlap.
+#[derive(clap::ArgEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OverlapMetric {
+    /// Mismatched positions
+    Hamming,
+    /// Substitutions, insertions, and deletions
+    Edit,
+}
+
+/// The length of the seed, either fixed or picked for each sequence from its length.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SeedLength {
+    Auto,
+    Fixed(u64),
+}
+
+impl FromStr for SeedLength {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "auto" => Ok(SeedLength::Auto),
+            _ => match s.parse() {
+                Ok(len @ 5..=64) => Ok(SeedLength::Fixed(len)),
+                _ => Err(format!(
+                    "invalid seed length '{}'. Expected auto or a number from 5 to 64",
+                    s
+                )),
+            },
+        }
+    }
+}
+
 #[derive(serde::Serialize)]
 struct Row {
     id: String,
     original_length: usize,
     monomer_length: usize,
+    copies: usize,
+    /// Only written with `--table-overlap`, which is why these are doubly optional
+    #[serde(skip_serializing_if = "Option::is_none")]
+    overlap_len: Option<Option<usize>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    mismatches: Option<Option<u64>>,
+}
+
+/// The seed length for a sequence of length `len` when the seed is `fraction` of the sequence.
+///
+/// The result is clamped to between 5 and 63, since longer seeds overflow the matcher.
+pub fn seed_len_for(len: usize, fraction: f64) -> usize {
+    ((len as f64 * fraction).round() as usize).clamp(5, 63)
+}
+
+/// What a worker found for a record.
+#[derive(Default)]
+struct Monomer {
+    /// The end index of the monomer, or `None` if the record isn't a multimer or was rejected
+    end: Option<usize>,
+    /// How many times the record was trimmed to find the monomer, which is only counted for `--table`
+    count: usize,
+    /// The repeat the monomer was cut at, which is only found for `--table-overlap`
+    overlap: Option<circkit::Overlap>,
+}
+
+/// The number of whole copies in a sequence of length `len` with the given monomer boundaries.
+///
+/// Every copy found by the monomerizer is counted, except for the one after the first boundary if it's shorter than the monomer.
+pub fn whole_copies(len: usize, boundaries: &[usize]) -> usize {
+    match (boundaries.first(), boundaries.last()) {
+        (Some(&first), Some(&monomer_length)) => {
+            boundaries.len() + usize::from(len - first >= monomer_length)
+        }
+        _ => 1,
+    }
+}
+
+/// Describe how many mismatches `monomerizer` allows in an overlap of `overlap_len` nt, along with the identity that amounts to.
+pub fn explain_stringency(monomerizer: &circkit::Monomerizer, overlap_len: usize) -> String {
+    let max_dist = monomerizer
+        .max_overlap_dist(overlap_len)
+        .min(overlap_len as u64);
+    format!(
+        "At a {} nt overlap, at most {} mismatches are allowed (at least {:.1}% identity)",
+        overlap_len,
+        max_dist,
+        100.0 * (overlap_len as u64 - max_dist) as f64 / overlap_len as f64
+    )
+}
+
+/// Write whether each sequence is a multimer as `id\ttrue` or `id\tfalse`, without finding its monomer.
+fn check_multimers(
+    reader: seq_io::fasta::Reader<Box<dyn std::io::Read + Send>>,
+    mut writer: Box<dyn std::io::Write>,
+    monomerizer: circkit::Monomerizer,
+    seed_percent: Option<f64>,
+    alphabet: OutputAlphabet,
+    threads: u32,
+    batch_size: usize,
+) -> anyhow::Result<()> {
+    parallel_fasta(
+        reader,
+        threads,
+        batch_size,
+        |record, is_multimer| {
+            let _timer = profile::Timer::start(Stage::Compute);
+
+            let normalized = normalize_in(record.seq(), alphabet);
+            let monomerizer = match seed_percent {
+                Some(seed_percent) => circkit::Monomerizer {
+                    seed_len: seed_len_for(normalized.len(), seed_percent),
+                    ..monomerizer
+                },
+                None => monomerizer,
+            };
+            *is_multimer = monomerizer.is_multimer(&normalized);
+        },
+        |record, is_multimer| {
+            writeln!(
+                writer,
+                "{}\t{}",
+                record.id().unwrap_or_default(),
+                is_multimer
+            )
+            .unwrap();
+            count_record();
+            None::<()>
+        },
+    )?;
+    writer.flush()?;
+    Ok(())
 }
 
 pub fn monomerize(cmd: &Command) -> anyhow::Result<()> {
@@ -19,89 +159,278 @@ pub fn monomerize(cmd: &Command) -> anyhow::Result<()> {
             input,
             output,
             sensitive,
+            end_overlap,
+            inverted,
             seed_length,
+            seed_percent,
             max_mismatch,
+            max_mismatch_rate,
             min_identity,
+            overlap_combine,
+            overlap_metric,
+            explain,
+            explain_overlap,
             min_overlap,
             min_overlap_percent,
+            require_copies,
+            min_copy_identity,
             min_length,
             max_length,
             keep_all,
+            mask_overlap,
             table,
-            threads,
-            batch_size,
+            table_overlap,
+            overlap_output,
+            check,
+            rna,
+            parallel,
         } => {
             // region: some basic sanity checks
-            if max_mismatch.is_some() && min_identity.is_some() {
-                bail!("cannot specify both max_mismatch and min_identity");
+            // make sure the mismatch rate is in range
+            if let Some(max_mismatch_rate) = *max_mismatch_rate {
+                if !(0.0..=1.0).contains(&max_mismatch_rate) {
+                    return Err(
+                        UsageError::new("max_mismatch_rate must be between 0.0 and 1.0").into(),
+                    );
+                }
             }
 
             // make sure the minimum identity is in range
             if let Some(min_identity) = *min_identity {
                 if !(0.0..=1.0).contains(&min_identity) {
-                    bail!("min_identity must be between 0.0 and 1.0");
+                    return Err(UsageError::new("min_identity must be between 0.0 and 1.0").into());
                 }
             }
-            // endregion
 
-            let reader = input_to_reader(input)?;
-            let mut writer = output_to_writer(output)?;
-            let mut table_writer = table_path_to_writer(table);
+            if let Some(require_copies) = *require_copies {
+                if require_copies < 2 {
+                    return Err(UsageError::new("require_copies must be at least 2").into());
+                }
+            }
+
+            if let Some(min_copy_identity) = *min_copy_identity {
+                if !(0.0..=1.0).contains(&min_copy_identity) {
+                    return Err(
+                        UsageError::new("min_copy_identity must be between 0.0 and 1.0").into(),
+                    );
+                }
+            }
+
+            if let Some(seed_percent) = *seed_percent {
+                if !(seed_percent > 0.0 && seed_percent <= 1.0) {
+                    return Err(UsageError::new(
+                        "seed_percent must be greater than 0.0 and at most 1.0",
+                    )
+                    .into());
+                }
+            }
+            // endregion
 
             let mut builder = circkit::monomerize::Monomerizer::builder();
 
             // set the seed length
-            builder.seed_len((*seed_length).try_into().expect("Seed length is too large"));
+            match *seed_length {
+                SeedLength::Auto => builder.auto_seed_len(),
+                SeedLength::Fixed(len) => {
+                    builder.seed_len(len.try_into().expect("Seed length is too large"))
+                }
+            };
 
             // set the maximum mismatch count
             if let Some(max_mismatch) = *max_mismatch {
                 builder.overlap_dist(max_mismatch);
             }
 
+            // set the maximum mismatch rate
+            if let Some(max_mismatch_rate) = *max_mismatch_rate {
+                builder.overlap_mismatch_rate(max_mismatch_rate);
+            }
+
             // set the minimum identity
             if let Some(min_identity) = *min_identity {
                 builder.overlap_min_identity(min_identity);
             }
 
-            let monomerizer = builder.build().unwrap();
+            // set the monomer length range
+            builder.min_monomer_len(*min_length);
+            if let Some(max_length) = *max_length {
+                builder.max_monomer_len(max_length);
+            }
+
+            // set how to combine the mismatch count and identity
+            builder.overlap_combine(match overlap_combine {
+                OverlapCombine::Stricter => circkit::OverlapCombine::Stricter,
+                OverlapCombine::Looser => circkit::OverlapCombine::Looser,
+            });
+
+            // set how to count the differences in an overlap
+            builder.overlap_metric(match overlap_metric {
+                OverlapMetric::Hamming => circkit::OverlapMetric::Hamming,
+                OverlapMetric::Edit => circkit::OverlapMetric::Edit,
+            });
+
+            let monomerizer = builder
+                .build()
+                .map_err(|err| UsageError::new(err.to_string()))?;
+
+            // the default of exact overlaps surprises users expecting tolerant matching, so be explicit about it
+            match (end_overlap, max_mismatch, max_mismatch_rate, min_identity) {
+                (Some(end_overlap), _, _, _) => info!(
+                    "Overlap policy: the last {} nt of each sequence, without searching",
+                    end_overlap
+                ),
+                (None, Some(max_mismatch), _, Some(min_identity)) => info!(
+                    "Overlap policy: the {} of at most {} mismatches and at least {}% identity per overlap",
+                    match overlap_combine {
+                        OverlapCombine::Stricter => "stricter",
+                        OverlapCombine::Looser => "looser",
+                    },
+                    max_mismatch,
+                    min_identity * 100.0
+                ),
+                (None, Some(max_mismatch), _, None) => {
+                    info!("Overlap policy: at most {} mismatches per overlap", max_mismatch)
+                }
+                (None, None, Some(max_mismatch_rate), _) => info!(
+                    "Overlap policy: at most {}% mismatches per overlap",
+                    max_mismatch_rate * 100.0
+                ),
+                (None, None, None, Some(min_identity)) => info!(
+                    "Overlap policy: at least {}% identity per overlap",
+                    min_identity * 100.0
+                ),
+                (None, None, None, None) => info!(
+                    "Overlap policy: exact overlaps only (0 mismatches). Use --max-mismatch, --max-mismatch-rate, or --min-identity to allow mismatches"
+                ),
+            }
+
+            let stringency = explain_stringency(&monomerizer, *explain_overlap as usize);
+            if *explain {
+                let mut writer = output_to_writer(output)?;
+                writeln!(writer, "{}", stringency)?;
+                writer.flush()?;
+                return Ok(());
+            }
+            info!("Overlap stringency: {}", stringency);
+
+            let reader = input_to_reader(input)?;
+            let mut writer = output_to_writer(output)?;
+            let mut table_writer = table_path_to_writer(table);
+            let mut overlap_writer = match overlap_output {
+                Some(_) => Some(auxiliary_output_to_writer(overlap_output)?),
+                None => None,
+            };
+
+            let alphabet = match rna {
+                true => OutputAlphabet::Rna,
+                false => OutputAlphabet::Dna,
+            };
+
+            if *check {
+                return check_multimers(
+                    reader,
+                    writer,
+                    monomerizer,
+                    *seed_percent,
+                    alphabet,
+                    thread_count(parallel.threads),
+                    parallel.batch_size,
+                );
+            }
 
             parallel_fasta(
                 reader,
-                *threads,
-                *batch_size,
-                |record, idx| {
+                thread_count(parallel.threads),
+                parallel.batch_size,
+                |record, monomer: &mut Monomer| {
+                    let _timer = profile::Timer::start(Stage::Compute);
+                    let idx = &mut monomer.end;
+
                     // normalize the sequence
-                    let normalized = match needletail::sequence::normalize(record.seq(), false) {
-                        Some(x) => x,
-                        None => record.seq().to_vec(),
+                    let normalized = normalize_in(record.seq(), alphabet);
+
+                    // a relative seed length has to be worked out for each sequence separately
+                    let monomerizer = match *seed_percent {
+                        Some(seed_percent) => circkit::Monomerizer {
+                            seed_len: seed_len_for(normalized.len(), seed_percent),
+                            ..monomerizer
+                        },
+                        None => monomerizer.for_sequence(&normalized),
                     };
 
-                    // make sure the sequence is at least as long as the seed length and the minimum length
-                    if normalized.len() < monomerizer.seed_len || normalized.len() < *min_length {
-                        *idx = None;
-                        return;
+                    if let Some(end_overlap) = *end_overlap {
+                        // the overlap is already known, so it's trimmed without searching
+                        *idx = normalized
+                            .len()
+                            .checked_sub(end_overlap as usize)
+                            .filter(|&len| {
+                                len > 0
+                                    && len >= *min_length
+                                    && len <= max_length.unwrap_or(usize::MAX)
+                            });
+                    } else {
+                        // make sure the sequence is at least as long as the seed length and the minimum length
+                        if normalized.len() < monomerizer.seed_len || normalized.len() < *min_length
+                        {
+                            *idx = None;
+                            return;
+                        }
+
+                        *idx = match sensitive {
+                            true => monomerizer.last_monomer_end_index_sensitive(&normalized),
+                            false => monomerizer.last_monomer_end_index(&normalized),
+                        };
                     }
 
-                    *idx = match sensitive {
-                        true => monomerizer.last_monomer_end_index_sensitive(&normalized),
-                        false => monomerizer.last_monomer_end_index(&normalized),
+                    // reject monomers that don't repeat enough times
+                    if let (Some(require_copies), Some(_)) = (*require_copies, *idx) {
+                        let boundaries = monomerizer.monomer_boundaries(&normalized);
+                        if whole_copies(normalized.len(), &boundaries) < require_copies {
+                            *idx = None;
+                        }
                     }
-                },
-                |record, idx| {
-                    // get the full sequence
-                    let full_seq = record.full_seq();
 
-                    // region: check the monomer is long enough, either absolute or relative to the original sequence
+                    // reject multimers whose copies are inconsistent with each other
+                    if let (Some(min_copy_identity), Some(monomer_length)) =
+                        (*min_copy_identity, *idx)
+                    {
+                        if circkit::monomerize::copy_identity(&normalized, monomer_length)
+                            < min_copy_identity
+                        {
+                            *idx = None;
+                        }
+                    }
 
-                    // absolute monomer length
-                    if let Some(monomer_length) = *idx {
-                        if monomer_length < *min_length
-                            || monomer_length > max_length.unwrap_or(usize::MAX)
+                    // the inverted repeat is trimmed from whatever the tandem repeats left, so that either or both are trimmed
+                    let tandem_end = *idx;
+                    if *inverted {
+                        let end = idx.unwrap_or(normalized.len());
+                        if let Some(end) = monomerizer.inverted_repeat_end_index(&normalized[..end])
                         {
-                            *idx = None; // reject the monomer
+                            *idx = Some(end);
                         }
                     }
 
+                    // the copies are those of the tandem repeat, so a sequence only trimmed at an inverted repeat is a single copy
+                    monomer.count = match (&table, tandem_end, end_overlap) {
+                        (Some(_), Some(_), Some(_)) => 1,
+                        (Some(_), Some(_), None) => monomerizer.monomer_count(&normalized),
+                        _ => 0,
+                    };
+                    monomer.overlap = match (*table_overlap, *idx) {
+                        (true, Some(_)) => monomerizer.monomerize_detailed(&normalized),
+                        _ => None,
+                    };
+                },
+                |record, monomer| {
+                    let idx = &mut monomer.end;
+
+                    // get the full sequence
+                    let full_seq = record.full_seq();
+
+                    // region: check the overlap is long enough, either absolute or relative to the original sequence
+                    // the absolute monomer length was already checked by the monomerizer
+
                     // absolute overlap length
                     if let Some(min_overlap) = *min_overlap {
                         if let Some(monomer_length) = *idx {
@@ -125,15 +454,36 @@ pub fn monomerize(cmd: &Command) -> anyhow::Result<()> {
                     }
                     // endregion
 
+                    // the overlap is whatever follows the monomer
+                    if let (Some(overlap_writer), Some(monomer_length)) =
+                        (overlap_writer.as_mut(), *idx)
+                    {
+                        write_record(
+                            overlap_writer,
+                            &[record.head()],
+                            &[&full_seq[monomer_length..]],
+                        )
+                        .unwrap();
+                    }
+
                     // when keep_all is true, we write all sequences
                     // otherwise, we only write sequences that have been monomerized (i.e. the monomer index is Some)
                     if (idx.is_some()) || *keep_all {
                         let end_idx = idx.unwrap_or(full_seq.len());
-                        writer.write_all(b">").unwrap();
-                        writer.write_all(record.head()).unwrap();
-                        writer.write_all(b"\n").unwrap();
-                        writer.write_all(&full_seq[..end_idx]).unwrap();
-                        writer.write_all(b"\n").unwrap();
+                        match mask_overlap {
+                            true => write_record(
+                                &mut writer,
+                                &[record.head()],
+                                &[
+                                    &full_seq[..end_idx],
+                                    &full_seq[end_idx..].to_ascii_lowercase(),
+                                ],
+                            ),
+                            false => {
+                                write_record(&mut writer, &[record.head()], &[&full_seq[..end_idx]])
+                            }
+                        }
+                        .unwrap();
 
                         // write the table file if it was requested
                         if let Some(ref mut table_writer) = table_writer {
@@ -142,6 +492,15 @@ pub fn monomerize(cmd: &Command) -> anyhow::Result<()> {
                                     id: std::str::from_utf8(record.head()).unwrap().to_string(),
                                     original_length: full_seq.len(),
                                     monomer_length: end_idx,
+                                    // a monomer rejected by the filters above is written whole
+                                    copies: match idx {
+                                        Some(_) => monomer.count + 1,
+                                        None => 1,
+                                    },
+                                    overlap_len: table_overlap
+                                        .then_some(idx.and(monomer.overlap).map(|o| o.overlap_len)),
+                                    mismatches: table_overlap
+                                        .then_some(idx.and(monomer.overlap).map(|o| o.mismatches)),
                                 })
                                 .expect("failed to write to table")
                         }
@@ -153,6 +512,9 @@ pub fn monomerize(cmd: &Command) -> anyhow::Result<()> {
             if let Some(mut table_writer) = table_writer {
                 table_writer.flush()?;
             }
+            if let Some(mut overlap_writer) = overlap_writer {
+                overlap_writer.flush()?;
+            }
             Ok(())
         }
         _ => panic!("input command is not for monomerize"),
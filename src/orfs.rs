@@ -1,8 +1,15 @@
 use crate::{
     commands::Command,
-    utils::{input_to_reader, output_to_writer, table_path_to_writer},
+    exit::UsageError,
+    profile::{self, Stage},
+    utils::{
+        auxiliary_output_to_writer, count_record, input_to_reader, output_to_writer,
+        table_path_to_writer, thread_count, write_record,
+    },
 };
+use bio::alignment::distance::levenshtein;
 use seq_io::{fasta::Record, parallel::parallel_fasta};
+use std::{borrow::Cow, collections::HashMap};
 
 #[derive(clap::ArgEnum, Clone, Debug, PartialEq)]
 pub enum Strand {
@@ -11,6 +18,27 @@ pub enum Strand {
     Both,
 }
 
+/// Which kinds of partial (stop-less) ORFs to report.
+#[derive(clap::ArgEnum, Clone, Copy, Debug, PartialEq)]
+pub enum Partial {
+    /// Every ORF must end in a stop codon
+    None,
+    /// ORFs may run off the 3' end without reaching a stop codon
+    #[clap(name = "3prime")]
+    ThreePrime,
+    /// Additionally report reading frames open at both ends, with neither a start nor a stop codon
+    Both,
+}
+
+/// The format of the main output of `orfs`.
+#[derive(clap::ArgEnum, Clone, Copy, Debug, PartialEq)]
+pub enum OrfFormat {
+    /// The sequence of each ORF
+    Fasta,
+    /// A GFF3 feature locating each ORF in its sequence
+    Gff3,
+}
+
 #[derive(serde::Serialize, Debug)]
 struct Row {
     orf_id: String,
@@ -20,6 +48,244 @@ struct Row {
     length: usize,
     wraps: usize,
     ratio: f64,
+    stopless_circular: bool,
+    /// The translated ORF, only with --protein
+    #[serde(skip_serializing_if = "Option::is_none")]
+    protein: Option<String>,
+}
+
+#[derive(serde::Serialize, Debug)]
+struct ClusterRow<'a> {
+    id: &'a str,
+    member_id: &'a str,
+    distance: u32,
+}
+
+#[derive(serde::Serialize, Debug)]
+struct OverlapRow<'a> {
+    seq_id: &'a str,
+    orf_id: String,
+    other_orf_id: String,
+    frame: usize,
+    other_frame: usize,
+    overlap: usize,
+}
+
+/// A cluster of proteins, stored as indices into the buffered proteins.
+struct Cluster {
+    representative: usize,
+    /// Each member along with its edit distance to the representative
+    members: Vec<(usize, u32)>,
+}
+
+/// Greedily cluster `(orf_id, protein)` pairs.
+///
+/// Proteins are visited from longest to shortest and each joins the cluster whose representative is nearest, if it is within `max_dist` edits.
+/// Ties go to the earlier, i.e. longer, representative. Otherwise, it becomes the representative of a new cluster.
+fn cluster_proteins(proteins: &[(String, String)], max_dist: u32) -> Vec<Cluster> {
+    let mut order = (0..proteins.len()).collect::<Vec<_>>();
+    order.sort_by_key(|&i| std::cmp::Reverse(proteins[i].1.len()));
+
+    let mut clusters: Vec<Cluster> = Vec::new();
+    let mut exact = HashMap::<&str, usize>::new(); // representative protein -> cluster index
+
+    for i in order {
+        let protein = proteins[i].1.as_str();
+
+        // identical proteins are by far the most common case, so check those first
+        if let Some(&cluster) = exact.get(protein) {
+            clusters[cluster].members.push((i, 0));
+            continue;
+        }
+
+        let nearest = match max_dist {
+            0 => None,
+            _ => clusters
+                .iter()
+                .enumerate()
+                .filter_map(|(cluster, c)| {
+                    let representative = proteins[c.representative].1.as_bytes();
+                    // the length difference is a lower bound on the edit distance
+                    if representative.len() - protein.len() > max_dist as usize {
+                        return None;
+                    }
+                    let dist = levenshtein(representative, protein.as_bytes());
+                    (dist <= max_dist).then_some((cluster, dist))
+                })
+                .min_by_key(|&(_, dist)| dist),
+        };
+
+        match nearest {
+            Some((cluster, dist)) => clusters[cluster].members.push((i, dist)),
+            None => {
+                exact.insert(protein, clusters.len());
+                clusters.push(Cluster {
+                    representative: i,
+                    members: vec![(i, 0)],
+                });
+            }
+        }
+    }
+    clusters
+}
+
+/// The per-record state of the ORF finding workers.
+///
+/// The reader reuses these between records, so the vectors are cleared and refilled rather than reallocated.
+#[derive(Default)]
+struct OrfBuffers {
+    /// The ORFs to report on the forward strand
+    forward: Vec<circkit::orfs::Orf>,
+    /// The ORFs to report on the reverse strand, indexed into `revcomp`
+    reverse: Vec<circkit::orfs::Orf>,
+    /// The reverse complement of the sequence, or empty if the reverse strand isn't searched
+    revcomp: Vec<u8>,
+    /// Start and stop codon indices by frame, for whichever strand is being searched
+    starts: Vec<Vec<usize>>,
+    stops: Vec<Vec<usize>>,
+    /// Every ORF on the strand being searched, before choosing the longest for each stop codon
+    all: Vec<circkit::orfs::Orf>,
+    /// The index the sequence was rotated to before searching, which is 0 unless the origin is normalized
+    origin: usize,
+}
+
+/// Find the reading frames that are open at both ends, i.e. that contain neither a start nor a stop codon, for codons of `codon_size` nucleotides.
+///
+/// When the sequence length is a multiple of the codon size, each frame stays in itself across the origin, so each open frame is its own ORF.
+/// Otherwise, reading continues through other frames before returning to the first one (through all three for triplets), so all of those must be open.
+/// The ORFs are represented the same way as stop-less ORFs from [`circkit::orfs::find_orfs_with_codon_size_into`], starting at the first base of the frame.
+fn open_frames(
+    seq_len: usize,
+    codon_size: usize,
+    starts: &[Vec<usize>],
+    stops: &[Vec<usize>],
+) -> Vec<circkit::orfs::Orf> {
+    let is_open = |frame: usize| starts[frame].is_empty() && stops[frame].is_empty();
+
+    // frames are read one after another if they differ by a multiple of this
+    let reading_frames = (1..=codon_size)
+        .rev()
+        .find(|&d| seq_len.is_multiple_of(d) && codon_size.is_multiple_of(d))
+        .unwrap_or(1);
+    (0..reading_frames.min(seq_len))
+        .filter(|&first| (first..codon_size).step_by(reading_frames).all(is_open))
+        .map(|first| circkit::orfs::Orf {
+            start: first,
+            stop: None,
+            wraps: codon_size,
+            length: seq_len / reading_frames * codon_size,
+        })
+        .collect()
+}
+
+/// Drop ORFs whose start is within `min_spacing` nt of the start of a longer ORF that is kept, measuring the distance around a circular sequence of length `seq_len`.
+///
+/// The longest ORF is always kept, and equally long ORFs are considered from the smallest start.
+/// The kept ORFs stay in their original order.
+fn thin_orfs(orfs: &mut Vec<circkit::orfs::Orf>, seq_len: usize, min_spacing: usize) {
+    let mut order = (0..orfs.len()).collect::<Vec<_>>();
+    order.sort_by_key(|&i| (std::cmp::Reverse(orfs[i].length), orfs[i].start));
+
+    let distance = |a: usize, b: usize| {
+        let d = a.abs_diff(b);
+        d.min(seq_len - d)
+    };
+    let mut kept_starts = Vec::new();
+    let mut keep = vec![false; orfs.len()];
+    for i in order {
+        if kept_starts
+            .iter()
+            .all(|&start| distance(start, orfs[i].start) >= min_spacing)
+        {
+            kept_starts.push(orfs[i].start);
+            keep[i] = true;
+        }
+    }
+
+    let mut keep = keep.into_iter();
+    orfs.retain(|_| keep.next().unwrap());
+}
+
+/// Find the pairs of ORFs that overlap on a circular sequence of length `seq_len` but start in different frames.
+///
+/// Each pair is given as the indices of its ORFs, in order, and the number of positions they share.
+fn out_of_frame_overlaps(
+    orfs: &[circkit::orfs::Orf],
+    seq_len: usize,
+) -> Vec<(usize, usize, usize)> {
+    let mut pairs = Vec::new();
+    for (i, orf) in orfs.iter().enumerate() {
+        for (j, other) in orfs.iter().enumerate().skip(i + 1) {
+            if orf.frame() == other.frame() {
+                continue;
+            }
+            let overlap = orf.overlap(other, seq_len);
+            if overlap > 0 {
+                pairs.push((i, j, overlap));
+            }
+        }
+    }
+    pairs
+}
+
+/// Split the part of a circular sequence of length `seq_len` that begins at `begin` and spans `len` bases into BED intervals.
+///
+/// BED can't represent a feature that crosses the origin, so it is split there, giving one interval per pass around the sequence.
+fn bed_intervals(seq_len: usize, begin: usize, len: usize) -> Vec<(usize, usize)> {
+    let mut intervals = Vec::new();
+    let (mut pos, mut remaining) = (begin, len);
+    while remaining > 0 {
+        let end = (pos + remaining).min(seq_len);
+        intervals.push((pos, end));
+        remaining -= end - pos;
+        pos = 0;
+    }
+    intervals
+}
+
+/// Where an ORF on the reverse strand that begins at `start` of the reverse complement and spans `len` bases begins on the forward strand of a circular sequence of length `seq_len`.
+///
+/// On the forward strand, the ORF ends just after the base complementary to its first base.
+fn reverse_begin(seq_len: usize, start: usize, len: usize) -> usize {
+    let end = seq_len - start;
+    (end as i64 - len as i64).rem_euclid(seq_len as i64) as usize
+}
+
+/// Write the GFF3 CDS lines of an ORF that begins at `begin` on the forward strand of a circular sequence of length `seq_len` and spans `len` bases.
+///
+/// As with [`bed_intervals`], an ORF crossing the origin gets a line per pass around the sequence, which share its ID.
+/// Each line's phase is how many bases of a codon begun on the previous line in reading order it has to skip, and reading runs backwards on the reverse strand.
+fn write_gff3_cds(
+    writer: &mut dyn std::io::Write,
+    seqid: &str,
+    id: &str,
+    seq_len: usize,
+    (begin, len): (usize, usize),
+    reverse: bool,
+) -> std::io::Result<()> {
+    let mut intervals = bed_intervals(seq_len, begin, len);
+    if reverse {
+        intervals.reverse();
+    }
+    let mut read = 0;
+    for (start, end) in intervals {
+        writeln!(
+            writer,
+            "{}\tcirckit\tCDS\t{}\t{}\t.\t{}\t{}\tID={}",
+            seqid,
+            start + 1,
+            end,
+            match reverse {
+                true => '-',
+                false => '+',
+            },
+            (3 - read % 3) % 3,
+            id
+        )?;
+        read += end - start;
+    }
+    count_record();
+    Ok(())
 }
 
 pub fn orfs(cmd: &Command) -> anyhow::Result<()> {
@@ -30,96 +296,335 @@ pub fn orfs(cmd: &Command) -> anyhow::Result<()> {
             min_length,
             start_codons,
             stop_codons,
+            codon_size,
+            trans_table,
+            prefer_atg,
+            prefer_atg_window,
+            min_spacing,
+            ambiguous_codons,
             include_stop,
             min_wraps,
             max_wraps,
             min_ratio,
             strand,
             no_stop_required,
+            partial,
             table,
-            threads,
+            bed,
+            origin_normalize,
+            orf_overlap_frames,
+            overlaps_table,
+            format,
+            protein,
+            initiator_met,
+            cluster_proteins: cluster,
+            protein_max_dist,
+            cluster_table,
+            parallel,
         } => {
             let reader = input_to_reader(input)?;
             let mut writer = output_to_writer(output)?;
             let mut table_writer = table_path_to_writer(table);
+            let mut bed_writer = match bed {
+                Some(_) => Some(auxiliary_output_to_writer(bed)?),
+                None => None,
+            };
+            let mut overlaps_writer = match orf_overlap_frames {
+                true => table_path_to_writer(overlaps_table),
+                false => None,
+            };
+
+            // --no-stop-required predates --partial and is kept as a shorthand for the 3' policy
+            let partial = match no_stop_required {
+                true => Partial::ThreePrime,
+                false => *partial,
+            };
+
+            // when clustering, the (orf_id, protein) of every ORF is buffered until the end
+            let mut proteins = Vec::<(String, String)>::new();
 
             // Step 1: Find all stop and start codons by frame
-            let start_codons = start_codons.split(',').collect::<Vec<_>>();
-            let stop_codons = stop_codons.split(',').collect::<Vec<_>>();
+            let table = match trans_table {
+                Some(id) => Some(circkit::orfs::CodonTable::ncbi(*id).ok_or_else(|| {
+                    UsageError::new(format!("There is no NCBI translation table {}", id))
+                })?),
+                None => None,
+            };
+            let (start_codons, stop_codons) = match table {
+                Some(table) => (table.start_codons(), table.stop_codons()),
+                None => (
+                    start_codons.split(',').collect::<Vec<_>>(),
+                    stop_codons.split(',').collect::<Vec<_>>(),
+                ),
+            };
+            let codon_size = *codon_size as usize;
+            if let Some(codon) = start_codons
+                .iter()
+                .chain(&stop_codons)
+                .find(|codon| codon.len() != codon_size)
+            {
+                return Err(UsageError::new(format!(
+                    "The codon {} isn't {} nt long, as the codon size requires",
+                    codon, codon_size
+                ))
+                .into());
+            }
+            if codon_size != 3 {
+                let triplet_only = [
+                    ("--trans-table", trans_table.is_some()),
+                    ("--protein", *protein),
+                    ("--cluster-proteins", *cluster),
+                    ("--prefer-atg", *prefer_atg),
+                    ("--orf-overlap-frames", *orf_overlap_frames),
+                    ("--format gff3", *format == OrfFormat::Gff3),
+                ];
+                if let Some((flag, _)) = triplet_only.iter().find(|(_, used)| *used) {
+                    return Err(UsageError::new(format!(
+                        "{} assumes triplet codons, so it can't be used with --codon-size {}",
+                        flag, codon_size
+                    ))
+                    .into());
+                }
+            }
+            let max_wraps = max_wraps.unwrap_or(codon_size);
+            // a stop-less circular ORF has no stop codon to leave out, so all of it is coding sequence
+            let stop_len = |orf: &circkit::orfs::Orf| match orf.stop {
+                Some(_) => codon_size,
+                None => 0,
+            };
+            let table = table.unwrap_or_default();
+            let translate_orf = |orf: &circkit::orfs::Orf, seq: &[u8]| {
+                orf.protein_with_opts(
+                    seq,
+                    &table,
+                    *include_stop,
+                    initiator_met.then_some(&start_codons[..]),
+                )
+            };
+            let codon_indices = match ambiguous_codons {
+                true => circkit::orfs::start_stop_codon_indices_by_frame_ambiguous_into,
+                false => circkit::orfs::start_stop_codon_indices_by_frame_naive_into,
+            };
+            // the triplet-specific functions are faster, so the general ones are only used for other codon sizes
+            let find_codons = |seq: &[u8], starts: &mut [Vec<usize>], stops: &mut [Vec<usize>]| {
+                let seq = std::str::from_utf8(seq).unwrap();
+                match codon_size {
+                    3 => codon_indices(seq, &start_codons, &stop_codons, starts, stops),
+                    _ => circkit::orfs::start_stop_codon_indices_by_frame_sized_into(
+                        seq,
+                        codon_size,
+                        &start_codons,
+                        &stop_codons,
+                        *ambiguous_codons,
+                        starts,
+                        stops,
+                    ),
+                }
+            };
+            let find_orfs =
+                |seq_len: usize,
+                 starts: &[Vec<usize>],
+                 stops: &[Vec<usize>],
+                 orfs: &mut Vec<circkit::orfs::Orf>| match codon_size {
+                    3 => circkit::orfs::find_orfs_with_indices_into(seq_len, starts, stops, orfs),
+                    _ => circkit::orfs::find_orfs_with_codon_size_into(
+                        seq_len, codon_size, starts, stops, orfs,
+                    ),
+                };
+
+            if *format == OrfFormat::Gff3 {
+                writeln!(writer, "##gff-version 3")?;
+            }
 
             parallel_fasta(
                 reader,
-                *threads,
-                64,
-                |record, orfs: &mut (Vec<circkit::orfs::Orf>, Vec<circkit::orfs::Orf>, Vec<u8>)| {
+                thread_count(parallel.threads),
+                parallel.batch_size,
+                |record, orfs: &mut OrfBuffers| {
                     // runs in worker
-                    let normalized = match needletail::sequence::normalize(record.seq(), false) {
-                        Some(x) => x,
-                        None => record.seq().to_vec(),
-                    };
+                    let _timer = profile::Timer::start(Stage::Compute);
+                    // ambiguity codes have to survive normalization to be expanded
+                    let mut normalized =
+                        match needletail::sequence::normalize(record.seq(), *ambiguous_codons) {
+                            Some(x) => x,
+                            None => record.seq().to_vec(),
+                        };
 
-                    let (starts, stops) = circkit::orfs::start_stop_codon_indices_by_frame_naive(
-                        std::str::from_utf8(&normalized).unwrap(),
-                        &start_codons,
-                        &stop_codons,
-                    );
+                    orfs.starts.resize_with(codon_size, Vec::new);
+                    orfs.stops.resize_with(codon_size, Vec::new);
 
-                    let mut all_orfs =
-                        circkit::orfs::find_orfs_with_indices(normalized.len(), starts, stops);
+                    orfs.origin = match origin_normalize {
+                        true => circkit::canonicalize::lmsr_index(&normalized),
+                        false => 0,
+                    };
+                    normalized.rotate_left(orfs.origin);
 
-                    // length filtering, stop codon requirement (with optional bypass), and wrap filtering
-                    all_orfs.retain(|orf| {
-                        (orf.length - 3 >= *min_length)
-                            && (*no_stop_required || orf.stop.is_some())
+                    // length filtering, stop codon requirement (depending on the partial policy), and wrap filtering
+                    let keep = |orf: &circkit::orfs::Orf| {
+                        (orf.length - stop_len(orf) >= *min_length)
+                            && (partial != Partial::None || orf.stop.is_some())
                             && (*min_wraps <= orf.wraps)
-                            && (orf.wraps <= *max_wraps)
+                            && (orf.wraps <= max_wraps)
                             && (orf.length as f64 / normalized.len() as f64 >= *min_ratio)
-                    });
-
-                    orfs.0 = circkit::orfs::longest_orfs(&mut all_orfs);
-
-                    orfs.1 = if *strand == Strand::Both || *strand == Strand::Reverse {
-                        orfs.2 = bio::alphabets::dna::revcomp(&normalized);
-                        let (starts, stops) =
-                            circkit::orfs::start_stop_codon_indices_by_frame_naive(
-                                std::str::from_utf8(&orfs.2).unwrap(),
-                                &start_codons,
-                                &stop_codons,
-                            );
-
-                        let mut all_rc_orfs =
-                            circkit::orfs::find_orfs_with_indices(normalized.len(), starts, stops);
-                        all_rc_orfs.retain(|orf| {
-                            (orf.length - 3 >= *min_length)
-                                && (*no_stop_required || orf.stop.is_some())
-                                && (*min_wraps <= orf.wraps)
-                                && (orf.wraps <= *max_wraps)
-                                && (orf.length as f64 / normalized.len() as f64 >= *min_ratio)
-                        });
-                        circkit::orfs::longest_orfs(&mut all_rc_orfs)
-                    } else {
-                        orfs.2.clear();
-                        Vec::new()
                     };
+
+                    // with --strand reverse, the forward strand isn't searched at all
+                    orfs.forward.clear();
+                    if *strand == Strand::Both || *strand == Strand::Forward {
+                        find_codons(&normalized, &mut orfs.starts, &mut orfs.stops);
+                        let open = match partial {
+                            Partial::Both => {
+                                open_frames(normalized.len(), codon_size, &orfs.starts, &orfs.stops)
+                            }
+                            _ => Vec::new(),
+                        };
+
+                        find_orfs(normalized.len(), &orfs.starts, &orfs.stops, &mut orfs.all);
+                        orfs.all.retain(keep);
+
+                        // open frames have no start codon, so they never compete with the ORFs for their stop
+                        orfs.forward = match prefer_atg {
+                            true => circkit::orfs::longest_orfs_preferring(
+                                &mut orfs.all,
+                                &normalized,
+                                b"ATG",
+                                *prefer_atg_window,
+                            ),
+                            false => circkit::orfs::longest_orfs(&mut orfs.all),
+                        };
+                        orfs.forward
+                            .extend(open.into_iter().filter(|orf| keep(orf)));
+                    }
+
+                    orfs.revcomp.clear();
+                    orfs.reverse.clear();
+                    if *strand == Strand::Both || *strand == Strand::Reverse {
+                        orfs.revcomp.extend(
+                            normalized
+                                .iter()
+                                .rev()
+                                .map(|&base| bio::alphabets::dna::complement(base)),
+                        );
+                        find_codons(&orfs.revcomp, &mut orfs.starts, &mut orfs.stops);
+                        let open = match partial {
+                            Partial::Both => {
+                                open_frames(normalized.len(), codon_size, &orfs.starts, &orfs.stops)
+                            }
+                            _ => Vec::new(),
+                        };
+
+                        find_orfs(normalized.len(), &orfs.starts, &orfs.stops, &mut orfs.all);
+                        orfs.all.retain(keep);
+
+                        orfs.reverse = match prefer_atg {
+                            true => circkit::orfs::longest_orfs_preferring(
+                                &mut orfs.all,
+                                &orfs.revcomp,
+                                b"ATG",
+                                *prefer_atg_window,
+                            ),
+                            false => circkit::orfs::longest_orfs(&mut orfs.all),
+                        };
+                        orfs.reverse
+                            .extend(open.into_iter().filter(|orf| keep(orf)));
+                    }
                 },
                 |record, orfs| {
+                    // the ORFs were found in the rotated sequence, so they have to be read from it too
+                    let full_seq = match origin_normalize {
+                        true => {
+                            let full_seq = record.full_seq();
+                            Cow::Owned(
+                                [&full_seq[orfs.origin..], &full_seq[..orfs.origin]].concat(),
+                            )
+                        }
+                        false => record.full_seq(),
+                    };
+
+                    if let Some(min_spacing) = *min_spacing {
+                        // the reverse strand is thinned on its own, in reverse complement coordinates
+                        let seq_len = full_seq.len();
+                        thin_orfs(&mut orfs.forward, seq_len, min_spacing);
+                        thin_orfs(&mut orfs.reverse, seq_len, min_spacing);
+                    }
+
                     let head = std::str::from_utf8(record.head()).expect(
                         "Could not convert FASTA record header to UTF-8. Are you sure it's ASCII?",
                     );
+                    if let Some(ref mut overlaps_writer) = overlaps_writer {
+                        let seq_len = full_seq.len();
+                        for (orfs, prefix) in [(&orfs.forward, "ORF"), (&orfs.reverse, "RC_ORF")] {
+                            for (i, j, overlap) in out_of_frame_overlaps(orfs, seq_len) {
+                                overlaps_writer
+                                    .serialize(OverlapRow {
+                                        seq_id: head,
+                                        orf_id: format!("{}_{}{}", head, prefix, orfs[i].start),
+                                        other_orf_id: format!(
+                                            "{}_{}{}",
+                                            head, prefix, orfs[j].start
+                                        ),
+                                        frame: orfs[i].frame(),
+                                        other_frame: orfs[j].frame(),
+                                        overlap,
+                                    })
+                                    .expect("failed to write to overlaps table");
+                            }
+                        }
+                    }
+
+                    // the stop codon is left out of the written sequence without --include-stop
+                    let written_trim = |orf: &circkit::orfs::Orf| match *include_stop {
+                        true => 0,
+                        false => stop_len(orf),
+                    };
+                    let written_length = |orf: &circkit::orfs::Orf| orf.length - written_trim(orf);
 
-                    for orf in &orfs.0 {
-                        writer.write_all(b">").unwrap();
-                        writer.write_all(record.head()).unwrap();
-                        writer.write_all(b"_ORF").unwrap();
-                        writer.write_all(orf.start.to_string().as_bytes()).unwrap();
-                        writer.write_all(b"\n").unwrap();
-                        writer
-                            .write_all(
-                                orf.seq_with_opts(&record.full_seq(), *include_stop)
-                                    .as_bytes(),
+                    // the region line marks the sequence as circular, so features may cross its origin
+                    if *format == OrfFormat::Gff3 && !full_seq.is_empty() {
+                        let id = record.id().unwrap();
+                        writeln!(
+                            writer,
+                            "{}\tcirckit\tregion\t1\t{}\t.\t.\t.\tID={};Is_circular=true",
+                            id,
+                            full_seq.len(),
+                            id
+                        )
+                        .unwrap();
+                    }
+
+                    for orf in &orfs.forward {
+                        let seq = orf.seq_trimmed(&full_seq, written_trim(orf));
+                        let protein_seq = match protein {
+                            true => Some(translate_orf(orf, &full_seq)),
+                            false => None,
+                        };
+                        if *cluster {
+                            proteins.push((
+                                format!("{}_ORF{}", head, orf.start),
+                                translate_orf(orf, &full_seq),
+                            ));
+                        } else if *format == OrfFormat::Gff3 {
+                            let id = record.id().unwrap();
+                            write_gff3_cds(
+                                &mut writer,
+                                id,
+                                &format!("{}_ORF{}", id, orf.start),
+                                full_seq.len(),
+                                (orf.start, written_length(orf)),
+                                false,
                             )
                             .unwrap();
-                        writer.write_all(b"\n").unwrap();
+                        } else {
+                            write_record(
+                                &mut writer,
+                                &[record.head(), b"_ORF", orf.start.to_string().as_bytes()],
+                                &[match &protein_seq {
+                                    Some(protein_seq) => protein_seq.as_bytes(),
+                                    None => seq.as_bytes(),
+                                }],
+                            )
+                            .unwrap();
+                        }
 
                         // write the table file if it was requested
                         if let Some(ref mut table_writer) = table_writer {
@@ -129,27 +634,67 @@ pub fn orfs(cmd: &Command) -> anyhow::Result<()> {
                                     seq_id: head.to_string(),
                                     start: orf.start,
                                     stop: orf.stop,
-                                    length: orf.length
-                                        - match *include_stop {
-                                            true => 0,
-                                            false => 3,
-                                        },
+                                    length: written_length(orf),
                                     wraps: orf.wraps,
-                                    ratio: orf.length as f64 / record.full_seq().len() as f64,
+                                    ratio: orf.length as f64 / full_seq.len() as f64,
+                                    stopless_circular: orf.is_stopless_circular(),
+                                    protein: protein_seq,
                                 })
                                 .expect("failed to write to table");
                         }
+
+                        if let Some(ref mut bed_writer) = bed_writer {
+                            let id = record.id().unwrap();
+                            let seq_len = full_seq.len();
+                            for (start, end) in
+                                bed_intervals(seq_len, orf.start, written_length(orf))
+                            {
+                                writeln!(
+                                    bed_writer,
+                                    "{}\t{}\t{}\t{}_ORF{}\t0\t+",
+                                    id, start, end, id, orf.start
+                                )
+                                .expect("failed to write to BED file");
+                            }
+                        }
                     }
-                    for orf in &orfs.1 {
-                        writer.write_all(b">").unwrap();
-                        writer.write_all(record.head()).unwrap();
-                        writer.write_all(b"_RC_ORF").unwrap();
-                        writer.write_all(orf.start.to_string().as_bytes()).unwrap();
-                        writer.write_all(b"\n").unwrap();
-                        writer
-                            .write_all(orf.seq_with_opts(&orfs.2, *include_stop).as_bytes())
+                    for orf in &orfs.reverse {
+                        let seq = orf.seq_trimmed(&orfs.revcomp, written_trim(orf));
+                        let protein_seq = match protein {
+                            true => Some(translate_orf(orf, &orfs.revcomp)),
+                            false => None,
+                        };
+                        if *cluster {
+                            proteins.push((
+                                format!("{}_RC_ORF{}", head, orf.start),
+                                translate_orf(orf, &orfs.revcomp),
+                            ));
+                        } else if *format == OrfFormat::Gff3 {
+                            let id = record.id().unwrap();
+                            let seq_len = orfs.revcomp.len();
+                            write_gff3_cds(
+                                &mut writer,
+                                id,
+                                &format!("{}_RC_ORF{}", id, orf.start),
+                                seq_len,
+                                (
+                                    reverse_begin(seq_len, orf.start, written_length(orf)),
+                                    written_length(orf),
+                                ),
+                                true,
+                            )
+                            .unwrap();
+                        } else {
+                            write_record(
+                                &mut writer,
+                                &[record.head(), b"_RC_ORF", orf.start.to_string().as_bytes()],
+                                &[match &protein_seq {
+                                    Some(protein_seq) => protein_seq.as_bytes(),
+                                    None => seq.as_bytes(),
+                                }],
+                            )
                             .unwrap();
-                        writer.write_all(b"\n").unwrap();
+                        }
 
                         // write the table file if it was requested
                         if let Some(ref mut table_writer) = table_writer {
@@ -157,21 +702,33 @@ pub fn orfs(cmd: &Command) -> anyhow::Result<()> {
                                 .serialize(Row {
                                     orf_id: format!("{}_RC_ORF{}", head, orf.start),
                                     seq_id: head.to_string(),
-                                    start: &orfs.2.len() - 1 - orf.start, // reverse complement coordinates back to forward strand
+                                    start: &orfs.revcomp.len() - 1 - orf.start, // reverse complement coordinates back to forward strand
                                     stop: match orf.stop {
-                                        Some(x) => Some(&orfs.2.len() - 1 - x),
+                                        Some(x) => Some(&orfs.revcomp.len() - 1 - x),
                                         None => None,
                                     },
-                                    length: orf.length
-                                        - match *include_stop {
-                                            true => 0,
-                                            false => 3,
-                                        },
+                                    length: written_length(orf),
                                     wraps: orf.wraps,
-                                    ratio: orf.length as f64 / record.full_seq().len() as f64,
+                                    ratio: orf.length as f64 / full_seq.len() as f64,
+                                    stopless_circular: orf.is_stopless_circular(),
+                                    protein: protein_seq,
                                 })
                                 .expect("failed to write to table");
                         }
+
+                        if let Some(ref mut bed_writer) = bed_writer {
+                            let id = record.id().unwrap();
+                            let seq_len = orfs.revcomp.len();
+                            let begin = reverse_begin(seq_len, orf.start, written_length(orf));
+                            for (start, end) in bed_intervals(seq_len, begin, written_length(orf)) {
+                                writeln!(
+                                    bed_writer,
+                                    "{}\t{}\t{}\t{}_RC_ORF{}\t0\t-",
+                                    id, start, end, id, orf.start
+                                )
+                                .expect("failed to write to BED file");
+                            }
+                        }
                     }
 
                     // Some(value) will stop the reader, and the value will be returned.
@@ -181,10 +738,40 @@ pub fn orfs(cmd: &Command) -> anyhow::Result<()> {
                     None::<()>
                 },
             )?;
+
+            if *cluster {
+                let mut cluster_table_writer = table_path_to_writer(cluster_table);
+                for c in cluster_proteins(&proteins, *protein_max_dist) {
+                    let (id, protein) = &proteins[c.representative];
+                    write_record(&mut writer, &[id.as_bytes()], &[protein.as_bytes()])?;
+
+                    if let Some(ref mut cluster_table_writer) = cluster_table_writer {
+                        for (member, distance) in c.members {
+                            cluster_table_writer
+                                .serialize(ClusterRow {
+                                    id,
+                                    member_id: &proteins[member].0,
+                                    distance,
+                                })
+                                .expect("failed to write to cluster table");
+                        }
+                    }
+                }
+                if let Some(mut cluster_table_writer) = cluster_table_writer {
+                    cluster_table_writer.flush()?;
+                }
+            }
+
             writer.flush()?;
             if let Some(mut table_writer) = table_writer {
                 table_writer.flush()?;
             }
+            if let Some(mut bed_writer) = bed_writer {
+                bed_writer.flush()?;
+            }
+            if let Some(mut overlaps_writer) = overlaps_writer {
+                overlaps_writer.flush()?;
+            }
         }
         _ => panic!("input command is not for orfs"),
     }
@@ -0,0 +1,67 @@
+use seq_io::{fasta::Record, parallel::parallel_fasta};
+
+use crate::{
+    commands::Command,
+    profile::{self, Stage},
+    utils::{count_record, input_to_reader, table_output_to_writer, thread_count},
+};
+
+#[derive(serde::Serialize)]
+struct Row<'a> {
+    id: &'a str,
+    length: usize,
+    period: usize,
+    copies: usize,
+}
+
+pub fn period(cmd: &Command) -> anyhow::Result<()> {
+    match cmd {
+        Command::Period {
+            input,
+            output,
+            max_mismatch,
+            parallel,
+        } => {
+            let reader = input_to_reader(input)?;
+            let mut writer = table_output_to_writer(output)?;
+
+            parallel_fasta(
+                reader,
+                thread_count(parallel.threads),
+                parallel.batch_size,
+                |record, found: &mut (usize, usize)| {
+                    // runs in worker
+                    let _timer = profile::Timer::start(Stage::Compute);
+                    let normalized = match needletail::sequence::normalize(record.seq(), false) {
+                        Some(x) => x,
+                        None => record.seq().to_vec(),
+                    };
+
+                    *found = (
+                        normalized.len(),
+                        circkit::period::period_with_mismatches(&normalized, *max_mismatch),
+                    );
+                },
+                |record, (length, period)| {
+                    // runs in main thread
+                    writer
+                        .serialize(Row {
+                            id: record.id().unwrap(),
+                            length: *length,
+                            period: *period,
+                            copies: match period {
+                                0 => 0,
+                                _ => *length / *period,
+                            },
+                        })
+                        .expect("failed to write to table");
+                    count_record();
+                    None::<()>
+                },
+            )?;
+            writer.flush()?;
+        }
+        _ => panic!("input command is not for period"),
+    }
+    Ok(())
+}
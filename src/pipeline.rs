@@ -0,0 +1,111 @@
+use seq_io::{fasta::Record, parallel::parallel_fasta};
+use std::str::FromStr;
+
+use crate::{
+    commands::Command,
+    exit::UsageError,
+    profile::{self, Stage},
+    rotate::rotation_index,
+    utils::{input_to_reader, output_to_writer, thread_count, write_record},
+};
+
+/// A single per-record operation in a `--transform` chain.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Transform {
+    /// Reverse complement the sequence
+    Revcomp,
+    /// Rotate the sequence by a number of bases (positive to the right, negative to the left)
+    Rotate(i64),
+    /// Canonicalize the circular sequence
+    Canonicalize,
+    /// Convert the sequence to uppercase
+    Uppercase,
+    /// Remove gap characters (`-` and `.`)
+    Degap,
+}
+
+impl FromStr for Transform {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.split_once(':') {
+            Some(("rotate", bases)) => bases
+                .parse()
+                .map(Transform::Rotate)
+                .map_err(|_| format!("invalid rotation amount '{}'", bases)),
+            Some((name, _)) => Err(format!("transform '{}' does not take an argument", name)),
+            None => match s {
+                "revcomp" => Ok(Transform::Revcomp),
+                "canonicalize" => Ok(Transform::Canonicalize),
+                "uppercase" => Ok(Transform::Uppercase),
+                "degap" => Ok(Transform::Degap),
+                "rotate" => Err("rotate requires an amount, e.g. rotate:5".to_string()),
+                _ => Err(format!(
+                    "unknown transform '{}'. Expected one of revcomp, rotate:N, canonicalize, uppercase, degap",
+                    s
+                )),
+            },
+        }
+    }
+}
+
+impl Transform {
+    /// Apply the transform to a sequence.
+    pub fn apply(&self, seq: Vec<u8>) -> Vec<u8> {
+        match self {
+            Transform::Revcomp => bio::alphabets::dna::revcomp(&seq),
+            Transform::Rotate(bases) => {
+                if seq.is_empty() {
+                    return seq;
+                }
+                let i = rotation_index(seq.len(), *bases);
+                [&seq[i..], &seq[..i]].concat()
+            }
+            Transform::Canonicalize => circkit::canonicalize(&seq),
+            Transform::Uppercase => seq.to_ascii_uppercase(),
+            Transform::Degap => seq
+                .into_iter()
+                .filter(|&b| b != b'-' && b != b'.')
+                .collect(),
+        }
+    }
+}
+
+pub fn pipeline(cmd: &Command) -> anyhow::Result<()> {
+    match cmd {
+        Command::Pipeline {
+            input,
+            output,
+            transform,
+            parallel,
+        } => {
+            if transform.is_empty() {
+                return Err(UsageError::new("At least one transform must be given").into());
+            }
+
+            let reader = input_to_reader(input)?;
+            let mut writer = output_to_writer(output)?;
+
+            parallel_fasta(
+                reader,
+                thread_count(parallel.threads),
+                parallel.batch_size,
+                |record, seq| {
+                    // runs in worker
+                    let _timer = profile::Timer::start(Stage::Compute);
+                    *seq = transform
+                        .iter()
+                        .fold(record.full_seq().into_owned(), |seq, t| t.apply(seq));
+                },
+                |record, seq| {
+                    // runs in main thread
+                    write_record(&mut writer, &[record.head()], &[seq]).unwrap();
+                    None::<()>
+                },
+            )?;
+            writer.flush()?;
+        }
+        _ => panic!("input command is not for pipeline"),
+    }
+    Ok(())
+}
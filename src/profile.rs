@@ -0,0 +1,124 @@
+//! Lightweight per-stage timers for `--profile`.
+//!
+//! Timings are accumulated into process-wide atomics so that they can be recorded from the reader thread,
+//! the worker threads, and the main (writer) thread without any locking.
+//! When profiling is disabled, each timer costs a single relaxed atomic load.
+
+use std::{
+    io::{Read, Write},
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        OnceLock,
+    },
+    time::{Duration, Instant},
+};
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+static STARTED: OnceLock<Instant> = OnceLock::new();
+static READ_NANOS: AtomicU64 = AtomicU64::new(0);
+static COMPUTE_NANOS: AtomicU64 = AtomicU64::new(0);
+static WRITE_NANOS: AtomicU64 = AtomicU64::new(0);
+
+/// A phase of processing that time can be attributed to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Stage {
+    /// Reading and decompressing the input
+    Read,
+    /// Per-record work done in the workers
+    Compute,
+    /// Compressing and writing the output
+    Write,
+}
+
+impl Stage {
+    fn counter(self) -> &'static AtomicU64 {
+        match self {
+            Stage::Read => &READ_NANOS,
+            Stage::Compute => &COMPUTE_NANOS,
+            Stage::Write => &WRITE_NANOS,
+        }
+    }
+}
+
+/// Turn on profiling and start the total wall-clock timer.
+pub fn enable() {
+    STARTED.get_or_init(Instant::now);
+    ENABLED.store(true, Ordering::Relaxed);
+}
+
+pub fn is_enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+/// Run `f`, attributing the time it takes to `stage` if profiling is enabled.
+pub fn time<T>(stage: Stage, f: impl FnOnce() -> T) -> T {
+    let _timer = Timer::start(stage);
+    f()
+}
+
+/// A guard that attributes the time until it is dropped to a stage if profiling is enabled.
+pub struct Timer {
+    stage: Stage,
+    start: Option<Instant>,
+}
+
+impl Timer {
+    pub fn start(stage: Stage) -> Self {
+        Timer {
+            stage,
+            start: is_enabled().then(Instant::now),
+        }
+    }
+}
+
+impl Drop for Timer {
+    fn drop(&mut self) {
+        if let Some(start) = self.start {
+            self.stage
+                .counter()
+                .fetch_add(start.elapsed().as_nanos() as u64, Ordering::Relaxed);
+        }
+    }
+}
+
+fn elapsed(stage: Stage) -> Duration {
+    Duration::from_nanos(stage.counter().load(Ordering::Relaxed))
+}
+
+/// Print the timing summary to stderr. Does nothing if profiling is disabled.
+pub fn report() {
+    if !is_enabled() {
+        return;
+    }
+    let total = STARTED.get().map(Instant::elapsed).unwrap_or_default();
+    eprintln!("Profile (wall-clock seconds):");
+    eprintln!("  read     {:>10.3}", elapsed(Stage::Read).as_secs_f64());
+    eprintln!(
+        "  compute  {:>10.3} (summed across worker threads)",
+        elapsed(Stage::Compute).as_secs_f64()
+    );
+    eprintln!("  write    {:>10.3}", elapsed(Stage::Write).as_secs_f64());
+    eprintln!("  total    {:>10.3}", total.as_secs_f64());
+}
+
+/// A reader that attributes the time spent in `read` to [`Stage::Read`].
+pub struct TimedReader<R>(pub R);
+
+impl<R: Read> Read for TimedReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        time(Stage::Read, || self.0.read(buf))
+    }
+}
+
+/// A writer that attributes the time spent in `write` and `flush` to [`Stage::Write`].
+pub struct TimedWriter<W>(pub W);
+
+impl<W: Write> Write for TimedWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        time(Stage::Write, || self.0.write(buf))
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        time(Stage::Write, || self.0.flush())
+    }
+}
@@ -1,11 +1,50 @@
-use anyhow::bail;
-use seq_io::fasta::Record;
+use log::warn;
+use seq_io::{fasta::Record, parallel::parallel_fasta};
 
 use crate::{
     commands::Command,
-    utils::{input_to_reader, output_to_writer},
+    exit::UsageError,
+    profile::{self, Stage},
+    utils::{input_to_reader, output_to_writer, thread_count, write_record},
 };
 
+/// Compute the index that becomes the start of a sequence of length `len` after rotating it by `bases`.
+///
+/// Positive numbers rotate to the right and negative numbers rotate to the left.
+/// The index is always less than `len`, so rotating by a multiple of the length gives 0 rather than `len`.
+/// An empty sequence has nothing to rotate, so its index is 0.
+pub fn rotation_index(len: usize, bases: i64) -> usize {
+    if len == 0 {
+        return 0;
+    }
+    match bases >= 0 {
+        true => (len - (bases as usize % len)) % len,
+        false => bases.unsigned_abs() as usize % len,
+    }
+}
+
+/// Convert a rotation by a fraction of a sequence of length `len` into a number of bases.
+///
+/// The result is truncated towards zero so that rotating left by a fraction moves exactly as many bases as rotating right by it.
+pub fn percent_to_bases(len: usize, percent: f64) -> i64 {
+    (len as f64 * percent).trunc() as i64
+}
+
+/// Find the first index of a circular sequence at which `motif` begins, ignoring case.
+///
+/// The motif may span the origin but may not be longer than the sequence.
+pub fn start_with_index(seq: &[u8], motif: &[u8]) -> Option<usize> {
+    if motif.is_empty() || motif.len() > seq.len() {
+        return None;
+    }
+    (0..seq.len()).find(|&i| {
+        motif
+            .iter()
+            .enumerate()
+            .all(|(j, base)| seq[(i + j) % seq.len()].eq_ignore_ascii_case(base))
+    })
+}
+
 pub fn rotate(cmd: &Command) -> anyhow::Result<()> {
     match cmd {
         Command::Rotate {
@@ -13,36 +52,68 @@ pub fn rotate(cmd: &Command) -> anyhow::Result<()> {
             output,
             bases,
             percent,
+            start_with,
+            parallel,
         } => {
-            let mut reader = input_to_reader(input)?;
+            let reader = input_to_reader(input)?;
             let mut writer = output_to_writer(output)?;
 
             // ensure bases and percent aren't 0
             if bases == &Some(0) || percent == &Some(0.0) {
-                bail!("Rotation by 0 is not allowed");
+                return Err(UsageError::new("Rotation by 0 is not allowed").into());
+            }
+            if start_with.as_ref().is_some_and(|motif| motif.is_empty()) {
+                return Err(UsageError::new("--start-with must not be empty").into());
             }
 
-            while let Some(Ok(record)) = reader.next() {
-                let full_seq = record.full_seq();
-
-                let new_start_index = match percent {
-                    Some(percent) => f64::floor(full_seq.len() as f64 * percent) as i64,
-                    None => bases.expect("Must provide either --bases or --percent"),
-                };
+            parallel_fasta(
+                reader,
+                thread_count(parallel.threads),
+                parallel.batch_size,
+                |record, (rotated, missing_motif): &mut (Vec<u8>, bool)| {
+                    // runs in worker
+                    let _timer = profile::Timer::start(Stage::Compute);
+                    let full_seq = record.full_seq();
 
-                writer.write_all(b">").unwrap();
-                writer.write_all(record.head()).unwrap();
-                writer.write_all(b"\n").unwrap();
+                    *missing_motif = false;
+                    let rotation_index = match start_with {
+                        Some(motif) => match start_with_index(&full_seq, motif.as_bytes()) {
+                            Some(index) => index,
+                            None => {
+                                *missing_motif = true;
+                                0
+                            }
+                        },
+                        None => {
+                            let new_start_index = match percent {
+                                Some(percent) => percent_to_bases(full_seq.len(), *percent),
+                                None => bases.expect(
+                                    "Must provide either --bases, --percent, or --start-with",
+                                ),
+                            };
+                            rotation_index(full_seq.len(), new_start_index)
+                        }
+                    };
 
-                let rotation_index = match new_start_index >= 0 {
-                    true => full_seq.len() - (new_start_index as usize % full_seq.len()),
-                    false => new_start_index.unsigned_abs() as usize % full_seq.len(),
-                };
+                    rotated.clear();
+                    rotated.extend_from_slice(&full_seq[rotation_index..]);
+                    rotated.extend_from_slice(&full_seq[..rotation_index]);
+                },
+                |record, (rotated, missing_motif)| {
+                    // runs in main thread
+                    if let (true, Some(motif)) = (*missing_motif, start_with) {
+                        warn!(
+                            "{} does not contain {}, so it was written unchanged",
+                            record.id().unwrap_or("A record"),
+                            motif
+                        );
+                    }
 
-                writer.write_all(&full_seq[rotation_index..]).unwrap();
-                writer.write_all(&full_seq[..rotation_index]).unwrap();
-                writer.write_all(b"\n").unwrap();
-            }
+                    write_record(&mut writer, &[record.head()], &[rotated]).unwrap();
+                    None::<()>
+                },
+            )?;
+            writer.flush()?;
 
             Ok(())
         }
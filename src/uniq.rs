@@ -1,15 +1,73 @@
 use crate::{
     commands::Command,
-    utils::{input_to_reader, output_to_writer, table_path_to_writer},
+    profile::{self, Stage},
+    utils::{
+        input_to_reader, normalize_in, output_to_writer, table_path_to_writer, thread_count,
+        write_record, LengthFilter, OutputAlphabet,
+    },
 };
 use nohash_hasher::BuildNoHashHasher;
 use seq_io::{fasta::Record, parallel::parallel_fasta};
-use std::collections::HashMap;
+use std::collections::{hash_map::Entry, HashMap};
+use xxhash_rust::xxh3::{xxh3_64, Xxh3};
+
+/// How to choose the form of a sequence that `uniq --canonicalize` outputs.
+#[derive(clap::ArgEnum, Clone, Copy, Debug, PartialEq)]
+pub enum StrandConvention {
+    /// The lexicographically minimal rotation of either strand
+    Lexmin,
+    /// The lexicographically minimal rotation of the strand with more purines, which therefore starts with a purine.
+    /// Falls back to lexmin if both strands have as many purines
+    PurineStart,
+}
+
+/// What makes two records duplicates for `uniq`.
+#[derive(clap::ArgEnum, Clone, Copy, Debug, PartialEq)]
+pub enum DedupBy {
+    /// Having the same canonical sequence
+    Sequence,
+    /// Having the same id, whatever their sequences
+    Header,
+}
 
 #[derive(serde::Serialize)]
 struct Row<'a> {
     id: &'a str,
     duplicate_id: &'a str,
+    forward_count: usize,
+    reverse_count: usize,
+}
+
+#[derive(serde::Serialize)]
+struct ClusterRow<'a> {
+    id: &'a str,
+    size: usize,
+    forward_count: usize,
+    reverse_count: usize,
+    members: String,
+}
+
+/// What a worker found for a record.
+struct Canonical {
+    /// The hash of the canonical form of the record, or of its id when deduplicating by header
+    hash: u64,
+    /// Whether the canonical form is on the reverse complement
+    reverse: bool,
+    /// The canonical form itself, which is only built if it's written out
+    seq: Option<Vec<u8>>,
+}
+
+/// The sequences that are identical to the first one seen, which represents them.
+struct Cluster {
+    id: String,
+    /// Whether the representative's canonical form is on its reverse complement
+    reverse: bool,
+    /// How many members (including the representative) are on the same strand as the representative
+    forward_count: usize,
+    /// How many members are on the opposite strand of the representative
+    reverse_count: usize,
+    /// The ids of the members other than the representative, which are only kept for `--table-all`
+    duplicate_ids: Vec<String>,
 }
 
 pub fn uniq(cmd: &Command) -> anyhow::Result<()> {
@@ -18,55 +76,136 @@ pub fn uniq(cmd: &Command) -> anyhow::Result<()> {
             input,
             output,
             canonicalize,
+            strand_convention,
+            rna,
+            dedup_by,
+            min_length,
+            max_length,
             table,
-            threads,
+            table_all,
+            parallel,
         } => {
+            let length_filter = LengthFilter::new(*min_length, *max_length)?;
             let reader = input_to_reader(input)?;
             let mut writer = output_to_writer(output)?;
-            let mut table_writer = table_path_to_writer(table);
-            let mut seen = HashMap::<u64, String, BuildNoHashHasher<u64>>::default();
+            let table_writer = table_path_to_writer(table);
+            let table_all_writer = table_path_to_writer(table_all);
+            let mut seen = HashMap::<u64, usize, BuildNoHashHasher<u64>>::default(); // hash -> cluster index
+            let mut clusters = Vec::<Cluster>::new();
+            // the table needs the final strand counts of each cluster, so its rows are written at the end
+            let mut duplicates = Vec::<(usize, String)>::new();
 
             parallel_fasta(
                 reader,
-                *threads,
-                64,
-                |record, canonicalized| {
+                thread_count(parallel.threads),
+                parallel.batch_size,
+                |record, canonical: &mut Option<Canonical>| {
                     // runs in worker
-                    let normalized = match needletail::sequence::normalize(record.seq(), false) {
-                        Some(x) => x,
-                        None => record.seq().to_vec(),
-                    };
+                    let _timer = profile::Timer::start(Stage::Compute);
+
+                    // leaving the sequence out marks it as skipped
+                    if !length_filter.keep(record.seq()) {
+                        *canonical = None;
+                        return;
+                    }
 
-                    *canonicalized = circkit::canonicalize(&normalized);
+                    // the sequence is only looked at by header if it's written canonicalized
+                    if *dedup_by == DedupBy::Header && !canonicalize {
+                        *canonical = Some(Canonical {
+                            hash: xxh3_64(record.id_bytes()),
+                            reverse: false,
+                            seq: None,
+                        });
+                        return;
+                    }
+
+                    *canonical = Some(match (canonicalize, rna) {
+                        // only the hash is needed, so the canonical form is streamed into the hasher
+                        // and the sequence is only copied if it has to be normalized
+                        (false, false) => {
+                            let normalized = needletail::sequence::normalize(record.seq(), false);
+                            let mut hasher = Xxh3::new();
+                            let reverse = circkit::hash_canonical(
+                                normalized.as_deref().unwrap_or(record.seq()),
+                                &mut hasher,
+                            );
+                            Canonical {
+                                hash: hasher.digest(),
+                                reverse,
+                                seq: None,
+                            }
+                        }
+                        (_, true) => {
+                            let (seq, _, reverse) = circkit::canonicalize_with_order(
+                                &normalize_in(record.seq(), OutputAlphabet::Rna),
+                                circkit::canonicalize::rna_rank,
+                                circkit::canonicalize::rna_complement,
+                            );
+                            Canonical {
+                                hash: xxh3_64(&seq),
+                                reverse,
+                                seq: canonicalize.then_some(seq),
+                            }
+                        }
+                        (true, false) => {
+                            let (seq, _, reverse) = circkit::canonicalize_with_offset(
+                                &normalize_in(record.seq(), OutputAlphabet::Dna),
+                            );
+                            Canonical {
+                                hash: xxh3_64(&seq),
+                                reverse,
+                                seq: Some(seq),
+                            }
+                        }
+                    });
+                    if *dedup_by == DedupBy::Header {
+                        // records with the same id needn't be on the same strand, so they're all counted as forward
+                        if let Some(canonical) = canonical {
+                            canonical.hash = xxh3_64(record.id_bytes());
+                            canonical.reverse = false;
+                        }
+                    }
                 },
-                |record, canonicalized| {
+                |record, canonical| {
                     // runs in main thread
+                    let Some(Canonical { hash, reverse, seq }) = canonical else {
+                        return None;
+                    };
 
-                    let canonicalized_hash = xxhash_rust::xxh3::xxh3_64(canonicalized);
-
-                    if !seen.contains_key(&canonicalized_hash) {
-                        seen.insert(canonicalized_hash, record.id().unwrap().to_owned());
+                    match seen.entry(*hash) {
+                        Entry::Vacant(entry) => {
+                            entry.insert(clusters.len());
+                            clusters.push(Cluster {
+                                id: record.id().unwrap().to_owned(),
+                                reverse: *reverse,
+                                forward_count: 1,
+                                reverse_count: 0,
+                                duplicate_ids: Vec::new(),
+                            });
 
-                        writer.write_all(b">").unwrap();
-                        writer.write_all(record.head()).unwrap();
-                        writer.write_all(b"\n").unwrap();
-                        match canonicalize {
-                            true => {
-                                writer.write_all(canonicalized).unwrap();
+                            let purine_strand;
+                            let seq = match (seq, strand_convention) {
+                                (Some(seq), StrandConvention::Lexmin) => seq.as_slice(),
+                                (Some(seq), StrandConvention::PurineStart) => {
+                                    purine_strand = circkit::canonicalize_purine_strand(seq);
+                                    purine_strand.as_slice()
+                                }
+                                (None, _) => record.seq(),
+                            };
+                            write_record(&mut writer, &[record.head()], &[seq]).unwrap();
+                        }
+                        Entry::Occupied(entry) => {
+                            let cluster = &mut clusters[*entry.get()];
+                            match cluster.reverse == *reverse {
+                                true => cluster.forward_count += 1,
+                                false => cluster.reverse_count += 1,
+                            }
+                            if table_writer.is_some() {
+                                duplicates.push((*entry.get(), record.id().unwrap().to_owned()));
                             }
-                            false => {
-                                writer.write_all(record.seq()).unwrap();
+                            if table_all_writer.is_some() {
+                                cluster.duplicate_ids.push(record.id().unwrap().to_owned());
                             }
-                        };
-                        writer.write_all(b"\n").unwrap();
-                    } else {
-                        if let Some(ref mut table_writer) = table_writer {
-                            table_writer
-                                .serialize(Row {
-                                    id: seen.get(&canonicalized_hash).unwrap(),
-                                    duplicate_id: record.id().unwrap(),
-                                })
-                                .expect("failed to serialize table row");
                         }
                     }
 
@@ -78,9 +217,39 @@ pub fn uniq(cmd: &Command) -> anyhow::Result<()> {
                 },
             )?;
             writer.flush()?;
+            length_filter.report();
             if let Some(mut table_writer) = table_writer {
+                for (cluster, duplicate_id) in &duplicates {
+                    let cluster = &clusters[*cluster];
+                    table_writer
+                        .serialize(Row {
+                            id: &cluster.id,
+                            duplicate_id,
+                            forward_count: cluster.forward_count,
+                            reverse_count: cluster.reverse_count,
+                        })
+                        .expect("failed to serialize table row");
+                }
                 table_writer.flush()?;
             }
+            if let Some(mut table_all_writer) = table_all_writer {
+                for cluster in &clusters {
+                    table_all_writer
+                        .serialize(ClusterRow {
+                            id: &cluster.id,
+                            size: cluster.forward_count + cluster.reverse_count,
+                            forward_count: cluster.forward_count,
+                            reverse_count: cluster.reverse_count,
+                            members: std::iter::once(&cluster.id)
+                                .chain(&cluster.duplicate_ids)
+                                .map(String::as_str)
+                                .collect::<Vec<_>>()
+                                .join(","),
+                        })
+                        .expect("failed to serialize table row");
+                }
+                table_all_writer.flush()?;
+            }
         }
         _ => panic!("input command is not for uniq"),
     }
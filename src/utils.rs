@@ -1,84 +1,1178 @@
-use anyhow::bail;
-use seq_io::fasta::Reader;
+use anyhow::{bail, Context};
+use log::{info, warn};
+use seq_io::fasta::{Reader, Record};
 use std::{
     fs::File,
-    io::{prelude::*, stdin, stdout, BufReader, BufWriter},
-    path::PathBuf,
+    io::{prelude::*, stdin, stdout, BufReader, BufWriter, Cursor, SeekFrom},
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Mutex, OnceLock,
+    },
 };
 
+use crate::{
+    exit::UsageError,
+    profile::{self, TimedReader, TimedWriter},
+};
+
+/// Whether `--mmap` was given, i.e. to memory-map uncompressed input files instead of reading them through a buffer.
+static MMAP_INPUT: AtomicBool = AtomicBool::new(false);
+
+pub fn set_mmap_input(enabled: bool) {
+    MMAP_INPUT.store(enabled, Ordering::Relaxed);
+}
+
+/// Whether `--null-output` was given, i.e. to discard everything instead of writing it.
+static NULL_OUTPUT: AtomicBool = AtomicBool::new(false);
+
+pub fn set_null_output(enabled: bool) {
+    NULL_OUTPUT.store(enabled, Ordering::Relaxed);
+}
+
+/// Whether `--atomic` was given, i.e. to write output files under a temporary name until the command succeeds.
+static ATOMIC_OUTPUT: AtomicBool = AtomicBool::new(false);
+
+pub fn set_atomic_output(enabled: bool) {
+    ATOMIC_OUTPUT.store(enabled, Ordering::Relaxed);
+}
+
+/// The temporary files written with `--atomic`, along with the paths they are renamed to when the command succeeds.
+static PENDING_OUTPUTS: Mutex<Vec<(PathBuf, PathBuf)>> = Mutex::new(Vec::new());
+
+/// The path to actually create for an output file.
+///
+/// With `--atomic`, this is a hidden temporary file in the same directory, so that renaming it is atomic.
+fn output_path(path: &Path) -> PathBuf {
+    if !ATOMIC_OUTPUT.load(Ordering::Relaxed) {
+        return path.to_path_buf();
+    }
+    let mut name = std::ffi::OsString::from(".");
+    name.push(path.file_name().unwrap_or_default());
+    name.push(format!(".circkit-{}.tmp", std::process::id()));
+    let temp = path.with_file_name(name);
+    PENDING_OUTPUTS
+        .lock()
+        .unwrap()
+        .push((temp.clone(), path.to_path_buf()));
+    temp
+}
+
+/// Move the temporary files written with `--atomic` to their final paths.
+///
+/// This must only be called once every writer has been dropped, since compressed output is only finished then.
+pub fn commit_outputs() -> anyhow::Result<()> {
+    for (temp, path) in PENDING_OUTPUTS.lock().unwrap().drain(..) {
+        std::fs::rename(&temp, &path)
+            .with_context(|| format!("Could not move {} to {}", temp.display(), path.display()))?;
+    }
+    Ok(())
+}
+
+/// Remove the temporary files written with `--atomic`, leaving any existing files at their final paths untouched.
+pub fn discard_outputs() {
+    for (temp, _) in PENDING_OUTPUTS.lock().unwrap().drain(..) {
+        let _ = std::fs::remove_file(temp);
+    }
+}
+
+/// The format given with `--input-format`.
+static INPUT_FORMAT: OnceLock<InputFormat> = OnceLock::new();
+
+/// The format of the records in the input.
+#[derive(clap::ArgEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum InputFormat {
+    #[default]
+    Fasta,
+    /// Newline-delimited JSON objects with "id" and "seq" fields and an optional "desc" field
+    Jsonl,
+}
+
+/// Set the format that [`input_to_reader`] expects. Only the first call has any effect.
+pub fn set_input_format(format: InputFormat) {
+    let _ = INPUT_FORMAT.set(format);
+}
+
+/// The id given with `--id`, i.e. the only record to read from the input.
+static INPUT_ID: OnceLock<String> = OnceLock::new();
+
+/// Set the id of the only record that [`input_to_reader`] should yield. Only the first call has any effect.
+pub fn set_input_id(id: Option<String>) {
+    if let Some(id) = id {
+        let _ = INPUT_ID.set(id);
+    }
+}
+
+/// The records given with `--skip` and `--limit`, as the number of records to skip and the maximum number to read after them.
+static RECORD_RANGE: OnceLock<(usize, Option<usize>)> = OnceLock::new();
+
+/// Set the range of records that [`input_to_reader`] should yield. Only the first call has any effect.
+pub fn set_record_range(skip: usize, limit: Option<usize>) {
+    let _ = RECORD_RANGE.set((skip, limit));
+}
+
+/// Whether `--skip-all-n` was given, i.e. to drop records without any unambiguous bases as they are read.
+static SKIP_ALL_N: AtomicBool = AtomicBool::new(false);
+
+pub fn set_skip_all_n(enabled: bool) {
+    SKIP_ALL_N.store(enabled, Ordering::Relaxed);
+}
+
+/// Whether a sequence has bases but none of them is A, C, G, T, or U, e.g. because it is all N.
+pub fn is_all_n(seq: &[u8]) -> bool {
+    let mut bases = seq.iter().filter(|b| !b.is_ascii_whitespace()).peekable();
+    bases.peek().is_some()
+        && bases.all(|b| !matches!(b.to_ascii_uppercase(), b'A' | b'C' | b'G' | b'T' | b'U'))
+}
+
+/// The number of records written so far by any command, for `--fail-on-empty`.
+static RECORDS_WRITTEN: AtomicUsize = AtomicUsize::new(0);
+
+/// Count a record written without going through [`write_record`], e.g. a table row.
+pub fn count_record() {
+    RECORDS_WRITTEN.fetch_add(1, Ordering::Relaxed);
+}
+
+pub fn records_written() -> usize {
+    RECORDS_WRITTEN.load(Ordering::Relaxed)
+}
+
+/// Options that apply to every FASTA record written by any command.
+#[derive(Debug, Default, Clone)]
+pub struct OutputOptions {
+    /// Append ` length=N` to each header, where N is the length of the written sequence
+    pub append_length: bool,
+    /// Convert each written sequence to this alphabet, or leave it as is if `None`
+    pub alphabet: Option<OutputAlphabet>,
+    /// Truncate each written sequence to at most this many bases, or leave it as is if `None`
+    pub trim_to_length: Option<usize>,
+}
+
+/// The nucleotide alphabet to write sequences in.
+#[derive(clap::ArgEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OutputAlphabet {
+    /// Write U as T
+    Dna,
+    /// Write T as U
+    Rna,
+}
+
+impl OutputAlphabet {
+    /// Convert a single base, keeping its case.
+    pub fn convert(self, base: u8) -> u8 {
+        match (self, base) {
+            (OutputAlphabet::Dna, b'U') => b'T',
+            (OutputAlphabet::Dna, b'u') => b't',
+            (OutputAlphabet::Rna, b'T') => b'U',
+            (OutputAlphabet::Rna, b't') => b'u',
+            _ => base,
+        }
+    }
+}
+
+/// Normalize a sequence as needletail does (uppercase, with anything but nucleotides as N), writing it in `alphabet`.
+///
+/// Needletail always writes U as T, so RNA is transcribed back afterwards.
+pub fn normalize_in(seq: &[u8], alphabet: OutputAlphabet) -> Vec<u8> {
+    let mut normalized =
+        needletail::sequence::normalize(seq, false).unwrap_or_else(|| seq.to_vec());
+    if alphabet == OutputAlphabet::Rna {
+        for base in normalized.iter_mut() {
+            *base = alphabet.convert(*base);
+        }
+    }
+    normalized
+}
+
+static OUTPUT_OPTIONS: OnceLock<OutputOptions> = OnceLock::new();
+
+/// Set the options used by [`write_record`]. Only the first call has any effect.
+pub fn set_output_options(options: OutputOptions) {
+    let _ = OUTPUT_OPTIONS.set(options);
+}
+
+/// The format to write records in.
+#[derive(clap::ArgEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum OutputFormat {
+    #[default]
+    Fasta,
+    /// One sequence per line, without headers
+    Txt,
+}
+
+impl OutputFormat {
+    /// Infer the format from the extension of an output path, looking past any compression extension.
+    ///
+    /// Only .txt is written as txt. Everything else, including unknown extensions, is FASTA.
+    pub fn from_path(path: &Path) -> Self {
+        let name = path.file_name().unwrap_or_default().to_string_lossy();
+        let mut extensions = name.rsplit('.');
+        let extension = match extensions.next() {
+            Some("gz" | "bz2" | "xz" | "zst") => extensions.next(),
+            extension => extension,
+        };
+        match extension {
+            Some("txt") => OutputFormat::Txt,
+            _ => OutputFormat::Fasta,
+        }
+    }
+}
+
+static OUTPUT_FORMAT: OnceLock<OutputFormat> = OnceLock::new();
+
+/// Set the format used by [`write_record`]. Only the first call has any effect.
+///
+/// If it isn't set, it is inferred from the path of the main output when that is opened.
+pub fn set_output_format(format: Option<OutputFormat>) {
+    if let Some(format) = format {
+        let _ = OUTPUT_FORMAT.set(format);
+    }
+}
+
+/// Write a record, applying the global [`OutputOptions`] and [`OutputFormat`].
+///
+/// The header (without the leading `>`) and the sequence are each given as a list of parts that are written back to back,
+/// so that callers don't have to allocate to build them.
+/// Line breaks inside the sequence are written as is but don't count towards its length.
+pub fn write_record<W: Write + ?Sized>(
+    writer: &mut W,
+    head: &[&[u8]],
+    seq: &[&[u8]],
+) -> std::io::Result<()> {
+    let options = OUTPUT_OPTIONS.get_or_init(OutputOptions::default);
+    count_record();
+
+    let trimmed;
+    let seq = match options.trim_to_length {
+        Some(max_length) => {
+            trimmed = trim_parts(seq, max_length);
+            &trimmed[..]
+        }
+        None => seq,
+    };
+
+    let histogram = HISTOGRAM.get();
+    let length = match options.append_length || histogram.is_some() {
+        true => seq
+            .iter()
+            .map(|part| part.iter().filter(|&&b| b != b'\n' && b != b'\r').count())
+            .sum(),
+        false => 0,
+    };
+    if let Some(histogram) = histogram {
+        histogram.add(length);
+    }
+
+    let format = OUTPUT_FORMAT.get().copied().unwrap_or_default();
+    if format == OutputFormat::Fasta {
+        writer.write_all(b">")?;
+        for part in head {
+            writer.write_all(part)?;
+        }
+        if options.append_length {
+            write!(writer, " length={}", length)?;
+        }
+        writer.write_all(b"\n")?;
+    }
+    for part in seq {
+        match (options.alphabet, format) {
+            (None, OutputFormat::Fasta) => writer.write_all(part)?,
+            (alphabet, _) => {
+                // a txt sequence has to stay on a single line
+                let converted = part
+                    .iter()
+                    .filter(|&&b| format == OutputFormat::Fasta || (b != b'\n' && b != b'\r'))
+                    .map(|&b| alphabet.map_or(b, |alphabet| alphabet.convert(b)))
+                    .collect::<Vec<_>>();
+                writer.write_all(&converted)?;
+            }
+        }
+    }
+    writer.write_all(b"\n")
+}
+
+/// The number of buckets in a [`LengthHistogram`]: one for empty sequences and one per power of two.
+const HISTOGRAM_BUCKETS: usize = usize::BITS as usize + 1;
+
+/// A histogram of sequence lengths with a bucket per power of two, so that it takes constant memory however many sequences are added.
+///
+/// Bucket 0 holds empty sequences and bucket k holds lengths from 2^(k-1) to 2^k - 1.
+/// It can be added to from any thread.
+#[derive(Debug)]
+pub struct LengthHistogram {
+    counts: [AtomicUsize; HISTOGRAM_BUCKETS],
+}
+
+impl Default for LengthHistogram {
+    fn default() -> Self {
+        LengthHistogram {
+            counts: std::array::from_fn(|_| AtomicUsize::new(0)),
+        }
+    }
+}
+
+impl LengthHistogram {
+    pub fn add(&self, length: usize) {
+        let bucket = (usize::BITS - length.leading_zeros()) as usize;
+        self.counts[bucket].fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// The smallest and largest length (both inclusive) in a bucket.
+    pub fn bucket_range(bucket: usize) -> (usize, usize) {
+        match bucket {
+            0 => (0, 0),
+            _ => (1 << (bucket - 1), ((1u128 << bucket) - 1) as usize),
+        }
+    }
+
+    /// Each bucket as `(min_length, max_length, count)`, from the first to the last one that isn't empty.
+    pub fn buckets(&self) -> Vec<(usize, usize, usize)> {
+        let counts = self
+            .counts
+            .iter()
+            .map(|count| count.load(Ordering::Relaxed))
+            .collect::<Vec<_>>();
+        let (Some(first), Some(last)) = (
+            counts.iter().position(|&count| count > 0),
+            counts.iter().rposition(|&count| count > 0),
+        ) else {
+            return Vec::new();
+        };
+        (first..=last)
+            .map(|bucket| {
+                let (min, max) = LengthHistogram::bucket_range(bucket);
+                (min, max, counts[bucket])
+            })
+            .collect()
+    }
+}
+
+static HISTOGRAM: OnceLock<LengthHistogram> = OnceLock::new();
+
+/// Start counting the length of every sequence written by [`write_record`].
+pub fn enable_histogram() {
+    let _ = HISTOGRAM.set(LengthHistogram::default());
+}
+
+/// Write the lengths counted since [`enable_histogram`] to a CSV or TSV file, depending on its extension.
+///
+/// Nothing but the header is written if no sequences were.
+pub fn write_histogram(path: &Path) -> anyhow::Result<()> {
+    create_parent_dir(path)?;
+    // open the file first, so that failing to create it is an IO error rather than a CSV one
+    let file = File::create(output_path(path))
+        .with_context(|| format!("Could not create {}", path.display()))?;
+    let mut writer = csv::WriterBuilder::new()
+        .delimiter(table_delimiter(path))
+        .from_writer(file);
+    writer.write_record(["min_length", "max_length", "count"])?;
+    for bucket in HISTOGRAM
+        .get()
+        .map(LengthHistogram::buckets)
+        .unwrap_or_default()
+    {
+        writer.serialize(bucket)?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+/// Cut the sequence parts so that they contain at most `max_length` bases in total.
+///
+/// Line breaks don't count as bases, and the line break following the last kept base is dropped too.
+fn trim_parts<'a>(seq: &[&'a [u8]], max_length: usize) -> Vec<&'a [u8]> {
+    let mut remaining = max_length;
+    let mut trimmed = Vec::with_capacity(seq.len());
+    for part in seq {
+        if remaining == 0 {
+            break;
+        }
+        let mut end = 0;
+        while end < part.len() && remaining > 0 {
+            if part[end] != b'\n' && part[end] != b'\r' {
+                remaining -= 1;
+            }
+            end += 1;
+        }
+        trimmed.push(&part[..end]);
+    }
+    trimmed
+}
+
+/// How many times the number of logical cores `--threads` may be before it is capped.
+const MAX_THREADS_PER_CORE: usize = 4;
+
+/// The number of worker threads to use when `requested` were asked for.
+///
+/// Many more threads than cores only adds overhead, so the count is capped at a few times the number of logical cores, with a warning.
+pub fn thread_count(requested: u32) -> u32 {
+    let cap = (num_cpus::get() * MAX_THREADS_PER_CORE) as u32;
+    if requested > cap {
+        warn!(
+            "{} threads were requested but only {} logical cores are available, so {} threads will be used",
+            requested,
+            num_cpus::get(),
+            cap
+        );
+        return cap;
+    }
+    requested
+}
+
+/// A filter on the length of input sequences, given by `--min-length` and `--max-length`.
+///
+/// It is shared by the worker threads, so the number of sequences it rejected is counted atomically.
+#[derive(Debug, Default)]
+pub struct LengthFilter {
+    min: usize,
+    max: Option<usize>,
+    skipped: AtomicUsize,
+}
+
+impl LengthFilter {
+    pub fn new(min: usize, max: Option<usize>) -> anyhow::Result<Self> {
+        if let Some(max) = max {
+            if max < min {
+                return Err(UsageError::new(format!(
+                    "max_length ({}) must not be less than min_length ({})",
+                    max, min
+                ))
+                .into());
+            }
+        }
+        Ok(LengthFilter {
+            min,
+            max,
+            skipped: AtomicUsize::new(0),
+        })
+    }
+
+    /// Whether a sequence is in range, not counting line breaks. Sequences that aren't are counted as skipped.
+    pub fn keep(&self, seq: &[u8]) -> bool {
+        let len = seq.iter().filter(|&&b| b != b'\n' && b != b'\r').count();
+        let keep = len >= self.min && self.max.is_none_or(|max| len <= max);
+        if !keep {
+            self.skipped.fetch_add(1, Ordering::Relaxed);
+        }
+        keep
+    }
+
+    /// Log how many sequences were skipped for being out of range, if any.
+    pub fn report(&self) {
+        let skipped = self.skipped.load(Ordering::Relaxed);
+        if skipped > 0 {
+            info!(
+                "Skipped {} sequence{} outside the length range",
+                skipped,
+                if skipped == 1 { "" } else { "s" }
+            );
+        }
+    }
+}
+
+/// Open the input for reading. `None` and `-` both mean stdin.
+///
+/// If `--id` was given, only the record with that id is read.
+/// When the input is a file with a samtools faidx index next to it (e.g. `in.fasta.fai`), the record is read directly without scanning the file.
+/// If `--skip` or `--limit` was given, only the records in that range are read.
 pub fn input_to_reader(input: &Option<PathBuf>) -> anyhow::Result<Reader<Box<dyn Read + Send>>> {
-    match input {
-        Some(input) => {
-            let fp_bufreader = BufReader::new(File::open(input)?);
-            let niffed = niffler::send::get_reader(Box::new(fp_bufreader))?.0;
-            let reader = Reader::new(niffed);
-            Ok(reader)
+    let reader = selected_input(input)?;
+    let (skip, limit) = RECORD_RANGE.get().copied().unwrap_or_default();
+    let skip_all_n = SKIP_ALL_N.load(Ordering::Relaxed);
+    match skip > 0 || limit.is_some() || skip_all_n {
+        true => Ok(fasta_reader(Box::new(
+            RecordRange::new(reader, skip, limit).skip_all_n(skip_all_n),
+        ))),
+        false => Ok(reader),
+    }
+}
+
+/// Open the input, narrowed down to the record given with `--id` if there is one.
+fn selected_input(input: &Option<PathBuf>) -> anyhow::Result<Reader<Box<dyn Read + Send>>> {
+    let id = match INPUT_ID.get() {
+        Some(id) => id,
+        None => return open_input(input),
+    };
+
+    let record = match input.as_ref().and_then(|input| index_path(input)) {
+        Some((input, index)) => {
+            info!("Reading {} using the index {}", id, index.display());
+            indexed_record(&input, &index, id)?
         }
+        None => scanned_record(input, id)?,
+    };
+    Ok(fasta_reader(Box::new(Cursor::new(record))))
+}
+
+fn open_input(input: &Option<PathBuf>) -> anyhow::Result<Reader<Box<dyn Read + Send>>> {
+    let reader = match input {
+        // an explicit `-` is a deliberate request for stdin, so don't second-guess it with the TTY check
+        Some(input) if input.as_os_str() == "-" => stdin_reader()?,
+        Some(input) => match MMAP_INPUT.load(Ordering::Relaxed) {
+            true => match mmap_reader(input)? {
+                Some(mapped) => mapped,
+                None => file_reader(input)?,
+            },
+            false => file_reader(input)?,
+        },
         None => {
             if atty::is(atty::Stream::Stdin) {
-                bail!("No stdin detected. Did you mean to include a file argument?");
+                return Err(UsageError::new("No stdin detected. Did you mean to include a file argument (or - to read from the terminal)?").into());
             }
-            let stdin_bufreader = BufReader::new(stdin());
-            let niffed = niffler::send::get_reader(Box::new(stdin_bufreader))?.0;
-            let reader = Reader::new(niffed);
-            Ok(reader)
+            stdin_reader()?
+        }
+    };
+
+    // every other format is converted to FASTA on the fly, so the commands only ever have to parse FASTA
+    match INPUT_FORMAT.get_or_init(InputFormat::default) {
+        InputFormat::Fasta => Ok(fasta_reader(reader)),
+        InputFormat::Jsonl => Ok(fasta_reader(Box::new(JsonlToFasta::new(BufReader::new(
+            reader,
+        ))))),
+    }
+}
+
+fn file_reader(input: &Path) -> anyhow::Result<Box<dyn Read + Send>> {
+    let fp_bufreader = BufReader::new(File::open(input)?);
+    Ok(niffler::send::get_reader(Box::new(fp_bufreader))?.0)
+}
+
+/// The companion `.fai` index of an input file, if there is one.
+///
+/// The offsets of an index are into the uncompressed bytes, so it isn't used for compressed input, which is scanned instead.
+fn index_path(input: &Path) -> Option<(PathBuf, PathBuf)> {
+    if input.as_os_str() == "-" {
+        return None;
+    }
+    let mut index = input.as_os_str().to_owned();
+    index.push(".fai");
+    let index = PathBuf::from(index);
+    if !index.is_file() {
+        return None;
+    }
+
+    let file = BufReader::new(File::open(input).ok()?);
+    match niffler::send::sniff(Box::new(file)).ok()?.1 {
+        niffler::send::compression::Format::No => Some((input.to_path_buf(), index)),
+        _ => {
+            info!(
+                "{} is compressed, so it will be scanned instead of using the index {}",
+                input.display(),
+                index.display()
+            );
+            None
+        }
+    }
+}
+
+/// A single line of a samtools faidx index.
+#[derive(Debug, PartialEq, Eq)]
+pub struct FaiEntry {
+    pub name: String,
+    /// The number of bases in the sequence
+    pub length: u64,
+    /// The byte offset of the first base of the sequence
+    pub offset: u64,
+    /// The number of bases on each line
+    pub line_bases: u64,
+    /// The number of bytes on each line, including the line break
+    pub line_width: u64,
+}
+
+impl FaiEntry {
+    /// The number of bytes the sequence takes up in the file, including line breaks but not the final one.
+    pub fn byte_len(&self) -> u64 {
+        if self.length == 0 || self.line_bases == 0 {
+            return 0;
+        }
+        let full_lines = (self.length - 1) / self.line_bases;
+        full_lines * self.line_width + (self.length - full_lines * self.line_bases)
+    }
+}
+
+/// Find the entry for `id` in a samtools faidx index.
+pub fn find_fai_entry<R: BufRead>(index: R, id: &str) -> anyhow::Result<Option<FaiEntry>> {
+    for line in index.lines() {
+        let line = line?;
+        let fields: Vec<&str> = line.split('\t').collect();
+        if fields.len() < 5 {
+            bail!("Malformed line in FASTA index: {}", line);
+        }
+        if fields[0] != id {
+            continue;
+        }
+        let number = |field: &str| {
+            field
+                .parse::<u64>()
+                .with_context(|| format!("Malformed line in FASTA index: {}", line))
+        };
+        return Ok(Some(FaiEntry {
+            name: fields[0].to_string(),
+            length: number(fields[1])?,
+            offset: number(fields[2])?,
+            line_bases: number(fields[3])?,
+            line_width: number(fields[4])?,
+        }));
+    }
+    Ok(None)
+}
+
+/// Read a single record from an indexed FASTA file by seeking straight to it.
+///
+/// The record is returned as FASTA, including its full header line.
+fn indexed_record(input: &Path, index: &Path, id: &str) -> anyhow::Result<Vec<u8>> {
+    let entry = match find_fai_entry(BufReader::new(File::open(index)?), id)? {
+        Some(entry) => entry,
+        None => bail!("No record with id {} in {}", id, index.display()),
+    };
+    let mut file = File::open(input)?;
+
+    // the index only points at the sequence, so look backwards from it for the header line
+    let window = entry.offset.min(64 * 1024);
+    let mut before = vec![0; window as usize];
+    file.seek(SeekFrom::Start(entry.offset - window))?;
+    file.read_exact(&mut before)?;
+    let before = before.strip_suffix(b"\n").unwrap_or(&before);
+    let before = before.strip_suffix(b"\r").unwrap_or(before);
+    let head_start = before
+        .iter()
+        .rposition(|&b| b == b'\n')
+        .map_or(0, |i| i + 1);
+    let head = &before[head_start..];
+    if !head.starts_with(b">") || (head_start == 0 && window < entry.offset) {
+        bail!(
+            "Could not find the header of {} in {}. Is the index out of date?",
+            id,
+            input.display()
+        );
+    }
+
+    let mut record = head.to_vec();
+    record.push(b'\n');
+    let seq_start = record.len();
+    record.resize(seq_start + entry.byte_len() as usize, 0);
+    file.seek(SeekFrom::Start(entry.offset))?;
+    file.read_exact(&mut record[seq_start..])?;
+    record.push(b'\n');
+    Ok(record)
+}
+
+/// Read the input until the record with `id` is found, returning it as FASTA.
+fn scanned_record(input: &Option<PathBuf>, id: &str) -> anyhow::Result<Vec<u8>> {
+    let mut reader = open_input(input)?;
+    while let Some(record) = reader.next() {
+        let record = record?;
+        if record.id() == Ok(id) {
+            let mut found = Vec::new();
+            record.write(&mut found)?;
+            return Ok(found);
         }
     }
+    bail!("No record with id {} in the input", id)
 }
 
+fn stdin_reader() -> anyhow::Result<Box<dyn Read + Send>> {
+    let stdin_bufreader = BufReader::new(stdin());
+    Ok(niffler::send::get_reader(Box::new(stdin_bufreader))?.0)
+}
+
+/// Memory-map an uncompressed regular file.
+///
+/// Returns `None` if the file should be read through a buffer instead, which is the case for compressed files, empty files, and anything that is not a regular file.
+fn mmap_reader(path: &Path) -> anyhow::Result<Option<Box<dyn Read + Send>>> {
+    let file = File::open(path)?;
+    let metadata = file.metadata()?;
+    if !metadata.is_file() {
+        info!(
+            "{} is not a regular file, so it will not be memory-mapped",
+            path.display()
+        );
+        return Ok(None);
+    }
+    if metadata.len() == 0 {
+        return Ok(None);
+    }
+    // SAFETY: the mapping is only ever read. If another process truncates or modifies the file while it is mapped,
+    // the reader may see inconsistent data or be killed by SIGBUS, which is the usual trade-off of memory-mapping.
+    let mapped = match unsafe { memmap2::Mmap::map(&file) } {
+        Ok(mapped) => mapped,
+        Err(e) => {
+            info!(
+                "Could not memory-map {} ({}), falling back to buffered reading",
+                path.display(),
+                e
+            );
+            return Ok(None);
+        }
+    };
+    // the reader consumes the file front to back
+    #[cfg(unix)]
+    if let Err(e) = mapped.advise(memmap2::Advice::Sequential) {
+        info!(
+            "Could not advise sequential access to {}: {}",
+            path.display(),
+            e
+        );
+    }
+
+    // compressed input has to be streamed through a decoder anyway, so mapping it gains nothing
+    let (reader, format) = niffler::send::sniff(Box::new(std::io::Cursor::new(mapped)))?;
+    match format {
+        niffler::send::compression::Format::No => Ok(Some(reader)),
+        _ => {
+            info!(
+                "{} is compressed, so it will not be memory-mapped",
+                path.display()
+            );
+            Ok(None)
+        }
+    }
+}
+
+/// The size given with `--chunk-bytes`, if any.
+static CHUNK_BYTES: OnceLock<usize> = OnceLock::new();
+
+pub fn set_chunk_bytes(chunk_bytes: Option<usize>) {
+    if let Some(chunk_bytes) = chunk_bytes {
+        let _ = CHUNK_BYTES.set(chunk_bytes);
+    }
+}
+
+/// Open the main output of a command for writing. `None` means stdout.
+///
+/// If `--chunk-bytes` was given, the output is split into numbered files instead (see [`ChunkedWriter`]).
 pub fn output_to_writer(output: &Option<PathBuf>) -> anyhow::Result<Box<dyn Write>> {
-    match output {
-        Some(output) => {
-            // match the suffix of outout to see if it should be compressed
-            let suffix = output.extension().unwrap_or_default().to_str().unwrap();
-
-            let compression_format = match suffix {
-                "gz" => niffler::send::compression::Format::Gzip,
-                "bz2" => niffler::send::compression::Format::Bzip,
-                "xz" => niffler::send::compression::Format::Lzma,
-                "zst" => niffler::send::compression::Format::Zstd,
-                _ => niffler::send::compression::Format::No,
-            };
+    // still time the writes, so that --profile shows the cost of formatting the output
+    if NULL_OUTPUT.load(Ordering::Relaxed) {
+        return Ok(timed_writer(Box::new(std::io::sink())));
+    }
 
-            let outfile = match File::create(output) {
-                Ok(file) => file,
-                Err(_) => {
-                    bail!(
-                        "Could not create output file {}. Are you sure it's not actually a directory?",
-                        output.display()
-                    );
-                }
-            };
+    let format = *OUTPUT_FORMAT.get_or_init(|| {
+        output
+            .as_deref()
+            .map(OutputFormat::from_path)
+            .unwrap_or_default()
+    });
 
-            let fp_bufwriter = BufWriter::new(outfile);
-            let niffed = niffler::send::get_writer(
-                Box::new(fp_bufwriter),
-                compression_format,
-                match compression_format {
-                    niffler::send::compression::Format::Gzip => niffler::compression::Level::Six,
-                    niffler::send::compression::Format::Bzip => niffler::compression::Level::Nine,
-                    niffler::send::compression::Format::Lzma => niffler::compression::Level::Six,
-                    niffler::send::compression::Format::Zstd => niffler::compression::Level::One,
-                    niffler::send::compression::Format::No => niffler::compression::Level::One,
-                },
-            )?;
-            Ok(niffed)
+    match (output, CHUNK_BYTES.get()) {
+        (Some(output), Some(&max_bytes)) => {
+            let mut writer = ChunkedWriter::new(output.clone(), max_bytes);
+            // without headers, each line is a record
+            writer.line_records = format == OutputFormat::Txt;
+            Ok(timed_writer(Box::new(writer)))
         }
+        (None, Some(_)) => Err(UsageError::new(
+            "--chunk-bytes requires an output file, since stdout can't be split",
+        )
+        .into()),
+        _ => auxiliary_output_to_writer(output),
+    }
+}
+
+/// Open an output other than the main one (e.g. a BED file) for writing. `None` means stdout.
+///
+/// These are never split by `--chunk-bytes`.
+pub fn auxiliary_output_to_writer(output: &Option<PathBuf>) -> anyhow::Result<Box<dyn Write>> {
+    if NULL_OUTPUT.load(Ordering::Relaxed) {
+        return Ok(timed_writer(Box::new(std::io::sink())));
+    }
+
+    match output {
+        Some(output) => Ok(timed_writer(output_file_writer(output)?)),
         None => {
             let stdout_bufwriter = BufWriter::new(stdout());
-            Ok(Box::new(stdout_bufwriter))
+            Ok(timed_writer(Box::new(stdout_bufwriter)))
+        }
+    }
+}
+
+/// Create an output file, compressing it according to its extension.
+fn output_file_writer(output: &Path) -> anyhow::Result<Box<dyn Write>> {
+    // match the suffix of outout to see if it should be compressed
+    let suffix = output.extension().unwrap_or_default().to_str().unwrap();
+
+    let compression_format = match suffix {
+        "gz" => niffler::send::compression::Format::Gzip,
+        "bz2" => niffler::send::compression::Format::Bzip,
+        "xz" => niffler::send::compression::Format::Lzma,
+        "zst" => niffler::send::compression::Format::Zstd,
+        _ => niffler::send::compression::Format::No,
+    };
+
+    create_parent_dir(output)?;
+    let outfile = File::create(output_path(output)).with_context(|| {
+        format!(
+            "Could not create output file {}. Are you sure it's not actually a directory?",
+            output.display()
+        )
+    })?;
+
+    let fp_bufwriter = BufWriter::new(outfile);
+    let niffed = niffler::send::get_writer(
+        Box::new(fp_bufwriter),
+        compression_format,
+        match compression_format {
+            niffler::send::compression::Format::Gzip => niffler::compression::Level::Six,
+            niffler::send::compression::Format::Bzip => niffler::compression::Level::Nine,
+            niffler::send::compression::Format::Lzma => niffler::compression::Level::Six,
+            niffler::send::compression::Format::Zstd => niffler::compression::Level::One,
+            niffler::send::compression::Format::No => niffler::compression::Level::One,
+        },
+    )?;
+    Ok(niffed)
+}
+
+/// The path of the `n`th file of a chunked output, e.g. `out.3.fasta.gz` for `out.fasta.gz`.
+///
+/// The number goes before the format extension and any compression extension, so the chunks are compressed like the output would have been.
+pub fn chunk_path(path: &Path, n: usize) -> PathBuf {
+    let name = path.file_name().unwrap_or_default().to_string_lossy();
+    let (name, compression) = match name.rsplit_once('.') {
+        Some((name, extension)) if matches!(extension, "gz" | "bz2" | "xz" | "zst") => {
+            (name, format!(".{}", extension))
+        }
+        _ => (name.as_ref(), String::new()),
+    };
+    let (stem, extension) = match name.rsplit_once('.') {
+        Some((stem, extension)) if !stem.is_empty() => (stem, format!(".{}", extension)),
+        _ => (name, String::new()),
+    };
+    path.with_file_name(format!("{}.{}{}{}", stem, n, extension, compression))
+}
+
+/// A writer that splits FASTA output into numbered files (see [`chunk_path`]) of at most `max_bytes` uncompressed bytes each.
+///
+/// A new file is only started between records, so each record is held back until the next one begins (a `>` at the start of a line) or the writer is flushed.
+/// A record longer than `max_bytes` gets a file of its own.
+pub struct ChunkedWriter {
+    path: PathBuf,
+    max_bytes: usize,
+    /// The number of files started so far
+    chunks: usize,
+    writer: Option<Box<dyn Write>>,
+    /// How many bytes have been written to the current file
+    written: usize,
+    /// The record being written, which hasn't been assigned to a file yet
+    record: Vec<u8>,
+    at_line_start: bool,
+    /// Whether every line is a record of its own, as in txt output, rather than only starting one at a `>`
+    pub line_records: bool,
+}
+
+impl ChunkedWriter {
+    pub fn new(path: PathBuf, max_bytes: usize) -> Self {
+        ChunkedWriter {
+            path,
+            max_bytes,
+            chunks: 0,
+            writer: None,
+            written: 0,
+            record: Vec::new(),
+            at_line_start: true,
+            line_records: false,
+        }
+    }
+
+    /// Finish the current file (if any) and start the next one.
+    fn start_chunk(&mut self) -> std::io::Result<()> {
+        if let Some(mut writer) = self.writer.take() {
+            writer.flush()?;
+        }
+        self.chunks += 1;
+        self.written = 0;
+        let path = chunk_path(&self.path, self.chunks);
+        self.writer = Some(output_file_writer(&path).map_err(std::io::Error::other)?);
+        Ok(())
+    }
+
+    /// Write the pending record, starting a new file first if it doesn't fit into the current one.
+    fn write_record(&mut self) -> std::io::Result<()> {
+        if self.record.is_empty() {
+            return Ok(());
+        }
+        if self.writer.is_none()
+            || (self.written > 0 && self.written + self.record.len() > self.max_bytes)
+        {
+            self.start_chunk()?;
+        }
+        self.writer.as_mut().unwrap().write_all(&self.record)?;
+        self.written += self.record.len();
+        self.record.clear();
+        Ok(())
+    }
+}
+
+impl Write for ChunkedWriter {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        for &byte in buf {
+            if self.at_line_start && (byte == b'>' || self.line_records) {
+                self.write_record()?;
+            }
+            self.record.push(byte);
+            self.at_line_start = byte == b'\n';
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.write_record()?;
+        // an empty output still gets a (first) file
+        if self.writer.is_none() {
+            self.start_chunk()?;
+        }
+        self.writer.as_mut().unwrap().flush()
+    }
+}
+
+impl Drop for ChunkedWriter {
+    fn drop(&mut self) {
+        let _ = self.flush();
+    }
+}
+
+/// Parse FASTA from a decompressed input.
+fn fasta_reader(reader: Box<dyn Read + Send>) -> Reader<Box<dyn Read + Send>> {
+    Reader::new(timed_reader(Box::new(StripCr(reader))))
+}
+
+/// Only pass on the records in `[skip, skip + limit)`, stopping at the end of the range without reading the rest of the input.
+///
+/// Records in the range without any unambiguous bases can also be dropped, see [`RecordRange::skip_all_n`].
+pub struct RecordRange<R: Read> {
+    reader: Reader<R>,
+    /// The index of the next record to be read from `reader`
+    index: usize,
+    skip: usize,
+    limit: Option<usize>,
+    skip_all_n: bool,
+    /// The FASTA of the current record and how much of it has been read
+    record: Vec<u8>,
+    pos: usize,
+}
+
+impl<R: Read> RecordRange<R> {
+    pub fn new(reader: Reader<R>, skip: usize, limit: Option<usize>) -> Self {
+        RecordRange {
+            reader,
+            index: 0,
+            skip,
+            limit,
+            skip_all_n: false,
+            record: Vec::new(),
+            pos: 0,
+        }
+    }
+
+    /// Whether to drop (with a warning) the records that [`is_all_n`], which still count towards the range.
+    pub fn skip_all_n(mut self, enabled: bool) -> Self {
+        self.skip_all_n = enabled;
+        self
+    }
+
+    /// Write the next record in the range as FASTA, returning false once the range or the input is exhausted.
+    fn next_record(&mut self) -> std::io::Result<bool> {
+        let end = self
+            .limit
+            .map_or(usize::MAX, |limit| self.skip.saturating_add(limit));
+        loop {
+            if self.index >= end {
+                return Ok(false);
+            }
+            let record = match self.reader.next() {
+                Some(record) => record
+                    .map_err(|err| std::io::Error::new(std::io::ErrorKind::InvalidData, err))?,
+                None => return Ok(false),
+            };
+            self.index += 1;
+            if self.index <= self.skip {
+                continue;
+            }
+            if self.skip_all_n && is_all_n(record.seq()) {
+                warn!(
+                    "Skipping {}: it has no unambiguous bases",
+                    record.id().unwrap_or_default()
+                );
+                continue;
+            }
+
+            self.record.clear();
+            self.pos = 0;
+            record.write(&mut self.record)?;
+            return Ok(true);
+        }
+    }
+}
+
+impl<R: Read> Read for RecordRange<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        if self.pos == self.record.len() && !self.next_record()? {
+            return Ok(0);
+        }
+        let n = buf.len().min(self.record.len() - self.pos);
+        buf[..n].copy_from_slice(&self.record[self.pos..self.pos + n]);
+        self.pos += n;
+        Ok(n)
+    }
+}
+
+/// A single line of `--input-format jsonl` input.
+#[derive(serde::Deserialize)]
+struct JsonRecord {
+    id: String,
+    seq: String,
+    #[serde(default)]
+    desc: Option<String>,
+}
+
+/// Convert newline-delimited JSON records into FASTA, one record at a time.
+///
+/// Blank lines are skipped. A line that isn't a valid record is reported as an [`std::io::ErrorKind::InvalidData`] error.
+pub struct JsonlToFasta<R> {
+    inner: R,
+    line: String,
+    line_number: usize,
+    /// The FASTA of the current record and how much of it has been read
+    record: Vec<u8>,
+    pos: usize,
+}
+
+impl<R: BufRead> JsonlToFasta<R> {
+    pub fn new(inner: R) -> Self {
+        JsonlToFasta {
+            inner,
+            line: String::new(),
+            line_number: 0,
+            record: Vec::new(),
+            pos: 0,
+        }
+    }
+
+    /// Convert the next record into FASTA, returning false at the end of the input.
+    fn next_record(&mut self) -> std::io::Result<bool> {
+        loop {
+            self.line.clear();
+            if self.inner.read_line(&mut self.line)? == 0 {
+                return Ok(false);
+            }
+            self.line_number += 1;
+            if !self.line.trim().is_empty() {
+                break;
+            }
+        }
+
+        let record: JsonRecord = serde_json::from_str(&self.line).map_err(|err| {
+            std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                format!("Invalid JSON record on line {}: {}", self.line_number, err),
+            )
+        })?;
+
+        self.record.clear();
+        self.pos = 0;
+        self.record.push(b'>');
+        self.record.extend_from_slice(record.id.as_bytes());
+        if let Some(desc) = record.desc {
+            self.record.push(b' ');
+            self.record.extend_from_slice(desc.as_bytes());
+        }
+        self.record.push(b'\n');
+        self.record.extend_from_slice(record.seq.as_bytes());
+        self.record.push(b'\n');
+        Ok(true)
+    }
+}
+
+impl<R: BufRead> Read for JsonlToFasta<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        if self.pos == self.record.len() && !self.next_record()? {
+            return Ok(0);
+        }
+        let n = buf.len().min(self.record.len() - self.pos);
+        buf[..n].copy_from_slice(&self.record[self.pos..self.pos + n]);
+        self.pos += n;
+        Ok(n)
+    }
+}
+
+/// Drop every `\r` from the input, so that Windows line endings don't end up in sequences or headers.
+pub struct StripCr<R>(pub R);
+
+impl<R: Read> Read for StripCr<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        loop {
+            let n = self.0.read(buf)?;
+            if n == 0 || !buf[..n].contains(&b'\r') {
+                return Ok(n);
+            }
+
+            let mut kept = 0;
+            for i in 0..n {
+                if buf[i] != b'\r' {
+                    buf[kept] = buf[i];
+                    kept += 1;
+                }
+            }
+            // a buffer of nothing but `\r` would otherwise look like the end of the input
+            if kept > 0 {
+                return Ok(kept);
+            }
+        }
+    }
+}
+
+/// Attribute time spent reading to the read stage when `--profile` is set.
+fn timed_reader(reader: Box<dyn Read + Send>) -> Box<dyn Read + Send> {
+    match profile::is_enabled() {
+        true => Box::new(TimedReader(reader)),
+        false => reader,
+    }
+}
+
+/// Attribute time spent writing to the write stage when `--profile` is set.
+fn timed_writer(writer: Box<dyn Write>) -> Box<dyn Write> {
+    match profile::is_enabled() {
+        true => Box::new(TimedWriter(writer)),
+        false => writer,
+    }
+}
+
+/// Create the directory an output file will be written to, if it doesn't exist yet.
+fn create_parent_dir(path: &Path) -> anyhow::Result<()> {
+    match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() && !parent.exists() => {
+            info!("Creating output directory {}", parent.display());
+            std::fs::create_dir_all(parent)
+                .with_context(|| format!("Could not create output directory {}", parent.display()))
         }
+        _ => Ok(()),
     }
 }
 
 pub fn table_path_to_writer(table: &Option<PathBuf>) -> Option<csv::Writer<File>> {
     table.as_ref().map(|path| {
+        create_parent_dir(path).expect("Could not create output table.");
         csv::WriterBuilder::new()
-            .delimiter(match path.extension().and_then(|x| x.to_str()) {
-                Some("tsv") => b'\t',
-                _ => b',',
-            })
-            .from_path(path)
+            .delimiter(table_delimiter(path))
+            .from_path(output_path(path))
             .expect("Could not create output table.")
     })
 }
+
+/// The delimiter of a table written to `path`: a tab if it ends in `.tsv`, and a comma otherwise.
+fn table_delimiter(path: &Path) -> u8 {
+    match path.extension().and_then(|x| x.to_str()) {
+        Some("tsv") => b'\t',
+        _ => b',',
+    }
+}
+
+/// Open the main output of a command that writes a table instead of sequences, e.g. `validate`. `None` means stdout.
+///
+/// As with [`table_path_to_writer`], the table is a TSV if the path ends in `.tsv` and a CSV otherwise, which includes stdout.
+pub fn table_output_to_writer(
+    output: &Option<PathBuf>,
+) -> anyhow::Result<csv::Writer<Box<dyn Write>>> {
+    Ok(csv::WriterBuilder::new()
+        .delimiter(output.as_deref().map_or(b',', table_delimiter))
+        .from_writer(output_to_writer(output)?))
+}
@@ -0,0 +1,116 @@
+use anyhow::bail;
+use log::warn;
+use seq_io::{fasta::Record, parallel::parallel_fasta};
+
+use crate::{
+    canonicalize::is_valid,
+    commands::Command,
+    profile::{self, Stage},
+    utils::{count_record, input_to_reader, table_output_to_writer, thread_count},
+};
+
+/// The outcome of each check for a single record.
+#[derive(Default)]
+struct Checks {
+    length: usize,
+    valid_bases: bool,
+    canonical_idempotent: bool,
+    monomer: bool,
+    length_ok: bool,
+}
+
+impl Checks {
+    fn pass(&self) -> bool {
+        self.valid_bases && self.canonical_idempotent && self.monomer && self.length_ok
+    }
+}
+
+#[derive(serde::Serialize)]
+struct Row<'a> {
+    id: &'a str,
+    length: usize,
+    valid_bases: bool,
+    canonical_idempotent: bool,
+    monomer: bool,
+    length_ok: bool,
+    pass: bool,
+}
+
+pub fn validate(cmd: &Command) -> anyhow::Result<()> {
+    match cmd {
+        Command::Validate {
+            input,
+            output,
+            seed_length,
+            min_length,
+            max_length,
+            strict,
+            parallel,
+        } => {
+            let reader = input_to_reader(input)?;
+            let mut writer = table_output_to_writer(output)?;
+
+            let monomerizer = circkit::Monomerizer {
+                seed_len: *seed_length as usize,
+                ..Default::default()
+            };
+            let (mut records, mut failed) = (0, 0);
+
+            parallel_fasta(
+                reader,
+                thread_count(parallel.threads),
+                parallel.batch_size,
+                |record, checks: &mut Checks| {
+                    // runs in worker
+                    let _timer = profile::Timer::start(Stage::Compute);
+                    let normalized = match needletail::sequence::normalize(record.seq(), false) {
+                        Some(x) => x,
+                        None => record.seq().to_vec(),
+                    };
+                    let canonical = circkit::canonicalize(&normalized);
+
+                    *checks = Checks {
+                        length: normalized.len(),
+                        valid_bases: is_valid(record.seq()),
+                        canonical_idempotent: circkit::canonicalize(&canonical) == canonical,
+                        // a sequence shorter than the seed can't be checked for an overlap
+                        monomer: normalized.len() < monomerizer.seed_len
+                            || !monomerizer.is_multimer(&normalized),
+                        length_ok: normalized.len() >= *min_length
+                            && max_length.is_none_or(|max| normalized.len() <= max),
+                    };
+                },
+                |record, checks| {
+                    // runs in main thread
+                    records += 1;
+                    if !checks.pass() {
+                        failed += 1;
+                    }
+                    writer
+                        .serialize(Row {
+                            id: record.id().unwrap(),
+                            length: checks.length,
+                            valid_bases: checks.valid_bases,
+                            canonical_idempotent: checks.canonical_idempotent,
+                            monomer: checks.monomer,
+                            length_ok: checks.length_ok,
+                            pass: checks.pass(),
+                        })
+                        .expect("failed to write to table");
+                    count_record();
+                    None::<()>
+                },
+            )?;
+            writer.flush()?;
+
+            if failed > 0 {
+                match strict {
+                    true => bail!("{} of {} records failed validation", failed, records),
+                    false => warn!("{} of {} records failed validation", failed, records),
+                }
+            }
+        }
+        _ => panic!("input command is not for validate"),
+    }
+    Ok(())
+}
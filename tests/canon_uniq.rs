@@ -87,3 +87,564 @@ fn fasta_files(
 
     Ok(())
 }
+
+#[rstest]
+fn keep_original_writes_both_records(
+    #[values("1", "2", "4")] threads: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = Command::cargo_bin("circkit")?;
+
+    let file = assert_fs::NamedTempFile::new("simple.fasta")?;
+    file.write_str(concat!(
+        ">seq1\n",
+        "ATGCA\n",
+        ">seq2 a description\n",
+        "tgca\n"
+    ))?;
+
+    cmd.arg("canonicalize")
+        .arg(file.path())
+        .arg("--keep-original")
+        .arg("--threads")
+        .arg(threads);
+    cmd.assert().success().stdout(concat!(
+        ">seq1 canonical\n",
+        "AATGC\n",
+        ">seq1 original\n",
+        "ATGCA\n",
+        ">seq2 a description canonical\n",
+        "ATGC\n",
+        ">seq2 a description original\n",
+        "tgca\n",
+    ));
+    Ok(())
+}
+
+#[test]
+fn append_length_ignores_line_breaks() -> Result<(), Box<dyn std::error::Error>> {
+    // without --canonicalize, uniq writes the sequence as it came in, line breaks included
+    let mut cmd = Command::cargo_bin("circkit")?;
+    cmd.arg("--append-length")
+        .arg("uniq")
+        .arg("tests/examples/multiple_sequences_split_lines/in.fasta");
+    cmd.assert()
+        .success()
+        .stdout(">seq1 length=5\nTT\nATG\n>seq2 length=6\nAAT\nGGA\n");
+    Ok(())
+}
+
+#[rstest]
+fn purine_start_is_consistent(
+    #[values("lexmin", "purine-start")] convention: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    // the lexicographically minimal form is on this strand, but the reverse complement has more purines
+    let circle = "AAACCCCCTC";
+    let revcomp: String = circle
+        .chars()
+        .rev()
+        .map(|c| match c {
+            'A' => 'T',
+            'C' => 'G',
+            'G' => 'C',
+            _ => 'A',
+        })
+        .collect();
+
+    let mut outputs = std::collections::HashSet::new();
+    for strand in [circle, revcomp.as_str()] {
+        for i in 0..strand.len() {
+            let file = assert_fs::NamedTempFile::new("in.fasta")?;
+            file.write_str(&format!(">seq\n{}{}\n", &strand[i..], &strand[..i]))?;
+
+            let output = Command::cargo_bin("circkit")?
+                .arg("uniq")
+                .arg("--canonicalize")
+                .arg("--strand-convention")
+                .arg(convention)
+                .arg(file.path())
+                .output()?;
+            assert!(output.status.success());
+            outputs.insert(String::from_utf8(output.stdout)?);
+        }
+    }
+
+    assert_eq!(outputs.len(), 1);
+    let expected = match convention {
+        "lexmin" => ">seq\nAAACCCCCTC\n",
+        _ => ">seq\nAGGGGGTTTG\n",
+    };
+    assert_eq!(outputs.into_iter().next().unwrap(), expected);
+    Ok(())
+}
+
+#[rstest]
+#[case("rna", ">seq1\nAAUGC\n")]
+#[case("dna", ">seq1\nAATGC\n")]
+fn output_alphabet(
+    #[case] alphabet: &str,
+    #[case] expected: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let file = assert_fs::NamedTempFile::new("simple.fasta")?;
+    file.write_str(">seq1\nATGCA\n")?;
+
+    let mut cmd = Command::cargo_bin("circkit")?;
+    cmd.arg("--output-alphabet")
+        .arg(alphabet)
+        .arg("canonicalize")
+        .arg(file.path());
+    cmd.assert().success().stdout(expected.to_string());
+    Ok(())
+}
+
+#[test]
+fn output_alphabet_keeps_case() -> Result<(), Box<dyn std::error::Error>> {
+    let file = assert_fs::NamedTempFile::new("mixed.fasta")?;
+    file.write_str(">seq1\nAuGc\n")?;
+
+    let mut cmd = Command::cargo_bin("circkit")?;
+    cmd.arg("--output-alphabet")
+        .arg("dna")
+        .arg("uniq")
+        .arg(file.path());
+    cmd.assert().success().stdout(">seq1\nAtGc\n");
+    Ok(())
+}
+
+#[test]
+fn passthrough_invalid() -> Result<(), Box<dyn std::error::Error>> {
+    let file = assert_fs::NamedTempFile::new("invalid.fasta")?;
+    file.write_str(">valid\nTTGCA\n>invalid with description\nACG*XT\nTA\n")?;
+
+    // normalization replaces the invalid bytes before canonicalizing
+    let mut cmd = Command::cargo_bin("circkit")?;
+    cmd.arg("canonicalize").arg(file.path());
+    cmd.assert()
+        .success()
+        .stdout(">valid\nAATGC\n>invalid with description\nAACGNNTT\n");
+
+    let mut cmd = Command::cargo_bin("circkit")?;
+    cmd.arg("canonicalize")
+        .arg(file.path())
+        .arg("--passthrough-invalid")
+        .arg("-v");
+    cmd.assert()
+        .success()
+        .stdout(">valid\nAATGC\n>invalid with description\nACG*XTTA\n")
+        .stderr(predicate::str::contains("invalid contains characters"));
+    Ok(())
+}
+
+/// Rotating the reported strand by the reported offset should give back the canonical sequence.
+#[rstest]
+#[case("TTGCA", ">seq1 rot=3 strand=-\nAATGC\n")]
+#[case("GCAAT", ">seq1 rot=2 strand=+\nAATGC\n")]
+#[case("AATGC", ">seq1 rot=0 strand=+\nAATGC\n")]
+fn annotate_offset(
+    #[case] seq: &str,
+    #[case] expected: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let file = assert_fs::NamedTempFile::new("in.fasta")?;
+    file.write_str(&format!(">seq1\n{}\n", seq))?;
+
+    let mut cmd = Command::cargo_bin("circkit")?;
+    cmd.arg("canonicalize")
+        .arg(file.path())
+        .arg("--annotate-offset");
+    let output = cmd.output()?;
+    assert!(output.status.success());
+    assert_eq!(String::from_utf8(output.stdout.clone())?, expected);
+
+    // check that the annotation really describes the rotation
+    let stdout = String::from_utf8(output.stdout)?;
+    let mut lines = stdout.lines();
+    let head = lines.next().unwrap();
+    let canonical = lines.next().unwrap();
+    let offset: usize = head
+        .split("rot=")
+        .nth(1)
+        .unwrap()
+        .split(' ')
+        .next()
+        .unwrap()
+        .parse()?;
+    let strand = match head.ends_with("strand=-") {
+        true => bio::alphabets::dna::revcomp(seq.as_bytes()),
+        false => seq.as_bytes().to_vec(),
+    };
+    assert_eq!(
+        [&strand[offset..], &strand[..offset]].concat(),
+        canonical.as_bytes()
+    );
+    Ok(())
+}
+
+/// Only sequences that come out of canonicalization unchanged are reported as already canonical.
+#[rstest]
+#[case("AATGC", ">seq1 already_canonical=true\nAATGC\n")]
+#[case("GCAAT", ">seq1 already_canonical=false\nAATGC\n")]
+#[case("TTGCA", ">seq1 already_canonical=false\nAATGC\n")]
+fn report_canonical_equal_original(
+    #[case] seq: &str,
+    #[case] expected: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let file = assert_fs::NamedTempFile::new("in.fasta")?;
+    file.write_str(&format!(">seq1\n{}\n", seq))?;
+
+    let mut cmd = Command::cargo_bin("circkit")?;
+    cmd.arg("canonicalize")
+        .arg(file.path())
+        .arg("--report-canonical-equal-original");
+    cmd.assert().success().stdout(expected.to_string());
+    Ok(())
+}
+
+/// Linear canonicalization only picks a strand, while circular canonicalization also rotates.
+#[rstest]
+#[case("GCAAT", ">seq1\nATTGC\n", ">seq1\nAATGC\n")]
+#[case("TTGCA", ">seq1\nTGCAA\n", ">seq1\nAATGC\n")]
+#[case("AATGC", ">seq1\nAATGC\n", ">seq1\nAATGC\n")]
+fn linear_vs_circular(
+    #[case] seq: &str,
+    #[case] linear: &str,
+    #[case] circular: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let file = assert_fs::NamedTempFile::new("in.fasta")?;
+    file.write_str(&format!(">seq1\n{}\n", seq))?;
+
+    Command::cargo_bin("circkit")?
+        .arg("canonicalize")
+        .arg(file.path())
+        .arg("--linear")
+        .assert()
+        .success()
+        .stdout(linear.to_string());
+    Command::cargo_bin("circkit")?
+        .arg("canonicalize")
+        .arg(file.path())
+        .assert()
+        .success()
+        .stdout(circular.to_string());
+    Ok(())
+}
+
+/// An RNA sequence and its DNA transcription canonicalize to the same form, up to T and U.
+#[rstest]
+#[case("UUGCA", "AAUGC")]
+#[case("GGUAC", "ACCGU")]
+#[case("CUCAAUG", "AAUGCUC")]
+fn rna_alphabet(
+    #[case] rna: &str,
+    #[case] canonical: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    for (seq, alphabet, expected) in [
+        (rna.to_string(), "rna", canonical.to_string()),
+        (rna.replace('U', "T"), "rna", canonical.to_string()),
+        (rna.replace('U', "T"), "dna", canonical.replace('U', "T")),
+    ] {
+        let file = assert_fs::NamedTempFile::new("in.fasta")?;
+        file.write_str(&format!(">seq1\n{}\n", seq))?;
+        Command::cargo_bin("circkit")?
+            .arg("canonicalize")
+            .arg(file.path())
+            .arg("--alphabet")
+            .arg(alphabet)
+            .assert()
+            .success()
+            .stdout(format!(">seq1\n{}\n", expected));
+    }
+    Ok(())
+}
+
+/// A circular RNA and its reverse complement are duplicates, but only --rna keeps the canonical form in RNA.
+#[rstest]
+#[case(&[], ">seq1\nAAUGC\n")]
+#[case(&["--canonicalize"], ">seq1\nAATGC\n")]
+#[case(&["--canonicalize", "--rna"], ">seq1\nAAUGC\n")]
+fn uniq_rna(
+    #[case] args: &[&str],
+    #[case] expected: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let file = assert_fs::NamedTempFile::new("in.fasta")?;
+    file.write_str(">seq1\nAAUGC\n>seq2\nUUGCA\n")?;
+    Command::cargo_bin("circkit")?
+        .arg("uniq")
+        .arg(file.path())
+        .args(args)
+        .assert()
+        .success()
+        .stdout(expected.to_string());
+    Ok(())
+}
+
+#[test]
+fn rna_flag_matches_alphabet() -> Result<(), Box<dyn std::error::Error>> {
+    let file = assert_fs::NamedTempFile::new("in.fasta")?;
+    file.write_str(">seq1\nUUGCA\n")?;
+    Command::cargo_bin("circkit")?
+        .arg("canonicalize")
+        .arg(file.path())
+        .arg("--rna")
+        .assert()
+        .success()
+        .stdout(">seq1\nAAUGC\n");
+    Ok(())
+}
+
+/// Both strands are written, and the smaller of the two is what canonicalize writes by default.
+#[rstest]
+fn both_strands(#[values("1", "4")] threads: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let input = "tests/examples/multiple_sequences/in.fasta";
+    let both = Command::cargo_bin("circkit")?
+        .arg("canonicalize")
+        .arg(input)
+        .arg("--both-strands")
+        .arg("--threads")
+        .arg(threads)
+        .output()?;
+    assert!(both.status.success());
+    let default = Command::cargo_bin("circkit")?
+        .arg("canonicalize")
+        .arg(input)
+        .arg("--threads")
+        .arg(threads)
+        .output()?;
+    assert!(default.status.success());
+
+    let both = String::from_utf8(both.stdout)?;
+    let default = String::from_utf8(default.stdout)?;
+    let both_lines = both.lines().collect::<Vec<_>>();
+    let default_lines = default.lines().collect::<Vec<_>>();
+    assert_eq!(both_lines.len(), 2 * default_lines.len());
+
+    for (pair, record) in both_lines.chunks(4).zip(default_lines.chunks(2)) {
+        assert_eq!(pair[0], format!("{} +", record[0]));
+        assert_eq!(pair[2], format!("{} -", record[0]));
+        assert_eq!(pair[1].min(pair[3]), record[1]);
+    }
+    Ok(())
+}
+
+/// Only sequences within the length range are output, and line breaks don't count towards the length.
+#[rstest]
+#[case("canonicalize", &[], ">short\nAC\n>medium\nAAACC\n>long\nAAAAACC\n")]
+#[case("canonicalize", &["--min-length", "5"], ">medium\nAAACC\n>long\nAAAAACC\n")]
+#[case("canonicalize", &["--min-length", "5", "--max-length", "6"], ">medium\nAAACC\n")]
+#[case("uniq", &["--min-length", "5", "--max-length", "6"], ">medium\nCCAAA\n")]
+#[case("uniq", &["--max-length", "6"], ">short\nAC\n>medium\nCCAAA\n")]
+fn length_range(
+    #[case] command: &str,
+    #[case] args: &[&str],
+    #[case] expected: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let file = assert_fs::NamedTempFile::new("in.fasta")?;
+    file.write_str(">short\nAC\n>medium\nCCAAA\n>long\nAAAAA\nCC\n")?;
+
+    Command::cargo_bin("circkit")?
+        .arg(command)
+        .arg(file.path())
+        .args(args)
+        .assert()
+        .success()
+        .stdout(expected.to_string());
+    Ok(())
+}
+
+#[test]
+fn length_range_must_not_be_empty() -> Result<(), Box<dyn std::error::Error>> {
+    Command::cargo_bin("circkit")?
+        .arg("canonicalize")
+        .arg("tests/examples/simple/in.fasta")
+        .arg("--min-length")
+        .arg("10")
+        .arg("--max-length")
+        .arg("5")
+        .assert()
+        .code(2);
+    Ok(())
+}
+
+/// The BED feature marks the base that was at position 0 of the input.
+#[rstest]
+#[case("GCAAT", "seq1\t3\t4\torigin\t0\t+\n")]
+#[case("AATGC", "seq1\t0\t1\torigin\t0\t+\n")]
+#[case("TTGCA", "seq1\t1\t2\torigin\t0\t-\n")]
+fn offset_bed(#[case] seq: &str, #[case] expected: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let file = assert_fs::NamedTempFile::new("in.fasta")?;
+    file.write_str(&format!(">seq1\n{}\n", seq))?;
+    let bed = assert_fs::NamedTempFile::new("origin.bed")?;
+
+    let output = Command::cargo_bin("circkit")?
+        .arg("canonicalize")
+        .arg(file.path())
+        .arg("--annotate-offset")
+        .arg("--offset-bed")
+        .arg(bed.path())
+        .output()?;
+    assert!(output.status.success());
+    let line = std::fs::read_to_string(bed.path())?;
+    assert_eq!(line, expected);
+
+    let stdout = String::from_utf8(output.stdout)?;
+    let mut lines = stdout.lines();
+    let offset: usize = lines
+        .next()
+        .unwrap()
+        .split("rot=")
+        .nth(1)
+        .unwrap()
+        .split(' ')
+        .next()
+        .unwrap()
+        .parse()?;
+    let canonical = lines.next().unwrap().as_bytes();
+    let position: usize = line.split('\t').nth(1).unwrap().parse()?;
+
+    // on the forward strand, the origin lands at len - offset
+    match line.trim_end().ends_with('+') {
+        true => {
+            assert_eq!(position, (seq.len() - offset) % seq.len());
+            assert_eq!(canonical[position], seq.as_bytes()[0]);
+        }
+        false => assert_eq!(
+            canonical[position],
+            bio::alphabets::dna::complement(seq.as_bytes()[0])
+        ),
+    }
+    Ok(())
+}
+
+#[test]
+fn uniq_table_strand_counts() -> Result<(), Box<dyn std::error::Error>> {
+    let file = assert_fs::NamedTempFile::new("in.fasta")?;
+    // seq2 is a rotation of seq1 and seq3 is a rotation of its reverse complement
+    file.write_str(">seq1\nAACGTC\n>seq2\nCGTCAA\n>seq3\nCGTTGA\n>other\nGGGG\n")?;
+    let table = assert_fs::NamedTempFile::new("table.csv")?;
+
+    Command::cargo_bin("circkit")?
+        .arg("uniq")
+        .arg(file.path())
+        .arg("--table")
+        .arg(table.path())
+        .assert()
+        .success()
+        .stdout(">seq1\nAACGTC\n>other\nGGGG\n");
+    table.assert("id,duplicate_id,forward_count,reverse_count\nseq1,seq2,2,1\nseq1,seq3,2,1\n");
+    Ok(())
+}
+
+#[test]
+fn uniq_table_all_includes_singletons() -> Result<(), Box<dyn std::error::Error>> {
+    let file = assert_fs::NamedTempFile::new("in.fasta")?;
+    file.write_str(">seq1\nAACGTC\n>seq2\nCGTCAA\n>other\nGGGG\n>seq3\nCGTTGA\n")?;
+    let table = assert_fs::NamedTempFile::new("all.tsv")?;
+
+    Command::cargo_bin("circkit")?
+        .arg("uniq")
+        .arg(file.path())
+        .arg("--table-all")
+        .arg(table.path())
+        .assert()
+        .success()
+        .stdout(">seq1\nAACGTC\n>other\nGGGG\n");
+    table.assert(
+        "id\tsize\tforward_count\treverse_count\tmembers\n\
+         seq1\t3\t2\t1\tseq1,seq2,seq3\n\
+         other\t1\t1\t0\tother\n",
+    );
+    Ok(())
+}
+
+/// Empty records, e.g. from truncated downloads, are written through instead of stopping the run.
+#[rstest]
+#[case("canonicalize", ">empty\n\n>seq1\nAACGTC\n>empty2\n\n")]
+#[case("uniq", ">empty\n\n>seq1\nCGTCAA\n")]
+fn empty_records_pass_through(
+    #[case] subcommand: &str,
+    #[case] expected: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let file = assert_fs::NamedTempFile::new("in.fasta")?;
+    file.write_str(">empty\n\n>seq1\nCGTCAA\n>empty2\n\n")?;
+
+    Command::cargo_bin("circkit")?
+        .arg(subcommand)
+        .arg(file.path())
+        .assert()
+        .success()
+        .stdout(expected.to_string());
+    Ok(())
+}
+
+/// Soft-masked bases stay lowercase on whichever strand is chosen, including across line breaks.
+#[rstest]
+#[case(&[], ">a\nAATGC\n>b\nAACTTGG\n")]
+#[case(&["--preserve-case"], ">a\naatGC\n>b\nAACttGG\n")]
+#[case(&["--preserve-case", "--linear"], ">a\ntGCaa\n>b\nAACttGG\n")]
+fn preserve_case(
+    #[case] args: &[&str],
+    #[case] expected: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let file = assert_fs::NamedTempFile::new("in.fasta")?;
+    file.write_str(">a\nttGCa\n>b\nCCaaG\nTT\n")?;
+
+    Command::cargo_bin("circkit")?
+        .arg("canonicalize")
+        .arg(file.path())
+        .args(args)
+        .assert()
+        .success()
+        .stdout(expected.to_string());
+    Ok(())
+}
+
+#[rstest]
+#[case(&[])]
+#[case(&["--rna"])]
+#[case(&["--min-length", "5"])]
+fn canonicalize_unique_matches_uniq(
+    #[case] args: &[&str],
+) -> Result<(), Box<dyn std::error::Error>> {
+    let file = assert_fs::NamedTempFile::new("in.fasta")?;
+    file.write_str(">seq1\nAAUGC\n>seq2\nTTGCA\n>seq3\nGCAAT\n>seq4\nCCG\n>seq5\nGGCATTA\n")?;
+
+    let unique = Command::cargo_bin("circkit")?
+        .arg("canonicalize")
+        .arg(file.path())
+        .arg("--unique")
+        .args(args)
+        .output()?;
+    let uniq = Command::cargo_bin("circkit")?
+        .arg("uniq")
+        .arg(file.path())
+        .arg("--canonicalize")
+        .args(args)
+        .output()?;
+    assert!(unique.status.success());
+    assert_eq!(
+        String::from_utf8(unique.stdout)?,
+        String::from_utf8(uniq.stdout)?
+    );
+    Ok(())
+}
+
+#[rstest]
+#[case(&[], ">seq1 first\nATGC\n>seq2\nAAAA\n>seq1 second\nCCCC\n")]
+#[case(&["--dedup-by", "sequence"], ">seq1 first\nATGC\n>seq2\nAAAA\n>seq1 second\nCCCC\n")]
+#[case(&["--dedup-by", "header"], ">seq1 first\nATGC\n>seq2\nAAAA\n>seq3\nGCAT\n")]
+#[case(&["--dedup-by", "header", "--canonicalize"], ">seq1 first\nATGC\n>seq2\nAAAA\n>seq3\nATGC\n")]
+fn dedup_by(
+    #[case] args: &[&str],
+    #[case] expected: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let file = assert_fs::NamedTempFile::new("in.fasta")?;
+    // seq1 is repeated with a different sequence, and seq3 is a rotation of seq1
+    file.write_str(">seq1 first\nATGC\n>seq2\nAAAA\n>seq1 second\nCCCC\n>seq3\nGCAT\n")?;
+
+    Command::cargo_bin("circkit")?
+        .arg("uniq")
+        .arg(file.path())
+        .args(args)
+        .assert()
+        .success()
+        .stdout(expected.to_string());
+    Ok(())
+}
@@ -17,3 +17,111 @@ fn decat() -> anyhow::Result<()> {
     common::check_fasta("decat", &mut cmd)?;
     Ok(())
 }
+
+#[test]
+fn spacer_round_trip() -> anyhow::Result<()> {
+    let cated = assert_cmd::Command::cargo_bin("circkit")?
+        .arg("cat")
+        .arg("--spacer")
+        .arg("N")
+        .arg("--spacer-len")
+        .arg("3")
+        .arg("tests/examples/multiple_sequences/in.fasta")
+        .output()?;
+    assert!(cated.status.success());
+    assert!(String::from_utf8(cated.stdout.clone())?.contains("NNN"));
+
+    let output = assert_fs::NamedTempFile::new("out.fasta")?;
+    assert_cmd::Command::cargo_bin("circkit")?
+        .arg("decat")
+        .arg("--spacer")
+        .arg("N")
+        .arg("--spacer-len")
+        .arg("3")
+        .arg("-")
+        .arg("-o")
+        .arg(output.path())
+        .write_stdin(cated.stdout)
+        .assert()
+        .success();
+
+    assert!(common::sequences_are_identical(
+        output.path().to_str().unwrap(),
+        "tests/examples/multiple_sequences/in.fasta"
+    ));
+    Ok(())
+}
+
+#[test]
+fn spacer_must_match() -> anyhow::Result<()> {
+    let cated = Command::cargo_bin("circkit")?
+        .arg("cat")
+        .arg("tests/examples/multiple_sequences/in.fasta")
+        .output()?;
+
+    assert_cmd::Command::cargo_bin("circkit")?
+        .arg("decat")
+        .arg("--spacer")
+        .arg("N")
+        .arg("-")
+        .write_stdin(cated.stdout)
+        .assert()
+        .failure();
+    Ok(())
+}
+
+#[test]
+fn spacer_len_requires_spacer() -> anyhow::Result<()> {
+    Command::cargo_bin("circkit")?
+        .arg("cat")
+        .arg("--spacer-len")
+        .arg("3")
+        .arg("tests/examples/multiple_sequences/in.fasta")
+        .assert()
+        .code(2);
+    Ok(())
+}
+
+#[test]
+fn odd_length_warns() -> anyhow::Result<()> {
+    use predicates::prelude::*;
+
+    assert_cmd::Command::cargo_bin("circkit")?
+        .arg("-v")
+        .arg("decat")
+        .arg("-")
+        .write_stdin(">odd\nACGTACGTA\n")
+        .assert()
+        .success()
+        .stdout(">odd\nACGT\n")
+        .stderr(predicate::str::contains("odd has an odd length"));
+
+    // the spacer doesn't count towards the length
+    assert_cmd::Command::cargo_bin("circkit")?
+        .arg("-v")
+        .arg("decat")
+        .arg("--spacer")
+        .arg("N")
+        .arg("-")
+        .write_stdin(">even\nACGTNACGT\n")
+        .assert()
+        .success()
+        .stdout(">even\nACGT\n")
+        .stderr(predicate::str::contains("odd length").not());
+    Ok(())
+}
+
+#[test]
+fn odd_length_fails_when_strict() -> anyhow::Result<()> {
+    use predicates::prelude::*;
+
+    assert_cmd::Command::cargo_bin("circkit")?
+        .arg("decat")
+        .arg("--strict")
+        .arg("-")
+        .write_stdin(">odd\nACGTACGTA\n")
+        .assert()
+        .code(1)
+        .stderr(predicate::str::contains("odd has an odd length"));
+    Ok(())
+}
@@ -1,3 +1,4 @@
+#![allow(dead_code)] // each test binary only uses some of these helpers
 use assert_cmd::prelude::*;
 use bio::io::fasta;
 use predicates::prelude::*;
@@ -0,0 +1,39 @@
+use assert_cmd::Command; // Run programs
+use rstest::rstest; // Parameterized tests
+
+/// Every match in a tandem repeat lies on a diagonal offset from the main one by a multiple of the period.
+#[rstest]
+fn tandem_repeat_diagonals(#[values("1", "4")] threads: &str) -> anyhow::Result<()> {
+    let monomer = "ACGTTGCAAGGCTTACG";
+    let seq = monomer.repeat(3);
+
+    let output = Command::cargo_bin("circkit")?
+        .arg("dotplot")
+        .arg("-")
+        .arg("--kmer-length")
+        .arg("8")
+        .arg("--threads")
+        .arg(threads)
+        .write_stdin(format!(">repeat\n{}\n>other\nACGTACGA\n", seq))
+        .output()?;
+    assert!(output.status.success());
+
+    let stdout = String::from_utf8(output.stdout)?;
+    let mut lines = stdout.lines();
+    assert_eq!(lines.next(), Some("id,i,j"));
+
+    let mut offsets = Vec::new();
+    for line in lines {
+        let fields = line.split(',').collect::<Vec<_>>();
+        // the other sequence is as long as a k-mer, so it can't match itself anywhere else
+        assert_eq!(fields[0], "repeat");
+        let (i, j) = (fields[1].parse::<usize>()?, fields[2].parse::<usize>()?);
+        offsets.push((j + seq.len() - i) % seq.len());
+    }
+    // each position matches its counterpart in the other two copies
+    assert_eq!(offsets.len(), 2 * seq.len());
+    assert!(offsets
+        .iter()
+        .all(|&offset| offset == monomer.len() || offset == 2 * monomer.len()));
+    Ok(())
+}
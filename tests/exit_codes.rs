@@ -0,0 +1,56 @@
+use assert_cmd::Command; // Run programs
+use predicates::prelude::*; // Used for writing assertions
+
+#[test]
+fn missing_input_file_is_io_error() -> Result<(), Box<dyn std::error::Error>> {
+    Command::cargo_bin("circkit")?
+        .arg("cat")
+        .arg("tests/examples/does_not_exist.fasta")
+        .assert()
+        .code(3);
+    Ok(())
+}
+
+#[test]
+fn unknown_argument_is_usage_error() -> Result<(), Box<dyn std::error::Error>> {
+    Command::cargo_bin("circkit")?
+        .arg("cat")
+        .arg("--not-a-real-flag")
+        .assert()
+        .code(2);
+    Ok(())
+}
+
+#[test]
+fn invalid_argument_value_is_usage_error() -> Result<(), Box<dyn std::error::Error>> {
+    Command::cargo_bin("circkit")?
+        .arg("rotate")
+        .arg("tests/examples/simple/in.fasta")
+        .arg("--bases")
+        .arg("0")
+        .assert()
+        .code(2)
+        .stderr(predicate::str::contains("Rotation by 0 is not allowed"));
+    Ok(())
+}
+
+#[test]
+fn fail_on_empty() -> Result<(), Box<dyn std::error::Error>> {
+    // nothing survives the length filter
+    Command::cargo_bin("circkit")?
+        .arg("monomerize")
+        .arg("tests/examples/simple/in.fasta")
+        .arg("--min-length")
+        .arg("1000000")
+        .arg("--fail-on-empty")
+        .assert()
+        .code(4);
+
+    Command::cargo_bin("circkit")?
+        .arg("cat")
+        .arg("tests/examples/simple/in.fasta")
+        .arg("--fail-on-empty")
+        .assert()
+        .success();
+    Ok(())
+}
@@ -0,0 +1,103 @@
+use assert_cmd::prelude::*; // Add methods on commands
+use bio::io::fasta;
+use rstest::rstest; // Parameterized tests
+use std::process::Command; // Run programs
+
+fn generate(args: &[&str]) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+    let output = Command::cargo_bin("circkit")?
+        .arg("gen-test-data")
+        .args(args)
+        .output()?;
+    assert!(output.status.success());
+    Ok(output.stdout)
+}
+
+#[rstest]
+fn deterministic(
+    #[values("multimer", "rotations", "orfs")] kind: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let first = generate(&["--kind", kind, "--seed", "7", "--mutation-rate", "0.1"])?;
+    assert!(!first.is_empty());
+    assert_eq!(
+        first,
+        generate(&["--kind", kind, "--seed", "7", "--mutation-rate", "0.1"])?
+    );
+    assert_ne!(
+        first,
+        generate(&["--kind", kind, "--seed", "8", "--mutation-rate", "0.1"])?
+    );
+    Ok(())
+}
+
+#[rstest]
+fn multimers_monomerize(
+    #[values("1.5", "2", "3.25")] copies: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let multimers = assert_fs::NamedTempFile::new("multimers.fasta")?;
+    std::fs::write(
+        multimers.path(),
+        generate(&["--seed", "1", "-n", "50", "--copies", copies])?,
+    )?;
+    let monomers = Command::cargo_bin("circkit")?
+        .arg("monomerize")
+        .arg(multimers.path())
+        .output()?;
+    assert!(monomers.status.success());
+
+    let multimers = fasta::Reader::from_file(multimers.path())?
+        .records()
+        .collect::<Result<Vec<_>, _>>()?;
+    let monomers = fasta::Reader::new(&monomers.stdout[..])
+        .records()
+        .collect::<Result<Vec<_>, _>>()?;
+    assert_eq!(multimers.len(), monomers.len());
+    for (multimer, monomer) in multimers.iter().zip(&monomers) {
+        assert_eq!(multimer.id(), monomer.id());
+        assert_eq!(monomer.desc(), Some("monomer_length=100"));
+        assert_eq!(monomer.seq(), &multimer.seq()[..100]);
+    }
+    Ok(())
+}
+
+#[test]
+fn planted_orfs_are_found() -> Result<(), Box<dyn std::error::Error>> {
+    let generated = assert_fs::NamedTempFile::new("orfs.fasta")?;
+    let table = assert_fs::NamedTempFile::new("orfs.csv")?;
+    std::fs::write(
+        generated.path(),
+        generate(&["--kind", "orfs", "--seed", "3", "-n", "20", "-l", "300"])?,
+    )?;
+    Command::cargo_bin("circkit")?
+        .arg("orfs")
+        .arg(generated.path())
+        .arg("--strand")
+        .arg("forward")
+        .arg("--table")
+        .arg(table.path())
+        .arg("-o")
+        .arg("/dev/null")
+        .assert()
+        .success();
+
+    // (seq_id, stop) of every ORF found
+    let found = csv::Reader::from_path(table.path())?
+        .records()
+        .map(|row| {
+            let row = row.unwrap();
+            (row[1].to_string(), row[3].to_string())
+        })
+        .collect::<std::collections::HashSet<_>>();
+
+    // an upstream start codon in the same frame may extend the planted ORF, but it always ends at the planted stop codon
+    for record in fasta::Reader::from_file(generated.path())?.records() {
+        let record = record?;
+        let desc = record.desc().unwrap();
+        let fields = desc
+            .split(' ')
+            .map(|field| field.split('=').nth(1).unwrap().parse::<usize>())
+            .collect::<Result<Vec<_>, _>>()?;
+        let stop = (fields[0] + fields[1] - 3) % record.seq().len();
+        assert!(found.contains(&(format!("{} {}", record.id(), desc), stop.to_string())));
+    }
+    Ok(())
+}
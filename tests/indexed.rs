@@ -0,0 +1,94 @@
+use assert_cmd::prelude::*; // Add methods on commands
+use predicates::prelude::*; // Used for writing assertions
+use rstest::rstest; // Parameterized tests
+use std::process::Command; // Run programs
+
+#[rstest]
+fn extract_one_record(
+    #[values("first", "second", "third")] id: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    // a copy without its index has to be scanned instead
+    let unindexed = assert_fs::NamedTempFile::new("in.fasta")?;
+    std::fs::copy("tests/examples/indexed/in.fasta", unindexed.path())?;
+    let scanned = Command::cargo_bin("circkit")?
+        .args(["--id", id, "cat"])
+        .arg(unindexed.path())
+        .output()?;
+    assert!(scanned.status.success());
+    assert_eq!(
+        String::from_utf8(scanned.stdout.clone())?
+            .matches('>')
+            .count(),
+        1
+    );
+
+    Command::cargo_bin("circkit")?
+        .args(["-vv", "--id", id, "cat", "tests/examples/indexed/in.fasta"])
+        .assert()
+        .success()
+        .stdout(scanned.stdout)
+        .stderr(predicate::str::contains("using the index"));
+    Ok(())
+}
+
+/// The offsets of an index don't apply to the compressed bytes, so compressed input is scanned even if it has an index.
+#[test]
+fn compressed_input_ignores_index() -> Result<(), Box<dyn std::error::Error>> {
+    use assert_fs::prelude::*;
+
+    let dir = assert_fs::TempDir::new()?;
+    let compressed = dir.child("in.fasta.gz");
+    Command::cargo_bin("circkit")?
+        .args(["cat", "tests/examples/indexed/in.fasta", "-o"])
+        .arg(compressed.path())
+        .assert()
+        .success();
+    let scanned = Command::cargo_bin("circkit")?
+        .args(["--id", "second", "cat"])
+        .arg(compressed.path())
+        .output()?;
+    assert!(scanned.status.success());
+    assert!(String::from_utf8(scanned.stdout.clone())?.starts_with(">second"));
+
+    dir.child("in.fasta.gz.fai")
+        .write_file(std::path::Path::new("tests/examples/indexed/in.fasta.fai"))?;
+    Command::cargo_bin("circkit")?
+        .args(["--id", "second", "cat"])
+        .arg(compressed.path())
+        .assert()
+        .success()
+        .stdout(scanned.stdout);
+    Ok(())
+}
+
+#[test]
+fn missing_id() -> Result<(), Box<dyn std::error::Error>> {
+    Command::cargo_bin("circkit")?
+        .args(["--id", "fourth", "cat", "tests/examples/indexed/in.fasta"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("No record with id fourth"));
+    Ok(())
+}
+
+#[test]
+fn fai_entry() -> anyhow::Result<()> {
+    use circkit_cli::utils::{find_fai_entry, FaiEntry};
+
+    let index =
+        std::io::BufReader::new(std::fs::File::open("tests/examples/indexed/in.fasta.fai")?);
+    let entry = find_fai_entry(index, "second")?.unwrap();
+    assert_eq!(
+        entry,
+        FaiEntry {
+            name: "second".to_string(),
+            length: 53,
+            offset: 86,
+            line_bases: 10,
+            line_width: 11,
+        }
+    );
+    // five full lines of 10 bases plus a line of 3
+    assert_eq!(entry.byte_len(), 5 * 11 + 3);
+    Ok(())
+}
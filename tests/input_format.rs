@@ -0,0 +1,43 @@
+use assert_cmd::Command; // Run programs
+use predicates::prelude::*; // Used for writing assertions
+
+#[test]
+fn jsonl_matches_fasta() -> Result<(), Box<dyn std::error::Error>> {
+    let jsonl = concat!(
+        r#"{"id": "seq1", "seq": "TTGCA"}"#,
+        "\n\n",
+        r#"{"id": "seq2", "desc": "a circle", "seq": "GCAAT"}"#,
+        "\n",
+    );
+
+    Command::cargo_bin("circkit")?
+        .arg("canonicalize")
+        .arg("--input-format")
+        .arg("jsonl")
+        .write_stdin(jsonl)
+        .assert()
+        .success()
+        .stdout(">seq1\nAATGC\n>seq2 a circle\nAATGC\n");
+
+    // the same records as FASTA give the same output
+    Command::cargo_bin("circkit")?
+        .arg("canonicalize")
+        .write_stdin(">seq1\nTTGCA\n>seq2 a circle\nGCAAT\n")
+        .assert()
+        .success()
+        .stdout(">seq1\nAATGC\n>seq2 a circle\nAATGC\n");
+    Ok(())
+}
+
+#[test]
+fn invalid_jsonl_is_an_error() -> Result<(), Box<dyn std::error::Error>> {
+    Command::cargo_bin("circkit")?
+        .arg("canonicalize")
+        .arg("--input-format")
+        .arg("jsonl")
+        .write_stdin("{\"id\": \"seq1\", \"seq\": \"TTGCA\"}\n{\"id\": \"seq2\"}\n")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Invalid JSON record on line 2"));
+    Ok(())
+}
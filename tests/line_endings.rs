@@ -0,0 +1,27 @@
+use assert_cmd::prelude::*; // Add methods on commands
+use rstest::rstest; // Parameterized tests
+use std::process::Command; // Run programs
+
+#[rstest]
+fn crlf_matches_lf(
+    #[values("canonicalize", "uniq", "cat", "decat", "monomerize", "orfs", "period")] command: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let lf = std::fs::read_to_string("tests/examples/multiple_sequences_split_lines/in.fasta")?;
+    let crlf = assert_fs::NamedTempFile::new("in.fasta")?;
+    std::fs::write(crlf.path(), lf.replace('\n', "\r\n"))?;
+
+    let expected = Command::cargo_bin("circkit")?
+        .arg(command)
+        .arg("tests/examples/multiple_sequences_split_lines/in.fasta")
+        .output()?;
+    assert!(expected.status.success());
+
+    let output = Command::cargo_bin("circkit")?
+        .arg(command)
+        .arg(crlf.path())
+        .output()?;
+    assert!(output.status.success());
+    assert!(!output.stdout.contains(&b'\r'));
+    assert_eq!(output.stdout, expected.stdout);
+    Ok(())
+}
@@ -0,0 +1,27 @@
+use assert_cmd::prelude::*; // Add methods on commands
+use rstest::rstest; // Parameterized tests
+use std::process::Command; // Run programs
+
+fn run(args: &[&str]) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+    let output = Command::cargo_bin("circkit")?.args(args).output()?;
+    assert!(output.status.success());
+    Ok(output.stdout)
+}
+
+#[rstest]
+fn mmap_matches_buffered_reading(
+    #[values("canonicalize", "uniq", "monomerize", "orfs", "cat")] command: &str,
+    #[values(
+        "tests/examples/multiple_sequences_split_lines/in.fasta",
+        "tests/examples/repeated/in.fasta",
+        "tests/examples/min_overlap/in.fasta",
+        "tests/examples/compressed_input/in.fasta",
+        "tests/examples/compressed_input/in.fasta.gz",
+        "tests/examples/compressed_input/in.fasta.zst"
+    )]
+    file: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let buffered = run(&[command, file])?;
+    assert_eq!(run(&["--mmap", command, file])?, buffered);
+    Ok(())
+}
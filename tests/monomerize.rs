@@ -79,3 +79,710 @@ fn min_overlap_percent(
 
     Ok(())
 }
+
+#[rstest]
+#[case(&[], "exact overlaps only (0 mismatches)")]
+#[case(&["--max-mismatch", "3"], "at most 3 mismatches per overlap")]
+#[case(&["--max-mismatch-rate", "0.05"], "at most 5% mismatches per overlap")]
+#[case(&["--min-identity", "0.95"], "at least 95% identity per overlap")]
+#[case(
+    &["--max-mismatch", "3", "--min-identity", "0.95"],
+    "the stricter of at most 3 mismatches and at least 95% identity per overlap"
+)]
+#[case(
+    &["--max-mismatch", "3", "--min-identity", "0.95", "--overlap-combine", "looser"],
+    "the looser of at most 3 mismatches and at least 95% identity per overlap"
+)]
+fn logs_effective_overlap_policy(
+    #[case] args: &[&str],
+    #[case] policy: &str,
+) -> anyhow::Result<()> {
+    let output = assert_fs::NamedTempFile::new("out.fasta")?;
+
+    Command::cargo_bin("circkit")?
+        .arg("-vv")
+        .arg("monomerize")
+        .arg("tests/examples/simple/in.fasta")
+        .args(args)
+        .arg("-o")
+        .arg(output.path())
+        .assert()
+        .success()
+        .stderr(predicate::str::contains(format!(
+            "Overlap policy: {}",
+            policy
+        )));
+
+    Ok(())
+}
+
+#[rstest]
+fn append_length_matches_monomer(
+    #[values("1", "2", "4")] threads: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    use assert_fs::prelude::*;
+
+    // a monomer followed by a partial copy of itself, so monomerization shortens it
+    let monomer = "ATGCGTACGTTAGCCTAGGCATCGATCGGA";
+    let input = assert_fs::NamedTempFile::new("in.fasta")?;
+    input.write_str(&format!(">dimer\n{}{}\n", monomer, &monomer[..15]))?;
+
+    let mut cmd = Command::cargo_bin("circkit")?;
+    cmd.arg("monomerize")
+        .arg(input.path())
+        .arg("--append-length")
+        .arg("--threads")
+        .arg(threads);
+    cmd.assert()
+        .success()
+        .stdout(format!(">dimer length={}\n{}\n", monomer.len(), monomer));
+    Ok(())
+}
+
+#[rstest]
+fn seed_percent_scales_with_length(
+    #[values("1", "2", "4")] threads: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    use assert_fs::prelude::*;
+
+    // a pseudo-random monomer long enough that its seed is clamped to the maximum of 63
+    let mut state: u32 = 42;
+    let long_monomer: String = (0..700)
+        .map(|_| {
+            state = state.wrapping_mul(1_103_515_245).wrapping_add(12_345);
+            b"ACGT"[(state >> 16) as usize % 4] as char
+        })
+        .collect();
+    let short_monomer = "ATGCGTACGTTAGCCTAGGCATCGATCGGA";
+
+    let input = assert_fs::NamedTempFile::new("in.fasta")?;
+    input.write_str(&format!(
+        ">short\n{}{}\n>long\n{}{}\n",
+        short_monomer,
+        &short_monomer[..15],
+        long_monomer,
+        &long_monomer[..100]
+    ))?;
+    let expected = format!(">short\n{}\n>long\n{}\n", short_monomer, long_monomer);
+
+    // no single seed length works for both: the short overlap is shorter than a long seed
+    Command::cargo_bin("circkit")?
+        .arg("monomerize")
+        .arg(input.path())
+        .arg("--seed-length")
+        .arg("63")
+        .arg("--threads")
+        .arg(threads)
+        .assert()
+        .success()
+        .stdout(format!(">long\n{}\n", long_monomer));
+
+    Command::cargo_bin("circkit")?
+        .arg("monomerize")
+        .arg(input.path())
+        .arg("--seed-percent")
+        .arg("0.1")
+        .arg("--threads")
+        .arg(threads)
+        .assert()
+        .success()
+        .stdout(expected);
+    Ok(())
+}
+
+#[rstest]
+fn seed_length_auto(
+    #[values("1", "2", "4")] threads: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    use assert_fs::prelude::*;
+
+    let mut state: u32 = 42;
+    let long_monomer: String = (0..700)
+        .map(|_| {
+            state = state.wrapping_mul(1_103_515_245).wrapping_add(12_345);
+            b"ACGT"[(state >> 16) as usize % 4] as char
+        })
+        .collect();
+    let short_monomer = "ATGCGTACGTTAGCCTAGGCATCGATCGGA";
+
+    let input = assert_fs::NamedTempFile::new("in.fasta")?;
+    input.write_str(&format!(
+        ">short\n{}{}\n>long\n{}{}\n",
+        short_monomer,
+        &short_monomer[..15],
+        long_monomer,
+        &long_monomer[..100]
+    ))?;
+
+    // the short sequence gets a seed that fits in its 15 nt overlap
+    Command::cargo_bin("circkit")?
+        .arg("monomerize")
+        .arg(input.path())
+        .arg("--seed-length")
+        .arg("auto")
+        .arg("--threads")
+        .arg(threads)
+        .assert()
+        .success()
+        .stdout(format!(
+            ">short\n{}\n>long\n{}\n",
+            short_monomer, long_monomer
+        ));
+    Ok(())
+}
+
+#[rstest]
+#[case("4")]
+#[case("65")]
+#[case("automatic")]
+fn invalid_seed_length(#[case] seed_length: &str) -> Result<(), Box<dyn std::error::Error>> {
+    Command::cargo_bin("circkit")?
+        .arg("monomerize")
+        .arg("tests/examples/simple/in.fasta")
+        .arg("--seed-length")
+        .arg(seed_length)
+        .assert()
+        .code(2);
+    Ok(())
+}
+
+#[test]
+fn seed_percent_is_clamped() {
+    use circkit_cli::monomerize::seed_len_for;
+
+    assert_eq!(seed_len_for(20, 0.1), 5);
+    assert_eq!(seed_len_for(300, 0.1), 30);
+    assert_eq!(seed_len_for(10_000, 0.1), 63);
+}
+
+#[test]
+fn seed_percent_out_of_range() -> Result<(), Box<dyn std::error::Error>> {
+    Command::cargo_bin("circkit")?
+        .arg("monomerize")
+        .arg("tests/examples/simple/in.fasta")
+        .arg("--seed-percent")
+        .arg("1.5")
+        .assert()
+        .code(2);
+    Ok(())
+}
+
+#[rstest]
+fn overlap_output_matches_table(
+    #[values("1", "2", "4")] threads: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let output = assert_fs::NamedTempFile::new("out.fasta")?;
+    let overlaps = assert_fs::NamedTempFile::new("overlaps.fasta")?;
+    let table = assert_fs::NamedTempFile::new("table.tsv")?;
+
+    Command::cargo_bin("circkit")?
+        .arg("monomerize")
+        .arg("tests/examples/min_overlap/in.fasta")
+        .arg("--keep-all")
+        .arg("--threads")
+        .arg(threads)
+        .arg("--table")
+        .arg(table.path())
+        .arg("--overlap-output")
+        .arg(overlaps.path())
+        .arg("-o")
+        .arg(output.path())
+        .assert()
+        .success();
+
+    let overlaps: std::collections::HashMap<String, Vec<u8>> =
+        bio::io::fasta::Reader::from_file(overlaps.path())?
+            .records()
+            .map(|record| {
+                let record = record.unwrap();
+                (record.id().to_string(), record.seq().to_vec())
+            })
+            .collect();
+    assert!(!overlaps.is_empty());
+
+    let mut monomerized = 0;
+    for row in csv::ReaderBuilder::new()
+        .delimiter(b'\t')
+        .from_path(table.path())?
+        .records()
+    {
+        let row = row?;
+        let original_length: usize = row[1].parse()?;
+        let monomer_length: usize = row[2].parse()?;
+        let id = row[0].split_whitespace().next().unwrap();
+        // sequences that weren't monomerized are kept whole and have no overlap
+        match overlaps.get(id) {
+            Some(overlap) => {
+                assert_eq!(overlap.len(), original_length - monomer_length);
+                monomerized += 1;
+            }
+            None => assert_eq!(original_length, monomer_length),
+        }
+    }
+    assert_eq!(monomerized, overlaps.len());
+    Ok(())
+}
+
+#[rstest]
+#[case(&["--max-mismatch", "3"])]
+#[case(&["--min-identity", "0.95"])]
+fn max_mismatch_rate_conflicts(#[case] args: &[&str]) -> anyhow::Result<()> {
+    Command::cargo_bin("circkit")?
+        .arg("monomerize")
+        .arg("tests/examples/simple/in.fasta")
+        .arg("--max-mismatch-rate")
+        .arg("0.05")
+        .args(args)
+        .assert()
+        .code(2);
+    Ok(())
+}
+
+/// A sequence is reported as a multimer exactly when monomerizing it without any filters writes it.
+#[test]
+fn check_matches_monomerize() -> anyhow::Result<()> {
+    let input = "tests/examples/min_overlap/in.fasta";
+    let checked = Command::cargo_bin("circkit")?
+        .arg("monomerize")
+        .arg(input)
+        .arg("--check")
+        .output()?;
+    assert!(checked.status.success());
+    let multimers: Vec<String> = String::from_utf8(checked.stdout)?
+        .lines()
+        .filter_map(|line| line.strip_suffix("\ttrue"))
+        .map(String::from)
+        .collect();
+
+    let monomerized = Command::cargo_bin("circkit")?
+        .arg("monomerize")
+        .arg(input)
+        .output()?;
+    assert!(monomerized.status.success());
+    let ids: Vec<String> = String::from_utf8(monomerized.stdout)?
+        .lines()
+        .filter_map(|line| line.strip_prefix('>'))
+        .map(|head| head.split(' ').next().unwrap().to_string())
+        .collect();
+
+    assert!(!ids.is_empty());
+    assert_eq!(multimers, ids);
+    Ok(())
+}
+
+#[test]
+fn check_reports_every_record() -> anyhow::Result<()> {
+    assert_cmd::Command::cargo_bin("circkit")?
+        .arg("monomerize")
+        .arg("--check")
+        .arg("-")
+        .write_stdin(
+            ">dimer\nATGCAGGTCAGGATCAAATGCAGGTCAGGATCAA\n>monomer\nATGCAGGTCAGGATCAATTTTTTT\n",
+        )
+        .assert()
+        .success()
+        .stdout("dimer\ttrue\nmonomer\tfalse\n");
+    Ok(())
+}
+
+#[rstest]
+#[case(2.0, 2, true)]
+#[case(2.0, 3, false)]
+#[case(2.5, 3, false)] // the partial copy doesn't count
+#[case(3.0, 3, true)]
+#[case(3.5, 3, true)]
+fn require_copies(
+    #[case] copies: f64,
+    #[case] require: usize,
+    #[case] kept: bool,
+) -> anyhow::Result<()> {
+    let monomer = "ATGCAGGTCAGGATCAA";
+    let len = (monomer.len() as f64 * copies) as usize;
+    let seq: String = monomer.chars().cycle().take(len).collect();
+
+    let expected = match kept {
+        true => format!(">seq\n{}\n", monomer),
+        false => String::new(),
+    };
+    assert_cmd::Command::cargo_bin("circkit")?
+        .arg("monomerize")
+        .arg("--require-copies")
+        .arg(require.to_string())
+        .arg("-")
+        .write_stdin(format!(">seq\n{}\n", seq))
+        .assert()
+        .success()
+        .stdout(expected);
+    Ok(())
+}
+
+#[rstest]
+#[case(40, &[], 1)]
+#[case(40, &[20], 2)]
+#[case(30, &[20], 1)] // 1.5 copies
+#[case(60, &[40, 20], 3)]
+#[case(50, &[40, 20], 2)] // 2.5 copies
+fn whole_copies(#[case] len: usize, #[case] boundaries: &[usize], #[case] copies: usize) {
+    assert_eq!(
+        circkit_cli::monomerize::whole_copies(len, boundaries),
+        copies
+    );
+}
+
+#[test]
+fn mask_overlap() -> anyhow::Result<()> {
+    let monomer = "ATGCAGGTCAGGATCAA";
+    let overlap = &monomer[..12];
+    let unmonomerizable = "ACGTTGCA";
+
+    assert_cmd::Command::cargo_bin("circkit")?
+        .arg("monomerize")
+        .arg("--keep-all")
+        .arg("--mask-overlap")
+        .arg("-")
+        .write_stdin(format!(
+            ">seq\n{}{}\n>other\n{}\n",
+            monomer, overlap, unmonomerizable
+        ))
+        .assert()
+        .success()
+        .stdout(format!(
+            ">seq\n{}{}\n>other\n{}\n",
+            monomer,
+            overlap.to_lowercase(),
+            unmonomerizable
+        ));
+    Ok(())
+}
+
+#[test]
+fn mask_overlap_requires_keep_all() -> anyhow::Result<()> {
+    Command::cargo_bin("circkit")?
+        .arg("monomerize")
+        .arg("--mask-overlap")
+        .arg("tests/examples/simple/in.fasta")
+        .assert()
+        .code(2);
+    Ok(())
+}
+
+#[rstest]
+fn rna_keeps_u(#[values(&[] as &[&str], &["--check"])] args: &[&str]) -> anyhow::Result<()> {
+    let monomer = "AUGCAGGUCAGGAUCAA";
+    let expected = match args {
+        [] => format!(">seq\n{}\n", monomer),
+        _ => "seq\ttrue\n".to_string(),
+    };
+
+    assert_cmd::Command::cargo_bin("circkit")?
+        .arg("monomerize")
+        .arg("--rna")
+        .args(args)
+        .arg("-")
+        .write_stdin(format!(">seq\n{}\n", monomer.repeat(2)))
+        .assert()
+        .success()
+        .stdout(expected);
+    Ok(())
+}
+
+/// Each copy differs from the first in one base, which the overlap cutoff allows, but the last two copies differ from each other in two.
+#[rstest]
+#[case(None, true)]
+#[case(Some("0.94"), true)]
+#[case(Some("0.95"), false)]
+fn min_copy_identity(#[case] cutoff: Option<&str>, #[case] kept: bool) -> anyhow::Result<()> {
+    let monomer = "ACGTTGCAAGGCTTACGACCTGA";
+    let second = format!("{}T{}", &monomer[..1], &monomer[2..]);
+    let third = format!("{}A{}", &monomer[..3], &monomer[4..]);
+
+    let mut cmd = assert_cmd::Command::cargo_bin("circkit")?;
+    cmd.arg("monomerize")
+        .arg("--min-identity")
+        .arg("0.95")
+        .arg("-")
+        .write_stdin(format!(">seq\n{}{}{}\n", monomer, second, third));
+    if let Some(cutoff) = cutoff {
+        cmd.arg("--min-copy-identity").arg(cutoff);
+    }
+    cmd.assert().success().stdout(match kept {
+        true => format!(">seq\n{}\n", monomer),
+        false => String::new(),
+    });
+    Ok(())
+}
+
+#[test]
+fn table_copies() -> anyhow::Result<()> {
+    use assert_fs::prelude::*;
+
+    let monomer = "ACGTTGCAAGGCTTACGACCTGA";
+    let table = assert_fs::NamedTempFile::new("table.csv")?;
+    assert_cmd::Command::cargo_bin("circkit")?
+        .arg("monomerize")
+        .arg("--keep-all")
+        .arg("--table")
+        .arg(table.path())
+        .arg("-")
+        .write_stdin(format!(
+            ">dimer\n{0}{0}\n>trimer\n{0}{0}{0}\n>monomer\n{0}\n",
+            monomer
+        ))
+        .assert()
+        .success();
+    table.assert(
+        "id,original_length,monomer_length,copies\n\
+         dimer,46,23,2\n\
+         trimer,69,23,3\n\
+         monomer,23,23,1\n",
+    );
+    Ok(())
+}
+
+#[test]
+fn table_overlap() -> anyhow::Result<()> {
+    use assert_fs::prelude::*;
+
+    let monomer = "ACGTTGCAAGGCTTACGACCTGA";
+    // the repeat differs from the start of the monomer at its second base
+    let table = assert_fs::NamedTempFile::new("table.csv")?;
+    assert_cmd::Command::cargo_bin("circkit")?
+        .arg("monomerize")
+        .arg("--keep-all")
+        .arg("--max-mismatch")
+        .arg("1")
+        .arg("--table")
+        .arg(table.path())
+        .arg("--table-overlap")
+        .arg("-")
+        .write_stdin(format!(
+            ">dimer\n{0}AA{1}\n>trimer\n{0}{0}{0}\n>monomer\n{0}\n",
+            monomer,
+            &monomer[2..15]
+        ))
+        .assert()
+        .success();
+    table.assert(
+        "id,original_length,monomer_length,copies,overlap_len,mismatches\n\
+         dimer,38,23,2,15,1\n\
+         trimer,69,23,3,23,0\n\
+         monomer,23,23,1,,\n",
+    );
+    Ok(())
+}
+
+/// The reported overlap is trimmed even if it doesn't match the start of the contig.
+#[rstest]
+#[case(&[], ">contig\nACGTTGCAAGGCTTACGACCTGA\n")]
+#[case(&["--keep-all"], ">contig\nACGTTGCAAGGCTTACGACCTGA\n>short\nACGTA\n")]
+fn end_overlap(#[case] args: &[&str], #[case] expected: &str) -> anyhow::Result<()> {
+    assert_cmd::Command::cargo_bin("circkit")?
+        .arg("monomerize")
+        .arg("--end-overlap")
+        .arg("7")
+        .args(args)
+        .arg("-")
+        .write_stdin(">contig\nACGTTGCAAGGCTTACGACCTGATTTTTTT\n>short\nACGTA\n")
+        .assert()
+        .success()
+        .stdout(expected.to_owned());
+    Ok(())
+}
+
+/// A hairpin (a repeat, a loop, and the repeat's reverse complement) is only trimmed with --inverted,
+/// whether or not it is also repeated in tandem.
+#[rstest]
+#[case(&[], ">tandem\nATGCGTACGTTAGCCAAAAAAAAAAGGCTAACGTACGCAT\n")]
+#[case(
+    &["--inverted"],
+    ">hairpin\nATGCGTACGTTAGCCAAAAAAAAAA\n>tandem\nATGCGTACGTTAGCCAAAAAAAAAA\n"
+)]
+fn inverted(#[case] args: &[&str], #[case] expected: &str) -> anyhow::Result<()> {
+    let hairpin = "ATGCGTACGTTAGCCAAAAAAAAAAGGCTAACGTACGCAT";
+    assert_cmd::Command::cargo_bin("circkit")?
+        .arg("monomerize")
+        .args(args)
+        .arg("-")
+        .write_stdin(format!(
+            ">hairpin\n{0}\n>tandem\n{0}{0}\n>neither\nACGTTGCAAGGCTTACGACCTGA\n",
+            hairpin
+        ))
+        .assert()
+        .success()
+        .stdout(expected.to_owned());
+    Ok(())
+}
+
+/// The overlap has 2 mismatches, which --min-identity allows but --max-mismatch doesn't.
+#[rstest]
+#[case("stricter", false)]
+#[case("looser", true)]
+fn overlap_combine(#[case] combine: &str, #[case] monomerized: bool) -> anyhow::Result<()> {
+    let monomer = "ACGTTGCAAGGCTTACGACCTGA";
+    let overlap = format!("CA{}", &monomer[2..]);
+
+    assert_cmd::Command::cargo_bin("circkit")?
+        .arg("monomerize")
+        .arg("--max-mismatch")
+        .arg("1")
+        .arg("--min-identity")
+        .arg("0.9")
+        .arg("--overlap-combine")
+        .arg(combine)
+        .arg("-")
+        .write_stdin(format!(">seq\n{}{}\n", monomer, overlap))
+        .assert()
+        .success()
+        .stdout(match monomerized {
+            true => format!(">seq\n{}\n", monomer),
+            false => String::new(),
+        });
+    Ok(())
+}
+
+/// The copies in --table are tandem copies, so a hairpin that is only trimmed at its inverted repeat is a single copy,
+/// even when its tandem copies were rejected by --require-copies.
+#[rstest]
+#[case(&[], "25,2")]
+#[case(&["--require-copies", "3"], "65,1")]
+fn inverted_table_copies(#[case] args: &[&str], #[case] tandem: &str) -> anyhow::Result<()> {
+    let hairpin = "ATGCGTACGTTAGCCAAAAAAAAAAGGCTAACGTACGCAT";
+    let table = assert_fs::NamedTempFile::new("table.csv")?;
+    assert_cmd::Command::cargo_bin("circkit")?
+        .arg("monomerize")
+        .arg("--inverted")
+        .arg("--table")
+        .arg(table.path())
+        .args(args)
+        .arg("-")
+        .write_stdin(format!(">hairpin\n{0}\n>tandem\n{0}{0}\n", hairpin))
+        .assert()
+        .success();
+    assert_eq!(
+        std::fs::read_to_string(table.path())?,
+        format!(
+            "id,original_length,monomer_length,copies\nhairpin,40,25,1\ntandem,80,{}\n",
+            tandem
+        )
+    );
+    Ok(())
+}
+
+#[test]
+fn inverted_conflicts_with_table_overlap() -> anyhow::Result<()> {
+    Command::cargo_bin("circkit")?
+        .arg("monomerize")
+        .arg("tests/examples/simple/in.fasta")
+        .arg("--inverted")
+        .arg("--table")
+        .arg("table.csv")
+        .arg("--table-overlap")
+        .assert()
+        .code(2);
+    Ok(())
+}
+
+#[test]
+fn inverted_length_range_is_a_usage_error() -> anyhow::Result<()> {
+    Command::cargo_bin("circkit")?
+        .arg("monomerize")
+        .arg("tests/examples/simple/in.fasta")
+        .arg("--min-length")
+        .arg("10")
+        .arg("--max-length")
+        .arg("5")
+        .assert()
+        .code(2)
+        .stderr(predicate::str::contains("greater than max_monomer_len"));
+    Ok(())
+}
+
+/// --explain doesn't need any input, since it only describes the cutoffs.
+#[rstest]
+#[case(&["--max-mismatch", "5"], "At a 200 nt overlap, at most 5 mismatches are allowed (at least 97.5% identity)\n")]
+#[case(&["--max-mismatch", "5", "--explain-overlap", "50"], "At a 50 nt overlap, at most 5 mismatches are allowed (at least 90.0% identity)\n")]
+#[case(&["--min-identity", "0.95", "--explain-overlap", "50"], "At a 50 nt overlap, at most 3 mismatches are allowed (at least 94.0% identity)\n")]
+#[case(&[], "At a 200 nt overlap, at most 0 mismatches are allowed (at least 100.0% identity)\n")]
+fn explain(#[case] args: &[&str], #[case] expected: &str) -> anyhow::Result<()> {
+    Command::cargo_bin("circkit")?
+        .arg("monomerize")
+        .arg("--explain")
+        .args(args)
+        .assert()
+        .success()
+        .stdout(expected.to_string());
+    Ok(())
+}
+
+/// --explain writes to the main output like any other result.
+#[test]
+fn explain_respects_output() -> anyhow::Result<()> {
+    let output = assert_fs::NamedTempFile::new("explain.txt")?;
+    Command::cargo_bin("circkit")?
+        .arg("monomerize")
+        .arg("--explain")
+        .arg("-o")
+        .arg(output.path())
+        .assert()
+        .success()
+        .stdout("");
+    assert_eq!(
+        std::fs::read_to_string(output.path())?,
+        "At a 200 nt overlap, at most 0 mismatches are allowed (at least 100.0% identity)\n"
+    );
+
+    Command::cargo_bin("circkit")?
+        .arg("monomerize")
+        .arg("--explain")
+        .arg("--null-output")
+        .assert()
+        .success()
+        .stdout("");
+    Ok(())
+}
+
+#[test]
+fn logs_stringency() -> anyhow::Result<()> {
+    let output = assert_fs::NamedTempFile::new("out.fasta")?;
+    Command::cargo_bin("circkit")?
+        .arg("-vv")
+        .arg("monomerize")
+        .arg("tests/examples/simple/in.fasta")
+        .arg("--max-mismatch")
+        .arg("5")
+        .arg("-o")
+        .arg(output.path())
+        .assert()
+        .success()
+        .stderr(predicate::str::contains(
+            "Overlap stringency: At a 200 nt overlap, at most 5 mismatches are allowed (at least 97.5% identity)",
+        ));
+    Ok(())
+}
+
+/// The second copy is missing a base, which only the edit distance tolerates.
+#[rstest]
+#[case("hamming", None)]
+#[case("edit", Some("ACGTTGCAAGGCTTACGATCGTAGCTAGGCTAACGGTCC"))]
+fn overlap_metric(#[case] metric: &str, #[case] expected: Option<&str>) -> anyhow::Result<()> {
+    let monomer = "ACGTTGCAAGGCTTACGATCGTAGCTAGGCTAACGGTCCA";
+    let overlap = format!("{}{}", &monomer[..3], &monomer[4..25]);
+
+    assert_cmd::Command::cargo_bin("circkit")?
+        .arg("monomerize")
+        .arg("--max-mismatch")
+        .arg("2")
+        .arg("--seed-length")
+        .arg("6")
+        .arg("--overlap-metric")
+        .arg(metric)
+        .arg("-")
+        .write_stdin(format!(">seq\n{}{}\n", monomer, overlap))
+        .assert()
+        .success()
+        .stdout(match expected {
+            Some(expected) => format!(">seq\n{}\n", expected),
+            None => String::new(),
+        });
+    Ok(())
+}
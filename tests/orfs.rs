@@ -65,3 +65,800 @@ fn orfipy(
 
     Ok(())
 }
+
+#[test]
+fn cluster_proteins_groups_identical_orfs() -> anyhow::Result<()> {
+    use assert_fs::prelude::*;
+
+    // the same ORF (ATG + 25 alanine codons + TAA) embedded in two different records
+    let orf = format!("ATG{}TAA", "GCT".repeat(25));
+    let input = assert_fs::NamedTempFile::new("in.fasta")?;
+    input.write_str(&format!(">seq1\nCC{}GG\n>seq2\nT{}CCC\n", orf, orf))?;
+    let output = assert_fs::NamedTempFile::new("out.fasta")?;
+    let cluster_table = assert_fs::NamedTempFile::new("clusters.csv")?;
+
+    Command::cargo_bin("circkit")?
+        .arg("orfs")
+        .arg(input.path())
+        .arg("--strand")
+        .arg("forward")
+        .arg("--cluster-proteins")
+        .arg("--cluster-table")
+        .arg(cluster_table.path())
+        .arg("-o")
+        .arg(output.path())
+        .assert()
+        .success();
+
+    let records = fasta::Reader::from_file(output.path())?
+        .records()
+        .collect::<Result<Vec<_>, _>>()?;
+    assert_eq!(records.len(), 1);
+    assert_eq!(records[0].id(), "seq1_ORF2");
+    assert_eq!(records[0].seq(), format!("M{}", "A".repeat(25)).as_bytes());
+
+    let table = std::fs::read_to_string(cluster_table.path())?;
+    assert_eq!(
+        table,
+        "id,member_id,distance\nseq1_ORF2,seq1_ORF2,0\nseq1_ORF2,seq2_ORF1,0\n"
+    );
+
+    Ok(())
+}
+
+/// A protein within --protein-max-dist of several representatives joins the nearest one, not just the first.
+#[test]
+fn cluster_proteins_joins_nearest_cluster() -> anyhow::Result<()> {
+    use assert_fs::prelude::*;
+
+    // MAAAAA and MCCCAA are 3 edits apart, so both represent clusters, and MCCAAA is 2 edits from the first but 1 from the second
+    let orf = |codons: &str| format!("ATG{}TAA", codons);
+    let input = assert_fs::NamedTempFile::new("in.fasta")?;
+    input.write_str(&format!(
+        ">a\n{}\n>b\n{}\n>c\n{}\n",
+        orf(&"GCT".repeat(5)),
+        orf(&format!("{}{}", "TGT".repeat(3), "GCT".repeat(2))),
+        orf(&format!("{}{}", "TGT".repeat(2), "GCT".repeat(3))),
+    ))?;
+    let cluster_table = assert_fs::NamedTempFile::new("clusters.csv")?;
+
+    Command::cargo_bin("circkit")?
+        .arg("orfs")
+        .arg(input.path())
+        .arg("--strand")
+        .arg("forward")
+        .arg("--min-length")
+        .arg("0")
+        .arg("--cluster-proteins")
+        .arg("--protein-max-dist")
+        .arg("2")
+        .arg("--cluster-table")
+        .arg(cluster_table.path())
+        .assert()
+        .success()
+        .stdout(">a_ORF0\nMAAAAA\n>b_ORF0\nMCCCAA\n");
+
+    assert_eq!(
+        std::fs::read_to_string(cluster_table.path())?,
+        "id,member_id,distance\na_ORF0,a_ORF0,0\nb_ORF0,b_ORF0,0\nb_ORF0,c_ORF0,1\n"
+    );
+
+    Ok(())
+}
+
+#[rstest]
+#[case("csv", b',')]
+#[case("tsv", b'\t')]
+fn table_stop_is_empty_without_stop_codon(
+    #[case] extension: &str,
+    #[case] delimiter: u8,
+) -> anyhow::Result<()> {
+    use assert_fs::prelude::*;
+
+    // there is no stop codon in the frame of the only start codon, so the ORF never ends
+    let input = assert_fs::NamedTempFile::new("in.fasta")?;
+    input.write_str(&format!(">seq1\nATG{}\n", "GCT".repeat(30)))?;
+    let output = assert_fs::NamedTempFile::new("out.fasta")?;
+    let table = assert_fs::NamedTempFile::new(format!("table.{}", extension))?;
+
+    Command::cargo_bin("circkit")?
+        .arg("orfs")
+        .arg(input.path())
+        .arg("--strand")
+        .arg("forward")
+        .arg("--no-stop-required")
+        .arg("--table")
+        .arg(table.path())
+        .arg("-o")
+        .arg(output.path())
+        .assert()
+        .success();
+
+    let mut reader = csv::ReaderBuilder::new()
+        .delimiter(delimiter)
+        .from_path(table.path())?;
+    let headers = reader.headers()?.clone();
+    let rows = reader.records().collect::<Result<Vec<_>, _>>()?;
+    assert_eq!(rows.len(), 1);
+
+    let field = |name: &str| &rows[0][headers.iter().position(|h| h == name).unwrap()];
+    assert_eq!(field("orf_id"), "seq1_ORF0");
+    assert_eq!(field("start"), "0");
+    assert_eq!(field("stop"), "");
+
+    Ok(())
+}
+
+/// A stop-less ORF has no stop codon to leave out, so all of it is written, down to a circle of just the start codon.
+#[rstest]
+#[case("ATGAAAAAA", &[], "ATGAAAAAA", "9")]
+#[case("ATGAAAAAA", &["--include-stop"], "ATGAAAAAA", "9")]
+#[case("ATGAAAAAA", &["--protein"], "MKK", "9")]
+#[case("ATG", &[], "ATG", "3")]
+fn stopless_orf_is_written_whole(
+    #[case] seq: &str,
+    #[case] args: &[&str],
+    #[case] expected: &str,
+    #[case] length: &str,
+) -> anyhow::Result<()> {
+    use assert_fs::prelude::*;
+
+    let input = assert_fs::NamedTempFile::new("in.fasta")?;
+    input.write_str(&format!(">seq1\n{}\n", seq))?;
+    let table = assert_fs::NamedTempFile::new("table.csv")?;
+    let bed = assert_fs::NamedTempFile::new("orfs.bed")?;
+
+    Command::cargo_bin("circkit")?
+        .arg("orfs")
+        .arg(input.path())
+        .arg("--strand")
+        .arg("forward")
+        .arg("--min-length")
+        .arg("0")
+        .arg("--no-stop-required")
+        .arg("--table")
+        .arg(table.path())
+        .arg("--bed")
+        .arg(bed.path())
+        .args(args)
+        .assert()
+        .success()
+        .stdout(format!(">seq1_ORF0\n{}\n", expected));
+
+    let mut reader = csv::Reader::from_path(table.path())?;
+    let headers = reader.headers()?.clone();
+    let rows = reader.records().collect::<Result<Vec<_>, _>>()?;
+    assert_eq!(rows.len(), 1);
+    assert_eq!(
+        &rows[0][headers.iter().position(|h| h == "length").unwrap()],
+        length
+    );
+    assert_eq!(
+        std::fs::read_to_string(bed.path())?,
+        format!("seq1\t0\t{}\tseq1_ORF0\t0\t+\n", length)
+    );
+
+    Ok(())
+}
+
+#[test]
+fn stopless_frame_is_reported_once() -> anyhow::Result<()> {
+    use assert_fs::prelude::*;
+
+    // both start codons are in the same frame, which has no stop codon, so they read the same ORF forever
+    let input = assert_fs::NamedTempFile::new("in.fasta")?;
+    input.write_str(&format!(
+        ">seq1\nATG{}ATG{}\n",
+        "GCT".repeat(10),
+        "GCT".repeat(10)
+    ))?;
+    let output = assert_fs::NamedTempFile::new("out.fasta")?;
+    let table = assert_fs::NamedTempFile::new("table.csv")?;
+
+    Command::cargo_bin("circkit")?
+        .arg("orfs")
+        .arg(input.path())
+        .arg("--strand")
+        .arg("forward")
+        .arg("--partial")
+        .arg("3prime")
+        .arg("--min-length")
+        .arg("0")
+        .arg("--table")
+        .arg(table.path())
+        .arg("-o")
+        .arg(output.path())
+        .assert()
+        .success();
+
+    let mut reader = csv::Reader::from_path(table.path())?;
+    let headers = reader.headers()?.clone();
+    let rows = reader.records().collect::<Result<Vec<_>, _>>()?;
+    assert_eq!(rows.len(), 1);
+
+    let field = |name: &str| &rows[0][headers.iter().position(|h| h == name).unwrap()];
+    assert_eq!(field("orf_id"), "seq1_ORF0");
+    assert_eq!(field("stop"), "");
+    assert_eq!(field("wraps"), "3");
+    assert_eq!(field("stopless_circular"), "true");
+
+    Ok(())
+}
+
+#[rstest]
+#[case("none", &["stop_ORF0"])]
+#[case("3prime", &["start_ORF0", "stop_ORF0"])]
+#[case(
+    "both",
+    &["open_ORF0", "open_ORF1", "open_ORF2", "start_ORF0", "start_ORF1", "start_ORF2", "stop_ORF0", "stop_ORF1", "stop_ORF2"]
+)]
+fn partial_policies(#[case] partial: &str, #[case] expected: &[&str]) -> anyhow::Result<()> {
+    use assert_fs::prelude::*;
+
+    // "open" has no start or stop codons, "start" has a start but no stop, and "stop" has a complete ORF in frame 0
+    let input = assert_fs::NamedTempFile::new("in.fasta")?;
+    input.write_str(concat!(
+        ">open\nGCTGCTGCTGCTGCTGCTGCC\n",
+        ">start\nATGGCTGCTGCTGCTGCTGCC\n",
+        ">stop\nATGGCTGCTTAAGCTGCTGCC\n",
+    ))?;
+    let output = assert_fs::NamedTempFile::new("out.fasta")?;
+
+    Command::cargo_bin("circkit")?
+        .arg("orfs")
+        .arg(input.path())
+        .arg("--strand")
+        .arg("forward")
+        .arg("--min-length")
+        .arg("0")
+        .arg("--partial")
+        .arg(partial)
+        .arg("-o")
+        .arg(output.path())
+        .assert()
+        .success();
+
+    let ids = fasta::Reader::from_file(output.path())?
+        .records()
+        .map(|record| record.map(|record| record.id().to_owned()))
+        .collect::<Result<Vec<_>, _>>()?;
+    assert_eq!(ids, expected);
+
+    Ok(())
+}
+
+#[test]
+fn no_stop_required_is_3prime_partial() -> anyhow::Result<()> {
+    let run = |args: &[&str]| -> anyhow::Result<Vec<u8>> {
+        Ok(Command::cargo_bin("circkit")?
+            .arg("orfs")
+            .arg("tests/examples/multiple_sequences/in.fasta")
+            .arg("--min-length")
+            .arg("0")
+            .args(args)
+            .output()?
+            .stdout)
+    };
+    assert_eq!(
+        run(&["--no-stop-required"])?,
+        run(&["--partial", "3prime"])?
+    );
+    Ok(())
+}
+
+#[rstest]
+#[case(&[], "seq1_ORF0")]
+#[case(&["--prefer-atg"], "seq1_ORF6")]
+#[case(&["--prefer-atg", "--prefer-atg-window", "3"], "seq1_ORF0")]
+fn prefer_atg(#[case] args: &[&str], #[case] expected: &str) -> anyhow::Result<()> {
+    use assert_fs::prelude::*;
+
+    // a GTG start, then an in-frame ATG 6 nt downstream, both ending at the same stop
+    let input = assert_fs::NamedTempFile::new("in.fasta")?;
+    input.write_str(">seq1\nGTGAAAATGCCCCCCTAACC\n")?;
+    let output = assert_fs::NamedTempFile::new("out.fasta")?;
+
+    Command::cargo_bin("circkit")?
+        .arg("orfs")
+        .arg(input.path())
+        .arg("--strand")
+        .arg("forward")
+        .arg("--min-length")
+        .arg("0")
+        .arg("--max-wraps")
+        .arg("0")
+        .arg("--start-codons")
+        .arg("ATG,GTG")
+        .args(args)
+        .arg("-o")
+        .arg(output.path())
+        .assert()
+        .success();
+
+    let ids = fasta::Reader::from_file(output.path())?
+        .records()
+        .map(|record| record.map(|record| record.id().to_owned()))
+        .collect::<Result<Vec<_>, _>>()?;
+    assert_eq!(ids, [expected]);
+
+    Ok(())
+}
+
+#[rstest]
+fn bed_round_trips_to_table(#[values(true, false)] include_stop: bool) -> anyhow::Result<()> {
+    let file = std::path::Path::new("tests/examples")
+        .join("nim_cated_Soil_microbial_communities_from_permafrost_in_Bonanza_Creek__Alaska")
+        .join("in.fasta.xz");
+    let table = assert_fs::NamedTempFile::new("table.tsv")?;
+    let bed = assert_fs::NamedTempFile::new("orfs.bed")?;
+
+    let mut cmd = Command::cargo_bin("circkit")?;
+    cmd.arg("orfs")
+        .arg(file)
+        .arg("--table")
+        .arg(table.path())
+        .arg("--bed")
+        .arg(bed.path())
+        .arg("-o")
+        .arg("/dev/null");
+    if include_stop {
+        cmd.arg("--include-stop");
+    }
+    cmd.assert().success();
+
+    // every interval of each ORF, in order
+    let mut intervals = std::collections::HashMap::<String, Vec<(usize, usize, String)>>::new();
+    for line in std::fs::read_to_string(bed.path())?.lines() {
+        let fields = line.split('\t').collect::<Vec<_>>();
+        assert_eq!(fields.len(), 6);
+        assert!(fields[3].starts_with(fields[0]));
+        intervals.entry(fields[3].to_string()).or_default().push((
+            fields[1].parse()?,
+            fields[2].parse()?,
+            fields[5].to_string(),
+        ));
+    }
+
+    let mut rows = 0;
+    for row in csv::ReaderBuilder::new()
+        .delimiter(b'\t')
+        .from_path(table.path())?
+        .records()
+    {
+        let row = row?;
+        let start: usize = row[2].parse()?;
+        let length: usize = row[4].parse()?;
+        let orf = &intervals[&row[0]];
+
+        assert!(orf.iter().all(|(start, end, _)| start < end));
+        assert_eq!(
+            orf.iter().map(|(start, end, _)| end - start).sum::<usize>(),
+            length
+        );
+        if row[0].contains("_RC_ORF") {
+            assert!(orf.iter().all(|(_, _, strand)| strand == "-"));
+            // the start codon is at the end of the last interval on the reverse strand
+            assert_eq!(orf.last().unwrap().1 - 1, start);
+        } else {
+            assert!(orf.iter().all(|(_, _, strand)| strand == "+"));
+            assert_eq!(orf[0].0, start);
+            if !include_stop && !row[3].is_empty() {
+                // an ORF whose stop codon begins at the origin runs to the end of the sequence instead
+                let stop: usize = row[3].parse()?;
+                assert!(orf.last().unwrap().1 == stop || stop == 0);
+            }
+        }
+        rows += 1;
+    }
+    assert_eq!(rows, intervals.len());
+    Ok(())
+}
+
+#[rstest]
+#[case(&[], &[])]
+#[case(&["--ambiguous-codons"], &["seq1_ORF3"])]
+fn ambiguous_codons(#[case] args: &[&str], #[case] expected: &[&str]) -> anyhow::Result<()> {
+    use assert_fs::prelude::*;
+
+    // the start codon is ATR, which could be ATG
+    let input = assert_fs::NamedTempFile::new("in.fasta")?;
+    input.write_str(">seq1\nCCCATRGCAGCAGCATAACCC\n")?;
+    let output = assert_fs::NamedTempFile::new("out.fasta")?;
+
+    Command::cargo_bin("circkit")?
+        .arg("orfs")
+        .arg(input.path())
+        .arg("--strand")
+        .arg("forward")
+        .arg("--min-length")
+        .arg("0")
+        .arg("--max-wraps")
+        .arg("0")
+        .args(args)
+        .arg("-o")
+        .arg(output.path())
+        .assert()
+        .success();
+
+    let records = fasta::Reader::from_file(output.path())?
+        .records()
+        .collect::<Result<Vec<_>, _>>()?;
+    let ids = records.iter().map(|record| record.id()).collect::<Vec<_>>();
+    assert_eq!(ids, expected);
+
+    // the ambiguity code is kept in the output
+    if let Some(record) = records.first() {
+        assert_eq!(record.seq(), b"ATRGCAGCAGCA");
+    }
+    Ok(())
+}
+
+/// A GTG-initiated ORF is translated starting with M, unless --initiator-met is turned off.
+#[rstest]
+#[case("true", ">seq1_ORF0\nMKK*\n")]
+#[case("false", ">seq1_ORF0\nVKK*\n")]
+fn initiator_met(#[case] enabled: &str, #[case] expected: &str) -> anyhow::Result<()> {
+    use assert_fs::prelude::*;
+
+    let input = assert_fs::NamedTempFile::new("in.fasta")?;
+    input.write_str(">seq1\nGTGAAAAAATAACC\n")?;
+
+    Command::cargo_bin("circkit")?
+        .arg("orfs")
+        .arg(input.path())
+        .arg("--strand")
+        .arg("forward")
+        .arg("--start-codons")
+        .arg("ATG,GTG")
+        .arg("--min-length")
+        .arg("0")
+        .arg("--max-wraps")
+        .arg("0")
+        .arg("--include-stop")
+        .arg("--protein")
+        .arg("--initiator-met")
+        .arg(enabled)
+        .assert()
+        .success()
+        .stdout(expected.to_string());
+
+    Ok(())
+}
+
+/// The ORF starting at 55 is 4 nt away from the longer one starting at 2, across the origin.
+#[rstest]
+#[case(None, &["seq1_ORF2", "seq1_ORF55"])]
+#[case(Some("4"), &["seq1_ORF2", "seq1_ORF55"])]
+#[case(Some("5"), &["seq1_ORF2"])]
+fn min_spacing(#[case] spacing: Option<&str>, #[case] expected: &[&str]) -> anyhow::Result<()> {
+    use assert_fs::prelude::*;
+
+    let input = assert_fs::NamedTempFile::new("in.fasta")?;
+    input.write_str(">seq1\nGAATGCCCCCCCCCCCTAACCATGCCCCCCCCCCCCCCCCCTGACCCCCCCCCCCAT\n")?;
+    let output = assert_fs::NamedTempFile::new("out.fasta")?;
+
+    let mut cmd = Command::cargo_bin("circkit")?;
+    cmd.arg("orfs")
+        .arg(input.path())
+        .arg("--strand")
+        .arg("forward")
+        .arg("--min-length")
+        .arg("0")
+        .arg("-o")
+        .arg(output.path());
+    if let Some(spacing) = spacing {
+        cmd.arg("--min-spacing").arg(spacing);
+    }
+    cmd.assert().success();
+
+    let ids = fasta::Reader::from_file(output.path())?
+        .records()
+        .map(|record| record.map(|record| record.id().to_owned()))
+        .collect::<Result<Vec<_>, _>>()?;
+    assert_eq!(ids, expected);
+
+    Ok(())
+}
+
+/// Two ORFs in frames 0 and 1 share 17 nt, whether or not they cross the origin.
+#[rstest]
+#[case(
+    "ATGCATGCCCCTAACCCCTAGCTAACCCCC",
+    "seq1\tseq1_ORF0\tseq1_ORF4\t0\t1\t17\n"
+)]
+#[case(
+    "CTAACCCCTAGCTAACCCCCATGCATGCCC",
+    "seq1\tseq1_ORF20\tseq1_ORF24\t2\t0\t17\n"
+)]
+fn orf_overlap_frames(#[case] seq: &str, #[case] expected: &str) -> anyhow::Result<()> {
+    use assert_fs::prelude::*;
+
+    let input = assert_fs::NamedTempFile::new("in.fasta")?;
+    input.write_str(&format!(">seq1\n{}\n", seq))?;
+    let table = assert_fs::NamedTempFile::new("overlaps.tsv")?;
+
+    Command::cargo_bin("circkit")?
+        .arg("orfs")
+        .arg(input.path())
+        .arg("--strand")
+        .arg("forward")
+        .arg("--min-length")
+        .arg("0")
+        .arg("--orf-overlap-frames")
+        .arg("--overlaps-table")
+        .arg(table.path())
+        .assert()
+        .success();
+    table.assert(format!(
+        "seq_id\torf_id\tother_orf_id\tframe\tother_frame\toverlap\n{}",
+        expected
+    ));
+
+    Ok(())
+}
+
+/// Rotating the input doesn't move the ORFs when their coordinates are relative to the canonical origin.
+#[test]
+fn origin_normalize() -> anyhow::Result<()> {
+    use assert_fs::prelude::*;
+
+    let orfs = |seq: &str| -> anyhow::Result<String> {
+        let input = assert_fs::NamedTempFile::new("in.fasta")?;
+        input.write_str(&format!(">seq1\n{}\n", seq))?;
+        let output = Command::cargo_bin("circkit")?
+            .arg("orfs")
+            .arg(input.path())
+            .arg("--min-length")
+            .arg("0")
+            .arg("--origin-normalize")
+            .output()?;
+        assert!(output.status.success());
+        Ok(String::from_utf8(output.stdout)?)
+    };
+
+    let expected = orfs("ATGCATGCCCCTAACCCCTAGCTAACCCCC")?;
+    assert!(expected.contains(">seq1_ORF"));
+    assert_eq!(orfs("CTAACCCCTAGCTAACCCCCATGCATGCCC")?, expected);
+
+    Ok(())
+}
+
+/// Each strand is only searched when asked for, so its ORFs never show up otherwise.
+#[rstest]
+#[case("forward", &["seq1_ORF0", "seq1_ORF4"])]
+#[case("reverse", &["seq1_RC_ORF24", "seq1_RC_ORF28"])]
+#[case("both", &["seq1_ORF0", "seq1_ORF4", "seq1_RC_ORF24", "seq1_RC_ORF28"])]
+fn only_requested_strands(#[case] strand: &str, #[case] expected: &[&str]) -> anyhow::Result<()> {
+    use assert_fs::prelude::*;
+
+    let input = assert_fs::NamedTempFile::new("in.fasta")?;
+    input.write_str(">seq1\nATGCATGCCCCTAACCCCTAGCTAACCCCC\n")?;
+
+    let output = Command::cargo_bin("circkit")?
+        .arg("orfs")
+        .arg(input.path())
+        .arg("--min-length")
+        .arg("0")
+        .arg("--strand")
+        .arg(strand)
+        .output()?;
+    assert!(output.status.success());
+
+    let ids = String::from_utf8(output.stdout)?
+        .lines()
+        .filter_map(|line| line.strip_prefix('>'))
+        .map(String::from)
+        .collect::<Vec<_>>();
+    assert_eq!(ids, expected);
+
+    Ok(())
+}
+
+#[test]
+fn translate_adds_protein_column() -> anyhow::Result<()> {
+    use assert_fs::prelude::*;
+
+    let input = assert_fs::NamedTempFile::new("in.fasta")?;
+    input.write_str(">seq1\nATGAAAAAATAACC\n")?;
+    let table = assert_fs::NamedTempFile::new("table.csv")?;
+
+    Command::cargo_bin("circkit")?
+        .arg("orfs")
+        .arg(input.path())
+        .arg("--strand")
+        .arg("forward")
+        .arg("--min-length")
+        .arg("0")
+        .arg("--max-wraps")
+        .arg("0")
+        .arg("--translate")
+        .arg("--table")
+        .arg(table.path())
+        .assert()
+        .success()
+        .stdout(">seq1_ORF0\nMKK\n");
+
+    let mut reader = csv::Reader::from_path(table.path())?;
+    assert_eq!(reader.headers()?.iter().next_back(), Some("protein"));
+    let rows = reader.records().collect::<Result<Vec<_>, _>>()?;
+    assert_eq!(rows.len(), 1);
+    assert_eq!(&rows[0][rows[0].len() - 1], "MKK");
+
+    Ok(())
+}
+
+/// TGA is a stop codon in the standard code but tryptophan in Mycoplasma (table 4), so the ORF reads through it to TAA.
+#[rstest]
+#[case(&[], ">seq1_ORF0\nMKW\n")]
+#[case(&["--trans-table", "1"], ">seq1_ORF0\nMKW\n")]
+#[case(&["--trans-table", "4"], ">seq1_ORF0\nMKWWK\n")]
+fn trans_table(#[case] args: &[&str], #[case] expected: &str) -> anyhow::Result<()> {
+    use assert_fs::prelude::*;
+
+    let input = assert_fs::NamedTempFile::new("in.fasta")?;
+    input.write_str(">seq1\nATGAAATGGTGAAAATAACC\n")?;
+
+    Command::cargo_bin("circkit")?
+        .arg("orfs")
+        .arg(input.path())
+        .arg("--strand")
+        .arg("forward")
+        .arg("--min-length")
+        .arg("0")
+        .arg("--max-wraps")
+        .arg("0")
+        .arg("--protein")
+        .args(args)
+        .assert()
+        .success()
+        .stdout(expected.to_string());
+
+    Ok(())
+}
+
+#[rstest]
+#[case(&["--trans-table", "7"])]
+#[case(&["--trans-table", "11", "--start-codons", "ATG"])]
+fn invalid_trans_table(#[case] args: &[&str]) -> anyhow::Result<()> {
+    use assert_fs::prelude::*;
+
+    let input = assert_fs::NamedTempFile::new("in.fasta")?;
+    input.write_str(">seq1\nATGAAATAA\n")?;
+
+    Command::cargo_bin("circkit")?
+        .arg("orfs")
+        .arg(input.path())
+        .args(args)
+        .assert()
+        .code(2);
+
+    Ok(())
+}
+
+/// Both ORFs cross the origin, so each is split into two CDS lines sharing its ID.
+#[test]
+fn gff3() -> anyhow::Result<()> {
+    use assert_fs::prelude::*;
+
+    let input = assert_fs::NamedTempFile::new("in.fasta")?;
+    input.write_str(">seq1 desc\nCCATGAAATAACCCCCCCCCCCCCCCTTATTTCATGG\n")?;
+
+    Command::cargo_bin("circkit")?
+        .arg("orfs")
+        .arg(input.path())
+        .arg("--min-length")
+        .arg("0")
+        .arg("--format")
+        .arg("gff3")
+        .assert()
+        .success()
+        .stdout(
+            [
+                "##gff-version 3",
+                "seq1\tcirckit\tregion\t1\t37\t.\t.\t.\tID=seq1;Is_circular=true",
+                "seq1\tcirckit\tCDS\t34\t37\t.\t+\t0\tID=seq1_ORF33",
+                "seq1\tcirckit\tCDS\t1\t8\t.\t+\t2\tID=seq1_ORF33",
+                "seq1\tcirckit\tCDS\t1\t4\t.\t-\t0\tID=seq1_RC_ORF33",
+                "seq1\tcirckit\tCDS\t30\t37\t.\t-\t2\tID=seq1_RC_ORF33",
+                "",
+            ]
+            .join("\n"),
+        );
+
+    Ok(())
+}
+
+/// ATGC starts and TAAG stops, so the first ORF is two quadruplet codons before its stop, and the second one's stop crosses the origin.
+#[test]
+fn codon_size() -> anyhow::Result<()> {
+    use assert_fs::prelude::*;
+
+    let input = assert_fs::NamedTempFile::new("in.fasta")?;
+    input.write_str(">seq1\nCCATGCGGGGTAAGCC\n>seq2\nAGCCCCATGCCCCCCCCCTA\n")?;
+    let table = assert_fs::NamedTempFile::new("table.csv")?;
+
+    Command::cargo_bin("circkit")?
+        .arg("orfs")
+        .arg(input.path())
+        .arg("--codon-size")
+        .arg("4")
+        .arg("--start-codons")
+        .arg("ATGC")
+        .arg("--stop-codons")
+        .arg("TAAG")
+        .arg("--strand")
+        .arg("forward")
+        .arg("--min-length")
+        .arg("0")
+        .arg("--include-stop")
+        .arg("--table")
+        .arg(table.path())
+        .assert()
+        .success()
+        .stdout(">seq1_ORF2\nATGCGGGGTAAG\n>seq2_ORF6\nATGCCCCCCCCCTAAG\n");
+
+    let mut reader = csv::Reader::from_path(table.path())?;
+    let rows = reader
+        .records()
+        .map(|row| row.map(|row| (row[2].to_string(), row[3].to_string(), row[5].to_string())))
+        .collect::<Result<Vec<_>, _>>()?;
+    assert_eq!(
+        rows,
+        [
+            ("2".to_string(), "10".to_string(), "0".to_string()),
+            ("6".to_string(), "18".to_string(), "1".to_string()),
+        ]
+    );
+
+    Ok(())
+}
+
+#[rstest]
+// the default start codon is a triplet
+#[case(&["--codon-size", "4"])]
+#[case(&["--codon-size", "4", "--start-codons", "ATGC", "--stop-codons", "TAAG", "--protein"])]
+#[case(&["--codon-size", "4", "--start-codons", "ATGC", "--stop-codons", "TAAG", "--format", "gff3"])]
+#[case(&["--codon-size", "0"])]
+fn invalid_codon_size(#[case] args: &[&str]) -> anyhow::Result<()> {
+    use assert_fs::prelude::*;
+
+    let input = assert_fs::NamedTempFile::new("in.fasta")?;
+    input.write_str(">seq1\nATGCGGGGTAAG\n")?;
+
+    Command::cargo_bin("circkit")?
+        .arg("orfs")
+        .arg(input.path())
+        .args(args)
+        .assert()
+        .code(2);
+
+    Ok(())
+}
+
+/// Records shorter than a codon have no ORFs, and shouldn't stop the rest of the file from being searched.
+#[rstest]
+#[case(&[])]
+#[case(&["--trans-table", "11"])]
+#[case(&["--ambiguous-codons"])]
+// table 2 starts with ATA, which reading around AT more than once would make up
+#[case(&["--ambiguous-codons", "--trans-table", "2", "--no-stop-required"])]
+fn records_shorter_than_a_codon(#[case] args: &[&str]) -> anyhow::Result<()> {
+    use assert_fs::prelude::*;
+
+    let input = assert_fs::NamedTempFile::new("in.fasta")?;
+    input.write_str(">empty\n\n>short\nAT\n>seq1\nATGCCCTAA\n")?;
+
+    Command::cargo_bin("circkit")?
+        .arg("orfs")
+        .arg(input.path())
+        .arg("--strand")
+        .arg("forward")
+        .arg("--min-length")
+        .arg("0")
+        .args(args)
+        .assert()
+        .success()
+        .stdout(">seq1_ORF0\nATGCCC\n");
+
+    Ok(())
+}
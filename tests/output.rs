@@ -0,0 +1,337 @@
+use assert_cmd::prelude::*; // Add methods on commands
+use assert_fs::prelude::*; // Add methods on paths
+use predicates::prelude::*; // Used for writing assertions
+use std::io::Read;
+use std::process::Command; // Run programs
+
+#[test]
+fn creates_missing_output_directories() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = assert_fs::TempDir::new()?;
+    let output = dir.child("nested/does/not/exist/out.fasta.gz");
+    let table = dir.child("other/nested/table.csv");
+
+    Command::cargo_bin("circkit")?
+        .arg("monomerize")
+        .arg("tests/examples/min_overlap/in.fasta")
+        .arg("-o")
+        .arg(output.path())
+        .arg("--table")
+        .arg(table.path())
+        .assert()
+        .success();
+
+    output.assert(predicate::path::is_file());
+    table.assert(predicate::str::starts_with(
+        "id,original_length,monomer_length",
+    ));
+    Ok(())
+}
+
+#[test]
+fn output_directory_is_reported() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = assert_fs::TempDir::new()?;
+
+    // a directory can't be created where a file already is
+    let file = dir.child("file");
+    file.write_str("")?;
+
+    Command::cargo_bin("circkit")?
+        .arg("cat")
+        .arg("tests/examples/simple/in.fasta")
+        .arg("-o")
+        .arg(file.child("nested/out.fasta").path())
+        .assert()
+        .code(3)
+        .stderr(predicate::str::contains(
+            "Could not create output directory",
+        ));
+    Ok(())
+}
+
+#[test]
+fn null_output_writes_nothing() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = assert_fs::TempDir::new()?;
+    let output = dir.child("out.fasta");
+
+    Command::cargo_bin("circkit")?
+        .arg("canonicalize")
+        .arg("tests/examples/multiple_sequences/in.fasta")
+        .arg("-o")
+        .arg(output.path())
+        .arg("--null-output")
+        .assert()
+        .success()
+        .stdout(predicate::str::is_empty());
+    output.assert(predicate::path::missing());
+
+    // the records are still processed and counted
+    Command::cargo_bin("circkit")?
+        .arg("canonicalize")
+        .arg("tests/examples/multiple_sequences/in.fasta")
+        .arg("--null-output")
+        .arg("--fail-on-empty")
+        .assert()
+        .success()
+        .stdout(predicate::str::is_empty());
+    Ok(())
+}
+
+/// The names of the files in a directory.
+fn dir_entries(dir: &std::path::Path) -> Vec<String> {
+    let mut entries = std::fs::read_dir(dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+        .collect::<Vec<_>>();
+    entries.sort();
+    entries
+}
+
+#[test]
+fn atomic_output_is_moved_into_place() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = assert_fs::TempDir::new()?;
+    let output = dir.child("out.fasta.gz");
+    let table = dir.child("table.tsv");
+
+    Command::cargo_bin("circkit")?
+        .arg("monomerize")
+        .arg("tests/examples/min_overlap/in.fasta")
+        .arg("-o")
+        .arg(output.path())
+        .arg("--table")
+        .arg(table.path())
+        .arg("--atomic")
+        .assert()
+        .success();
+
+    // no temporary files are left behind
+    assert_eq!(dir_entries(dir.path()), ["out.fasta.gz", "table.tsv"]);
+
+    // the compressed output was finished before it was moved
+    let plain = Command::cargo_bin("circkit")?
+        .arg("monomerize")
+        .arg("tests/examples/min_overlap/in.fasta")
+        .output()?;
+    let mut decompressed = Vec::new();
+    niffler::from_path(output.path())?
+        .0
+        .read_to_end(&mut decompressed)?;
+    assert_eq!(decompressed, plain.stdout);
+    Ok(())
+}
+
+#[test]
+fn atomic_output_is_discarded_on_failure() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = assert_fs::TempDir::new()?;
+    let input = dir.child("in.fasta");
+    // the second record makes decat fail after the first has been written
+    input.write_str(">even\nACGTACGT\n>odd\nACGTACGTA\n")?;
+    let output = dir.child("out.fasta");
+
+    Command::cargo_bin("circkit")?
+        .arg("decat")
+        .arg(input.path())
+        .arg("--strict")
+        .arg("-o")
+        .arg(output.path())
+        .arg("--atomic")
+        .assert()
+        .code(1);
+
+    assert_eq!(dir_entries(dir.path()), ["in.fasta"]);
+    Ok(())
+}
+
+#[test]
+fn trim_to_length_caps_sequences() -> Result<(), Box<dyn std::error::Error>> {
+    let input = assert_fs::NamedTempFile::new("in.fasta")?;
+    // uniq writes the sequence as it came in, so the line breaks must not count towards the length
+    input.write_str(">long\nACG\nTACGTA\n>short\nAC\n")?;
+
+    Command::cargo_bin("circkit")?
+        .arg("uniq")
+        .arg(input.path())
+        .arg("--trim-to-length")
+        .arg("5")
+        .arg("--append-length")
+        .assert()
+        .success()
+        .stdout(">long length=5\nACG\nTA\n>short length=2\nAC\n");
+
+    Command::cargo_bin("circkit")?
+        .arg("uniq")
+        .arg(input.path())
+        .arg("--trim-to-length")
+        .arg("0")
+        .assert()
+        .code(2);
+    Ok(())
+}
+
+#[test]
+fn chunk_bytes_splits_between_records() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = assert_fs::TempDir::new()?;
+    let input = dir.child("in.fasta");
+    // ten records of 15 bytes each
+    let records = (0..10)
+        .map(|i| format!(">s{}\nACGTACGTAC\n", i))
+        .collect::<String>();
+    input.write_str(&records)?;
+
+    // two records fit into 40 bytes but three don't, so there are five files
+    Command::cargo_bin("circkit")?
+        .arg("pipeline")
+        .arg(input.path())
+        .arg("--transform")
+        .arg("uppercase")
+        .arg("--chunk-bytes")
+        .arg("40")
+        .arg("-o")
+        .arg(dir.child("out.fasta.gz").path())
+        .assert()
+        .success();
+
+    let mut chunks = String::new();
+    for n in 1..=5 {
+        let chunk = dir.child(format!("out.{}.fasta.gz", n));
+        let mut contents = String::new();
+        niffler::from_path(chunk.path())?
+            .0
+            .read_to_string(&mut contents)?;
+        assert_eq!(contents.len(), 30);
+        chunks.push_str(&contents);
+    }
+    dir.child("out.6.fasta.gz")
+        .assert(predicate::path::missing());
+    assert_eq!(chunks, records);
+    Ok(())
+}
+
+#[test]
+fn chunk_paths() {
+    use circkit_cli::utils::chunk_path;
+    use std::path::Path;
+
+    assert_eq!(
+        chunk_path(Path::new("a/out.fasta"), 2),
+        Path::new("a/out.2.fasta")
+    );
+    assert_eq!(
+        chunk_path(Path::new("out.fa.zst"), 1),
+        Path::new("out.1.fa.zst")
+    );
+    assert_eq!(chunk_path(Path::new("out.gz"), 1), Path::new("out.1.gz"));
+    assert_eq!(chunk_path(Path::new("out"), 3), Path::new("out.3"));
+}
+
+#[test]
+fn chunk_bytes_requires_output_file() -> Result<(), Box<dyn std::error::Error>> {
+    Command::cargo_bin("circkit")?
+        .arg("cat")
+        .arg("tests/examples/simple/in.fasta")
+        .arg("--chunk-bytes")
+        .arg("10")
+        .assert()
+        .code(2);
+    Ok(())
+}
+
+/// An unwritable --histogram path is an IO error like any other output, not a crash.
+#[test]
+fn unwritable_histogram_is_io_error() -> Result<(), Box<dyn std::error::Error>> {
+    // a path inside a regular file can't be created
+    let file = assert_fs::NamedTempFile::new("not_a_dir")?;
+    file.touch()?;
+
+    Command::cargo_bin("circkit")?
+        .arg("canonicalize")
+        .arg("tests/examples/simple/in.fasta")
+        .arg("--histogram")
+        .arg(file.path().join("lengths.tsv"))
+        .assert()
+        .code(3);
+    Ok(())
+}
+
+#[test]
+fn histogram_counts_every_record() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = assert_fs::TempDir::new()?;
+    let input = dir.child("in.fasta");
+    input
+        .write_str(">a\n\n>b\nA\n>c\nACG\n>d\nACGT\n>e\nACGTACGTACGTACGTACGTACGT\n>f\nACGTACG\n")?;
+    let histogram = dir.child("lengths.tsv");
+
+    Command::cargo_bin("circkit")?
+        .arg("canonicalize")
+        .arg(input.path())
+        .arg("--histogram")
+        .arg(histogram.path())
+        .assert()
+        .success();
+
+    histogram.assert(
+        "min_length\tmax_length\tcount\n\
+         0\t0\t1\n\
+         1\t1\t1\n\
+         2\t3\t1\n\
+         4\t7\t2\n\
+         8\t15\t0\n\
+         16\t31\t1\n",
+    );
+    let total: usize = std::fs::read_to_string(histogram.path())?
+        .lines()
+        .skip(1)
+        .map(|line| line.rsplit('\t').next().unwrap().parse::<usize>().unwrap())
+        .sum();
+    assert_eq!(total, 6);
+    Ok(())
+}
+
+#[test]
+fn histogram_buckets() {
+    use circkit_cli::utils::LengthHistogram;
+
+    assert_eq!(LengthHistogram::bucket_range(0), (0, 0));
+    assert_eq!(LengthHistogram::bucket_range(1), (1, 1));
+    assert_eq!(LengthHistogram::bucket_range(11), (1024, 2047));
+    assert_eq!(
+        LengthHistogram::bucket_range(usize::BITS as usize),
+        (1 << (usize::BITS - 1), usize::MAX)
+    );
+
+    let histogram = LengthHistogram::default();
+    histogram.add(usize::MAX);
+    histogram.add(1 << (usize::BITS - 1));
+    assert_eq!(
+        histogram.buckets(),
+        vec![(1 << (usize::BITS - 1), usize::MAX, 2)]
+    );
+}
+
+#[rstest::rstest]
+#[case("out.txt", &[])]
+#[case("out.txt.gz", &[])]
+#[case("out.fasta", &["--output-format", "txt"])]
+fn txt_output(#[case] name: &str, #[case] args: &[&str]) -> Result<(), Box<dyn std::error::Error>> {
+    let dir = assert_fs::TempDir::new()?;
+    let input = dir.child("in.fasta");
+    input.write_str(">a\nACG\nTT\n>empty\n>b\nCC\n")?;
+    let output = dir.child(name);
+
+    Command::cargo_bin("circkit")?
+        .arg("canonicalize")
+        .arg("--linear")
+        .arg(input.path())
+        .arg("-o")
+        .arg(output.path())
+        .args(args)
+        .assert()
+        .success();
+
+    // one sequence per line, with empty records kept as empty lines
+    let mut contents = String::new();
+    niffler::from_path(output.path())?
+        .0
+        .read_to_string(&mut contents)?;
+    assert_eq!(contents, "AACGT\n\nCC\n");
+    Ok(())
+}
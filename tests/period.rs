@@ -0,0 +1,54 @@
+use assert_cmd::prelude::*; // Add methods on commands
+use assert_fs::prelude::*; // Add methods on paths
+use rstest::rstest; // Parameterized tests
+use std::process::Command; // Run programs
+
+#[rstest]
+fn clean_trimer(#[values("1", "2", "4")] threads: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let file = assert_fs::NamedTempFile::new("in.fasta")?;
+    file.write_str(concat!(
+        ">trimer\n",
+        "ATGCCGTAATGCCGTAATGCCGTA\n",
+        ">monomer\n",
+        "ATGCCGTA\n",
+    ))?;
+
+    Command::cargo_bin("circkit")?
+        .arg("period")
+        .arg(file.path())
+        .arg("--threads")
+        .arg(threads)
+        .assert()
+        .success()
+        .stdout("id,length,period,copies\ntrimer,24,8,3\nmonomer,8,8,1\n");
+    Ok(())
+}
+
+#[rstest]
+#[case("0", "mutated,24,24,1\n")]
+#[case("1", "mutated,24,8,3\n")]
+fn tolerant_period(
+    #[case] max_mismatch: &str,
+    #[case] expected: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let file = assert_fs::NamedTempFile::new("in.fasta")?;
+    // the second copy has a single substitution
+    file.write_str(">mutated\nATGCCGTAATGACGTAATGCCGTA\n")?;
+    let output = assert_fs::NamedTempFile::new("out.tsv")?;
+
+    Command::cargo_bin("circkit")?
+        .arg("period")
+        .arg(file.path())
+        .arg("--max-mismatch")
+        .arg(max_mismatch)
+        .arg("-o")
+        .arg(output.path())
+        .assert()
+        .success();
+
+    output.assert(format!(
+        "id\tlength\tperiod\tcopies\n{}",
+        expected.replace(',', "\t")
+    ));
+    Ok(())
+}
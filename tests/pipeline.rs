@@ -0,0 +1,71 @@
+use assert_cmd::prelude::*; // Add methods on commands
+use predicates::prelude::*; // Used for writing assertions
+use rstest::rstest; // Parameterized tests
+use std::process::Command; // Run programs
+mod common;
+
+#[rstest]
+fn chained_transforms_equal_sequential_application(
+    #[values("1", "2", "4")] threads: &str,
+) -> anyhow::Result<()> {
+    let input = "tests/examples/multiple_sequences_split_lines/in.fasta";
+    let chained = assert_fs::NamedTempFile::new("chained.fasta")?;
+    let revcomped = assert_fs::NamedTempFile::new("revcomped.fasta")?;
+    let sequential = assert_fs::NamedTempFile::new("sequential.fasta")?;
+
+    let run = |input: &std::path::Path, transform: &str, output: &std::path::Path| {
+        Command::cargo_bin("circkit")
+            .unwrap()
+            .arg("pipeline")
+            .arg(input)
+            .arg("--threads")
+            .arg(threads)
+            .arg("--transform")
+            .arg(transform)
+            .arg("-o")
+            .arg(output)
+            .assert()
+            .success();
+    };
+
+    run(input.as_ref(), "revcomp,canonicalize", chained.path());
+    run(input.as_ref(), "revcomp", revcomped.path());
+    run(revcomped.path(), "canonicalize", sequential.path());
+
+    assert_eq!(
+        std::fs::read_to_string(chained.path())?,
+        std::fs::read_to_string(sequential.path())?
+    );
+
+    Ok(())
+}
+
+#[rstest]
+#[case("rotate_5", "rotate:5")]
+#[case("rotate_minus_5", "rotate:-5")]
+fn rotate_transform_matches_rotate(
+    #[case] directory: &str,
+    #[case] transform: &str,
+) -> anyhow::Result<()> {
+    let mut cmd = Command::cargo_bin("circkit")?;
+    cmd.arg("pipeline").arg("--transform").arg(transform);
+    common::check_fasta(directory, &mut cmd)?;
+    Ok(())
+}
+
+#[rstest]
+#[case("reverse")]
+#[case("rotate")]
+#[case("rotate:five")]
+#[case("uppercase:1")]
+fn invalid_transform(#[case] transform: &str) -> anyhow::Result<()> {
+    Command::cargo_bin("circkit")?
+        .arg("pipeline")
+        .arg("tests/examples/simple/in.fasta")
+        .arg("--transform")
+        .arg(transform)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Invalid value"));
+    Ok(())
+}
@@ -0,0 +1,38 @@
+use assert_cmd::prelude::*; // Add methods on commands
+use predicates::prelude::*; // Used for writing assertions
+use rstest::rstest; // Parameterized tests
+use std::process::Command; // Run programs
+
+#[rstest]
+fn profile_prints_timing_summary(
+    #[values("canonicalize", "uniq", "monomerize", "orfs", "cat", "rotate")] subcommand: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = Command::cargo_bin("circkit")?;
+
+    cmd.arg(subcommand)
+        .arg("tests/examples/multiple_sequences/in.fasta")
+        .arg("--profile");
+    if subcommand == "rotate" {
+        cmd.arg("--bases").arg("1");
+    }
+    cmd.assert().success().stderr(
+        predicate::str::contains("Profile (wall-clock seconds):")
+            .and(predicate::str::contains("read"))
+            .and(predicate::str::contains("compute"))
+            .and(predicate::str::contains("write"))
+            .and(predicate::str::contains("total")),
+    );
+    Ok(())
+}
+
+#[test]
+fn no_timing_summary_without_profile() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = Command::cargo_bin("circkit")?;
+
+    cmd.arg("canonicalize")
+        .arg("tests/examples/multiple_sequences/in.fasta");
+    cmd.assert()
+        .success()
+        .stderr(predicate::str::contains("Profile").not());
+    Ok(())
+}
@@ -0,0 +1,70 @@
+use assert_cmd::Command; // Run programs
+use assert_fs::prelude::*; // Add methods on paths
+use rstest::rstest; // Parameterized tests
+
+/// Six records whose ids are their 1-based position in the file.
+fn six_records() -> Result<assert_fs::NamedTempFile, Box<dyn std::error::Error>> {
+    let file = assert_fs::NamedTempFile::new("in.fasta")?;
+    file.write_str(
+        &(1..=6)
+            .map(|i| format!(">seq{}\nACGTA{}\n", i, "C".repeat(i)))
+            .collect::<String>(),
+    )?;
+    Ok(file)
+}
+
+#[rstest]
+#[case(&["--skip", "2", "--limit", "2"], &["seq3", "seq4"])]
+#[case(&["--skip", "4"], &["seq5", "seq6"])]
+#[case(&["--limit", "1"], &["seq1"])]
+#[case(&["--skip", "5", "--limit", "10"], &["seq6"])]
+#[case(&["--skip", "6"], &[])]
+fn processes_only_the_range(
+    #[values("cat", "canonicalize")] command: &str,
+    #[case] args: &[&str],
+    #[case] expected: &[&str],
+) -> Result<(), Box<dyn std::error::Error>> {
+    let file = six_records()?;
+
+    let output = Command::cargo_bin("circkit")?
+        .arg(command)
+        .arg(file.path())
+        .args(args)
+        .output()?;
+    assert!(output.status.success());
+
+    let ids: Vec<String> = String::from_utf8(output.stdout)?
+        .lines()
+        .filter_map(|line| line.strip_prefix('>'))
+        .map(String::from)
+        .collect();
+    assert_eq!(ids, expected);
+    Ok(())
+}
+
+#[rstest]
+fn skips_all_n_records(
+    #[values("cat", "canonicalize", "uniq")] command: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let file = assert_fs::NamedTempFile::new("in.fasta")?;
+    file.write_str(">seq1\nACGT\n>all_n\nNNNN\nnnRY\n>empty\n\n>seq2\nCC\n")?;
+
+    let output = Command::cargo_bin("circkit")?
+        .arg(command)
+        .arg(file.path())
+        .arg("--skip-all-n")
+        .arg("-v")
+        .output()?;
+    assert!(output.status.success());
+
+    let ids: Vec<String> = String::from_utf8(output.stdout)?
+        .lines()
+        .filter_map(|line| line.strip_prefix('>'))
+        .map(String::from)
+        .collect();
+    assert_eq!(ids, ["seq1", "empty", "seq2"]);
+    assert!(
+        String::from_utf8(output.stderr)?.contains("Skipping all_n: it has no unambiguous bases")
+    );
+    Ok(())
+}
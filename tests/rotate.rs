@@ -24,3 +24,127 @@ fn rotate_percent(#[case] directory: &str, #[case] percent: f64) -> anyhow::Resu
     common::check_fasta(directory, &mut cmd)?;
     Ok(())
 }
+
+/// Rotating by a fraction should give exactly the same result as rotating by the equivalent number of bases.
+#[rstest]
+#[case(40, 0.5, 20)]
+#[case(40, -0.5, -20)]
+#[case(40, 0.25, 10)]
+#[case(40, -0.25, -10)]
+#[case(41, 0.25, 10)]
+#[case(41, -0.25, -10)]
+#[case(41, 1.5, 61)]
+fn percent_matches_bases(
+    #[case] len: usize,
+    #[case] percent: f64,
+    #[case] bases: i64,
+) -> anyhow::Result<()> {
+    use assert_fs::prelude::*;
+
+    assert_eq!(circkit_cli::rotate::percent_to_bases(len, percent), bases);
+
+    let seq: String = (0..len).map(|i| b"ACGT"[i % 7 % 4] as char).collect();
+    let input = assert_fs::NamedTempFile::new("in.fasta")?;
+    input.write_str(&format!(">seq\n{}\n", seq))?;
+
+    let by_percent = Command::cargo_bin("circkit")?
+        .arg("rotate")
+        .arg(input.path())
+        .arg("--percent")
+        .arg(percent.to_string())
+        .output()?;
+    let by_bases = Command::cargo_bin("circkit")?
+        .arg("rotate")
+        .arg(input.path())
+        .arg("--bases")
+        .arg(bases.to_string())
+        .output()?;
+
+    assert!(by_percent.status.success());
+    assert!(by_bases.status.success());
+    assert_eq!(by_percent.stdout, by_bases.stdout);
+    Ok(())
+}
+
+#[rstest]
+#[case("CCGATA", "A", "ATACCG")]
+#[case("ccgata", "A", "ataccg")]
+#[case("AACCGT", "A", "AACCGT")]
+// the motif spans the origin
+#[case("TACCGA", "AT", "ATACCG")]
+#[case("CCGGTT", "A", "CCGGTT")]
+fn start_with(
+    #[case] seq: &str,
+    #[case] motif: &str,
+    #[case] expected: &str,
+) -> anyhow::Result<()> {
+    use assert_fs::prelude::*;
+
+    let input = assert_fs::NamedTempFile::new("in.fasta")?;
+    input.write_str(&format!(">seq\n{}\n", seq))?;
+
+    Command::cargo_bin("circkit")?
+        .arg("rotate")
+        .arg(input.path())
+        .arg("--start-with")
+        .arg(motif)
+        .assert()
+        .success()
+        .stdout(format!(">seq\n{}\n", expected));
+    Ok(())
+}
+
+proptest::proptest! {
+    /// Rotating by any amount gives a rotation of the original, the same one as rotating by the amount modulo the length.
+    #[test]
+    fn rotation_index_is_in_bounds(len in 1usize..200, bases: i64) {
+        use circkit_cli::rotate::rotation_index;
+
+        let index = rotation_index(len, bases);
+        proptest::prop_assert!(index < len);
+        // rotating right by n moves the base at len - n to the start
+        let expected = (len as i128 - bases as i128).rem_euclid(len as i128) as usize;
+        proptest::prop_assert_eq!(index, expected);
+        // rotating back undoes the rotation
+        if let Some(back) = bases.checked_neg() {
+            proptest::prop_assert_eq!((index + rotation_index(len, back)) % len, 0);
+        }
+    }
+}
+
+#[test]
+fn rotation_by_multiple_of_length_is_identity() {
+    use circkit_cli::rotate::rotation_index;
+
+    assert_eq!(rotation_index(10, 0), 0);
+    assert_eq!(rotation_index(10, 30), 0);
+    assert_eq!(rotation_index(10, -30), 0);
+    // rotating left by 2^63 bases
+    assert_eq!(rotation_index(10, i64::MIN), 8);
+}
+
+/// Empty records are written unchanged rather than dividing by their length.
+#[rstest]
+#[case(&["--bases", "1"], "TACG")]
+#[case(&["--bases", "-1"], "CGTA")]
+#[case(&["--percent", "0.5"], "GTAC")]
+fn empty_record(#[case] args: &[&str], #[case] rotated: &str) -> anyhow::Result<()> {
+    use assert_fs::prelude::*;
+    use circkit_cli::rotate::rotation_index;
+
+    assert_eq!(rotation_index(0, 1), 0);
+    assert_eq!(rotation_index(0, -1), 0);
+
+    let input = assert_fs::NamedTempFile::new("in.fasta")?;
+    input.write_str(">empty\n\n>seq1\nACGT\n")?;
+
+    Command::cargo_bin("circkit")?
+        .arg("rotate")
+        .arg(input.path())
+        .args(args)
+        .assert()
+        .success()
+        .stdout(format!(">empty\n\n>seq1\n{}\n", rotated));
+
+    Ok(())
+}
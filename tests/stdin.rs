@@ -0,0 +1,49 @@
+use assert_cmd::Command; // Run programs with stdin
+use rstest::rstest; // Parameterized tests
+
+#[rstest]
+fn dash_reads_stdin(
+    #[values("canonicalize", "uniq", "cat", "decat", "monomerize", "pipeline")] command: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let input = std::fs::read("tests/examples/multiple_sequences/in.fasta")?;
+    let extra_args: &[&str] = match command {
+        "pipeline" => &["--transform", "revcomp"],
+        _ => &[],
+    };
+
+    let from_file = Command::cargo_bin("circkit")?
+        .arg(command)
+        .args(extra_args)
+        .arg("tests/examples/multiple_sequences/in.fasta")
+        .output()?;
+    assert!(from_file.status.success());
+
+    Command::cargo_bin("circkit")?
+        .arg(command)
+        .args(extra_args)
+        .arg("-")
+        .write_stdin(input)
+        .assert()
+        .success()
+        .stdout(from_file.stdout);
+    Ok(())
+}
+
+#[test]
+fn dash_reads_compressed_stdin() -> Result<(), Box<dyn std::error::Error>> {
+    let input = std::fs::read("tests/examples/compressed_input/in.fasta.gz")?;
+
+    let expected = Command::cargo_bin("circkit")?
+        .arg("cat")
+        .arg("tests/examples/compressed_input/in.fasta")
+        .output()?;
+
+    Command::cargo_bin("circkit")?
+        .arg("cat")
+        .arg("-")
+        .write_stdin(input)
+        .assert()
+        .success()
+        .stdout(expected.stdout);
+    Ok(())
+}
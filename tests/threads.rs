@@ -0,0 +1,105 @@
+use assert_cmd::Command; // Run programs
+use predicates::prelude::*; // Used for writing assertions
+use rstest::rstest;
+
+/// An absurd thread count is capped with a warning, and the output is the same as with a sensible one.
+#[rstest]
+#[case("canonicalize")]
+#[case("uniq")]
+#[case("monomerize")]
+#[case("orfs")]
+fn absurd_thread_count_is_capped(#[case] command: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let expected = Command::cargo_bin("circkit")?
+        .arg(command)
+        .arg("tests/examples/multiple_sequences/in.fasta")
+        .arg("--threads")
+        .arg("1")
+        .output()?
+        .stdout;
+
+    Command::cargo_bin("circkit")?
+        .arg("-v")
+        .arg(command)
+        .arg("tests/examples/multiple_sequences/in.fasta")
+        .arg("--threads")
+        .arg("100000")
+        .assert()
+        .success()
+        .stdout(expected)
+        .stderr(predicate::str::contains("100000 threads were requested"));
+    Ok(())
+}
+
+/// Every command that processes records in parallel takes the same thread options.
+#[rstest]
+#[case(&["monomerize"])]
+#[case(&["cat"])]
+#[case(&["decat"])]
+#[case(&["canonicalize"])]
+#[case(&["uniq"])]
+#[case(&["rotate", "--bases", "1"])]
+#[case(&["orfs"])]
+#[case(&["pipeline", "--transform", "revcomp"])]
+#[case(&["period"])]
+#[case(&["validate"])]
+#[case(&["dotplot"])]
+fn parallel_options_parse(#[case] args: &[&str]) {
+    use circkit_cli::commands::{Cli, Command, ParallelOptions};
+    use clap::Parser;
+
+    let cli = Cli::try_parse_from(["circkit"].iter().chain(args).chain(&[
+        "--threads",
+        "3",
+        "--batch-size",
+        "8",
+    ]))
+    .unwrap();
+    let parallel = match cli.command {
+        Command::Monomerize { parallel, .. }
+        | Command::Cat { parallel, .. }
+        | Command::Decat { parallel, .. }
+        | Command::Canonicalize { parallel, .. }
+        | Command::Uniq { parallel, .. }
+        | Command::Rotate { parallel, .. }
+        | Command::Orfs { parallel, .. }
+        | Command::Pipeline { parallel, .. }
+        | Command::Period { parallel, .. }
+        | Command::Validate { parallel, .. }
+        | Command::Dotplot { parallel, .. } => parallel,
+        Command::GenTestData { .. } => unreachable!(),
+    };
+    assert_eq!(
+        parallel,
+        ParallelOptions {
+            threads: 3,
+            batch_size: 8
+        }
+    );
+}
+
+/// The commands that used to run on a single thread write their records in the same order with several.
+#[rstest]
+#[case(&["cat", "--spacer", "N"])]
+#[case(&["decat"])]
+#[case(&["rotate", "--bases", "-3"])]
+fn output_order_is_kept(#[case] args: &[&str]) -> Result<(), Box<dyn std::error::Error>> {
+    let input = (0..500)
+        .map(|i| format!(">seq{}\nACGTTGCA{}\n", i, "ACGT".repeat(i % 7 + 1)))
+        .collect::<String>();
+    let expected = Command::cargo_bin("circkit")?
+        .args(args)
+        .args(["--threads", "1"])
+        .write_stdin(input.clone())
+        .output()?
+        .stdout;
+    assert!(!expected.is_empty());
+
+    Command::cargo_bin("circkit")?
+        .args(args)
+        .args(["--threads", "4", "--batch-size", "2"])
+        .write_stdin(input)
+        .assert()
+        .success()
+        .stdout(expected);
+    Ok(())
+}
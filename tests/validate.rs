@@ -0,0 +1,66 @@
+use assert_cmd::Command; // Run programs
+use assert_fs::prelude::*; // Add methods on paths
+use predicates::prelude::*; // Used for writing assertions
+
+const INPUT: &str = concat!(
+    ">good\nACGTTGCAAGGCTTACG\n",
+    ">dimer\nACGTTGCAAGGCTTACGACGTTGCAAGGCTTACG\n",
+    ">invalid\nACGT*XACGTAGGCA\n",
+    ">short\nAC\n",
+);
+
+const REPORT: &str = concat!(
+    "id,length,valid_bases,canonical_idempotent,monomer,length_ok,pass\n",
+    "good,17,true,true,true,true,true\n",
+    "dimer,34,true,true,false,true,false\n",
+    "invalid,15,false,true,true,true,false\n",
+    "short,2,true,true,true,false,false\n",
+);
+
+#[test]
+fn reports_each_record() -> Result<(), Box<dyn std::error::Error>> {
+    let input = assert_fs::NamedTempFile::new("in.fasta")?;
+    input.write_str(INPUT)?;
+
+    Command::cargo_bin("circkit")?
+        .arg("validate")
+        .arg(input.path())
+        .arg("--min-length")
+        .arg("5")
+        .assert()
+        .success()
+        .stdout(REPORT);
+    Ok(())
+}
+
+#[test]
+fn strict_fails_after_writing_the_report() -> Result<(), Box<dyn std::error::Error>> {
+    let input = assert_fs::NamedTempFile::new("in.fasta")?;
+    input.write_str(INPUT)?;
+    let output = assert_fs::NamedTempFile::new("report.tsv")?;
+
+    Command::cargo_bin("circkit")?
+        .arg("validate")
+        .arg(input.path())
+        .arg("--min-length")
+        .arg("5")
+        .arg("--strict")
+        .arg("-o")
+        .arg(output.path())
+        .assert()
+        .code(1)
+        .stderr(predicate::str::contains("3 of 4 records failed validation"));
+    // a .tsv path gets a TSV, while stdout gets a CSV like every other table
+    output.assert(REPORT.replace(',', "\t"));
+
+    // only the good record is left, so strict mode passes
+    let good = assert_fs::NamedTempFile::new("good.fasta")?;
+    good.write_str(">good\nACGTTGCAAGGCTTACG\n")?;
+    Command::cargo_bin("circkit")?
+        .arg("validate")
+        .arg(good.path())
+        .arg("--strict")
+        .assert()
+        .success();
+    Ok(())
+}